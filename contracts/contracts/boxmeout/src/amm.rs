@@ -1,7 +1,13 @@
 // contracts/amm.rs - Automated Market Maker for Outcome Shares
 // Enables trading YES/NO outcome shares with dynamic odds pricing (Polymarket model)
 
-use soroban_sdk::{contract, contractevent, contractimpl, token, Address, BytesN, Env, Symbol};
+use soroban_sdk::{
+    contract, contracterror, contractevent, contractimpl, contracttype, token, Address, BytesN,
+    Env, Symbol,
+};
+
+use crate::helpers::{extend_instance, extend_persistent};
+use crate::upgrade::{self, PendingUpgrade, UpgradeError};
 
 #[contractevent]
 pub struct AmmInitializedEvent {
@@ -47,23 +53,87 @@ pub struct LiquidityRemovedEvent {
     pub no_amount: u128,
 }
 
-// Storage keys
-const ADMIN_KEY: &str = "admin";
-const FACTORY_KEY: &str = "factory";
-const USDC_KEY: &str = "usdc";
-const MAX_LIQUIDITY_CAP_KEY: &str = "max_liquidity_cap";
-const SLIPPAGE_PROTECTION_KEY: &str = "slippage_protection";
-const TRADING_FEE_KEY: &str = "trading_fee";
-const PRICING_MODEL_KEY: &str = "pricing_model";
-
-// Pool storage keys
-const POOL_YES_RESERVE_KEY: &str = "pool_yes_reserve";
-const POOL_NO_RESERVE_KEY: &str = "pool_no_reserve";
-const POOL_EXISTS_KEY: &str = "pool_exists";
-const POOL_K_KEY: &str = "pool_k";
-const POOL_LP_SUPPLY_KEY: &str = "pool_lp_supply";
-const POOL_LP_TOKENS_KEY: &str = "pool_lp_tokens";
-const USER_SHARES_KEY: &str = "user_shares";
+/// Storage schema version written by this code
+const SCHEMA_VERSION: u32 = 1;
+
+/// Storage keys
+///
+/// Contract-wide config lives in instance storage, pool and position
+/// entries in persistent storage. Variant names and field order are part of
+/// the on-chain layout; `tests/storage_layout_test.rs` pins them.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum DataKey {
+    Admin,
+    Factory,
+    Usdc,
+    MaxLiquidityCap,
+    SlippageProtection,
+    TradingFee,
+    PricingModel,
+    PoolExists(BytesN<32>),
+    PoolYesReserve(BytesN<32>),
+    PoolNoReserve(BytesN<32>),
+    PoolK(BytesN<32>),
+    PoolLpSupply(BytesN<32>),
+    /// LP token balance of (market, provider)
+    PoolLpTokens(BytesN<32>, Address),
+    /// Outcome share balance of (market, holder, outcome)
+    UserShares(BytesN<32>, Address, u32),
+    TradeCount(BytesN<32>),
+}
+
+/// Error codes returned by the AMM
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum AmmError {
+    /// AMM has not been initialized
+    NotInitialized = 1,
+    /// A pool already exists for this market
+    PoolAlreadyExists = 2,
+    /// No pool exists for this market
+    PoolNotFound = 3,
+    /// Amount, shares or LP tokens must be positive
+    InvalidAmount = 4,
+    /// Outcome must be 0 (NO) or 1 (YES)
+    InvalidOutcome = 5,
+    /// Pool reserves are empty or would be emptied
+    InsufficientLiquidity = 6,
+    /// Trade result is below the caller's minimum
+    SlippageExceeded = 7,
+    /// Trade would decrease the constant product
+    InvariantViolation = 8,
+    /// Seller holds fewer shares than requested
+    InsufficientShares = 9,
+    /// Provider holds fewer LP tokens than requested
+    InsufficientLpTokens = 10,
+    /// Withdrawal rounds down to zero on one side
+    WithdrawalTooSmall = 11,
+    /// Withdrawal would remove all remaining liquidity
+    CannotDrainPool = 12,
+    /// AMM is already initialized
+    AlreadyInitialized = 13,
+    /// No upgrade has been proposed
+    NoPendingUpgrade = 100,
+    /// Hash differs from the proposed upgrade
+    WasmHashMismatch = 101,
+    /// Upgrade timelock has not elapsed
+    TimelockNotElapsed = 102,
+    /// Storage schema is already current
+    SchemaUpToDate = 103,
+}
+
+impl From<UpgradeError> for AmmError {
+    fn from(err: UpgradeError) -> Self {
+        match err {
+            UpgradeError::NoPendingUpgrade => AmmError::NoPendingUpgrade,
+            UpgradeError::WasmHashMismatch => AmmError::WasmHashMismatch,
+            UpgradeError::TimelockNotElapsed => AmmError::TimelockNotElapsed,
+            UpgradeError::SchemaUpToDate => AmmError::SchemaUpToDate,
+        }
+    }
+}
 
 // Pool data structure
 #[derive(Clone)]
@@ -89,46 +159,44 @@ impl AMM {
         factory: Address,
         usdc_token: Address,
         max_liquidity_cap: u128,
-    ) {
+    ) -> Result<(), AmmError> {
+        // Check if already initialized
+        if env.storage().instance().has(&DataKey::Admin) {
+            return Err(AmmError::AlreadyInitialized);
+        }
+
         // Verify admin signature
         admin.require_auth();
 
         // Store admin address
-        env.storage()
-            .persistent()
-            .set(&Symbol::new(&env, ADMIN_KEY), &admin);
+        env.storage().instance().set(&DataKey::Admin, &admin);
 
         // Store factory address
-        env.storage()
-            .persistent()
-            .set(&Symbol::new(&env, FACTORY_KEY), &factory);
+        env.storage().instance().set(&DataKey::Factory, &factory);
 
         // Store USDC token contract address
-        env.storage()
-            .persistent()
-            .set(&Symbol::new(&env, USDC_KEY), &usdc_token);
+        env.storage().instance().set(&DataKey::Usdc, &usdc_token);
 
         // Set max_liquidity_cap per market
-        env.storage().persistent().set(
-            &Symbol::new(&env, MAX_LIQUIDITY_CAP_KEY),
-            &max_liquidity_cap,
-        );
+        env.storage()
+            .instance()
+            .set(&DataKey::MaxLiquidityCap, &max_liquidity_cap);
 
         // Set slippage_protection default (2% = 200 basis points)
         env.storage()
-            .persistent()
-            .set(&Symbol::new(&env, SLIPPAGE_PROTECTION_KEY), &200u32);
+            .instance()
+            .set(&DataKey::SlippageProtection, &200u32);
 
         // Set trading fee (0.2% = 20 basis points)
-        env.storage()
-            .persistent()
-            .set(&Symbol::new(&env, TRADING_FEE_KEY), &20u32);
+        env.storage().instance().set(&DataKey::TradingFee, &20u32);
 
         // Set pricing_model (CPMM - Constant Product Market Maker)
-        env.storage().persistent().set(
-            &Symbol::new(&env, PRICING_MODEL_KEY),
-            &Symbol::new(&env, "CPMM"),
-        );
+        env.storage()
+            .instance()
+            .set(&DataKey::PricingModel, &Symbol::new(&env, "CPMM"));
+
+        upgrade::init_schema_version(&env, SCHEMA_VERSION);
+        extend_instance(&env);
 
         // Emit initialization event
         AmmInitializedEvent {
@@ -137,22 +205,29 @@ impl AMM {
             max_liquidity_cap,
         }
         .publish(&env);
+
+        Ok(())
     }
 
     /// Create new liquidity pool for market
-    pub fn create_pool(env: Env, creator: Address, market_id: BytesN<32>, initial_liquidity: u128) {
+    pub fn create_pool(
+        env: Env,
+        creator: Address,
+        market_id: BytesN<32>,
+        initial_liquidity: u128,
+    ) -> Result<(), AmmError> {
         // Require creator auth to transfer USDC
         creator.require_auth();
 
         // Check if pool already exists
-        let pool_exists_key = (Symbol::new(&env, POOL_EXISTS_KEY), market_id.clone());
+        let pool_exists_key = DataKey::PoolExists(market_id.clone());
         if env.storage().persistent().has(&pool_exists_key) {
-            panic!("pool already exists");
+            return Err(AmmError::PoolAlreadyExists);
         }
 
         // Validate initial liquidity
         if initial_liquidity == 0 {
-            panic!("initial liquidity must be greater than 0");
+            return Err(AmmError::InvalidAmount);
         }
 
         // Initialize 50/50 split
@@ -163,15 +238,11 @@ impl AMM {
         let k = yes_reserve * no_reserve;
 
         // Create storage keys for this pool using tuples
-        let yes_key = (Symbol::new(&env, POOL_YES_RESERVE_KEY), market_id.clone());
-        let no_key = (Symbol::new(&env, POOL_NO_RESERVE_KEY), market_id.clone());
-        let k_key = (Symbol::new(&env, POOL_K_KEY), market_id.clone());
-        let lp_supply_key = (Symbol::new(&env, POOL_LP_SUPPLY_KEY), market_id.clone());
-        let lp_balance_key = (
-            Symbol::new(&env, POOL_LP_TOKENS_KEY),
-            market_id.clone(),
-            creator.clone(),
-        );
+        let yes_key = DataKey::PoolYesReserve(market_id.clone());
+        let no_key = DataKey::PoolNoReserve(market_id.clone());
+        let k_key = DataKey::PoolK(market_id.clone());
+        let lp_supply_key = DataKey::PoolLpSupply(market_id.clone());
+        let lp_balance_key = DataKey::PoolLpTokens(market_id.clone(), creator.clone());
 
         // Store reserves
         env.storage().persistent().set(&yes_key, &yes_reserve);
@@ -184,12 +255,16 @@ impl AMM {
         env.storage().persistent().set(&lp_supply_key, &lp_tokens);
         env.storage().persistent().set(&lp_balance_key, &lp_tokens);
 
+        extend_instance(&env);
+        extend_pool(&env, &market_id);
+        extend_persistent(&env, &lp_balance_key);
+
         // Transfer USDC from creator to contract
         let usdc_token: Address = env
             .storage()
-            .persistent()
-            .get(&Symbol::new(&env, USDC_KEY))
-            .expect("usdc token not set");
+            .instance()
+            .get(&DataKey::Usdc)
+            .ok_or(AmmError::NotInitialized)?;
 
         let token_client = token::Client::new(&env, &usdc_token);
         token_client.transfer(
@@ -206,6 +281,8 @@ impl AMM {
             no_reserve,
         }
         .publish(&env);
+
+        Ok(())
     }
 
     /// Buy outcome shares (YES or NO)
@@ -218,40 +295,40 @@ impl AMM {
         outcome: u32,
         amount: u128,
         min_shares: u128,
-    ) -> u128 {
+    ) -> Result<u128, AmmError> {
         // Require buyer authentication
         buyer.require_auth();
 
         // Validate inputs
         if outcome > 1 {
-            panic!("outcome must be 0 (NO) or 1 (YES)");
+            return Err(AmmError::InvalidOutcome);
         }
         if amount == 0 {
-            panic!("amount must be greater than 0");
+            return Err(AmmError::InvalidAmount);
         }
 
         // Check if pool exists
-        let pool_exists_key = (Symbol::new(&env, POOL_EXISTS_KEY), market_id.clone());
+        let pool_exists_key = DataKey::PoolExists(market_id.clone());
         if !env.storage().persistent().has(&pool_exists_key) {
-            panic!("pool does not exist");
+            return Err(AmmError::PoolNotFound);
         }
 
         // Get current reserves
-        let yes_key = (Symbol::new(&env, POOL_YES_RESERVE_KEY), market_id.clone());
-        let no_key = (Symbol::new(&env, POOL_NO_RESERVE_KEY), market_id.clone());
+        let yes_key = DataKey::PoolYesReserve(market_id.clone());
+        let no_key = DataKey::PoolNoReserve(market_id.clone());
 
         let yes_reserve: u128 = env.storage().persistent().get(&yes_key).unwrap_or(0);
         let no_reserve: u128 = env.storage().persistent().get(&no_key).unwrap_or(0);
 
         if yes_reserve == 0 || no_reserve == 0 {
-            panic!("insufficient liquidity");
+            return Err(AmmError::InsufficientLiquidity);
         }
 
         // Calculate trading fee (20 basis points = 0.2%)
         let trading_fee_bps: u128 = env
             .storage()
-            .persistent()
-            .get(&Symbol::new(&env, TRADING_FEE_KEY))
+            .instance()
+            .get(&DataKey::TradingFee)
             .unwrap_or(20);
 
         let fee_amount = (amount * trading_fee_bps) / 10000;
@@ -284,17 +361,14 @@ impl AMM {
 
         // Slippage protection
         if shares_out < min_shares {
-            panic!(
-                "Slippage exceeded: would receive {} shares, minimum is {}",
-                shares_out, min_shares
-            );
+            return Err(AmmError::SlippageExceeded);
         }
 
         // Verify CPMM invariant (k should increase due to fees, never decrease)
         let old_k = yes_reserve * no_reserve;
         let new_k = new_reserve_in * new_reserve_out;
         if new_k < old_k {
-            panic!("invariant violation");
+            return Err(AmmError::InvariantViolation);
         }
 
         // Update reserves
@@ -319,25 +393,24 @@ impl AMM {
         // Transfer USDC from buyer to contract
         let usdc_token: Address = env
             .storage()
-            .persistent()
-            .get(&Symbol::new(&env, USDC_KEY))
-            .expect("usdc token not set");
+            .instance()
+            .get(&DataKey::Usdc)
+            .ok_or(AmmError::NotInitialized)?;
 
         let token_client = token::Client::new(&env, &usdc_token);
         token_client.transfer(&buyer, env.current_contract_address(), &(amount as i128));
 
         // Update User Shares Balance
-        let user_share_key = (
-            Symbol::new(&env, USER_SHARES_KEY),
-            market_id.clone(),
-            buyer.clone(),
-            outcome,
-        );
+        let user_share_key = DataKey::UserShares(market_id.clone(), buyer.clone(), outcome);
         let current_shares: u128 = env.storage().persistent().get(&user_share_key).unwrap_or(0);
         env.storage()
             .persistent()
             .set(&user_share_key, &(current_shares + shares_out));
 
+        extend_instance(&env);
+        extend_pool(&env, &market_id);
+        extend_persistent(&env, &user_share_key);
+
         // Record trade (Optional: Simplified to event only for this resolution)
         BuySharesEvent {
             buyer,
@@ -349,7 +422,7 @@ impl AMM {
         }
         .publish(&env);
 
-        shares_out
+        Ok(shares_out)
     }
 
     /// Sell outcome shares back to AMM
@@ -361,43 +434,38 @@ impl AMM {
         outcome: u32,
         shares: u128,
         min_payout: u128,
-    ) -> u128 {
+    ) -> Result<u128, AmmError> {
         seller.require_auth();
 
         if outcome > 1 {
-            panic!("Invalid outcome: must be 0 (NO) or 1 (YES)");
+            return Err(AmmError::InvalidOutcome);
         }
         if shares == 0 {
-            panic!("Shares execution amount must be positive");
+            return Err(AmmError::InvalidAmount);
         }
 
         // Check if pool exists
-        let pool_exists_key = (Symbol::new(&env, POOL_EXISTS_KEY), market_id.clone());
+        let pool_exists_key = DataKey::PoolExists(market_id.clone());
         if !env.storage().persistent().has(&pool_exists_key) {
-            panic!("pool does not exist");
+            return Err(AmmError::PoolNotFound);
         }
 
         // Check user share balance
-        let user_share_key = (
-            Symbol::new(&env, USER_SHARES_KEY),
-            market_id.clone(),
-            seller.clone(),
-            outcome,
-        );
+        let user_share_key = DataKey::UserShares(market_id.clone(), seller.clone(), outcome);
         let user_shares: u128 = env.storage().persistent().get(&user_share_key).unwrap_or(0);
         if user_shares < shares {
-            panic!("Insufficient shares balance");
+            return Err(AmmError::InsufficientShares);
         }
 
         // Get current reserves
-        let yes_key = (Symbol::new(&env, POOL_YES_RESERVE_KEY), market_id.clone());
-        let no_key = (Symbol::new(&env, POOL_NO_RESERVE_KEY), market_id.clone());
+        let yes_key = DataKey::PoolYesReserve(market_id.clone());
+        let no_key = DataKey::PoolNoReserve(market_id.clone());
 
         let yes_reserve: u128 = env.storage().persistent().get(&yes_key).unwrap_or(0);
         let no_reserve: u128 = env.storage().persistent().get(&no_key).unwrap_or(0);
 
         if yes_reserve == 0 || no_reserve == 0 {
-            panic!("insufficient liquidity");
+            return Err(AmmError::InsufficientLiquidity);
         }
 
         // CPMM calculation for selling: payout = (shares * reserve_out) / (reserve_in + shares)
@@ -414,8 +482,8 @@ impl AMM {
         // Calculate trading fee (20 basis points = 0.2%)
         let trading_fee_bps: u128 = env
             .storage()
-            .persistent()
-            .get(&Symbol::new(&env, TRADING_FEE_KEY))
+            .instance()
+            .get(&DataKey::TradingFee)
             .unwrap_or(20);
 
         let fee_amount = (payout * trading_fee_bps) / 10000;
@@ -423,10 +491,7 @@ impl AMM {
 
         // Slippage protection
         if payout_after_fee < min_payout {
-            panic!(
-                "Slippage exceeded: would receive {} USDC, minimum is {}",
-                payout_after_fee, min_payout
-            );
+            return Err(AmmError::SlippageExceeded);
         }
 
         // Update reserves
@@ -453,7 +518,7 @@ impl AMM {
         let new_no: u128 = env.storage().persistent().get(&no_key).unwrap_or(0);
 
         if new_yes == 0 || new_no == 0 {
-            panic!("insufficient pool liquidity");
+            return Err(AmmError::InsufficientLiquidity);
         }
 
         // Burn user shares
@@ -461,12 +526,16 @@ impl AMM {
             .persistent()
             .set(&user_share_key, &(user_shares - shares));
 
+        extend_instance(&env);
+        extend_pool(&env, &market_id);
+        extend_persistent(&env, &user_share_key);
+
         // Transfer USDC to seller
         let usdc_address: Address = env
             .storage()
-            .persistent()
-            .get(&Symbol::new(&env, USDC_KEY))
-            .expect("USDC token not configured");
+            .instance()
+            .get(&DataKey::Usdc)
+            .ok_or(AmmError::NotInitialized)?;
         let usdc_client = soroban_sdk::token::Client::new(&env, &usdc_address);
 
         usdc_client.transfer(
@@ -486,7 +555,7 @@ impl AMM {
         }
         .publish(&env);
 
-        payout_after_fee
+        Ok(payout_after_fee)
     }
 
     /// Calculate current odds for an outcome
@@ -495,15 +564,15 @@ impl AMM {
     /// Read-only function with no state changes
     pub fn get_odds(env: Env, market_id: BytesN<32>) -> (u32, u32) {
         // Check if pool exists
-        let pool_exists_key = (Symbol::new(&env, POOL_EXISTS_KEY), market_id.clone());
+        let pool_exists_key = DataKey::PoolExists(market_id.clone());
         if !env.storage().persistent().has(&pool_exists_key) {
             // No pool exists - return 50/50 odds
             return (5000, 5000);
         }
 
         // Get pool reserves
-        let yes_key = (Symbol::new(&env, POOL_YES_RESERVE_KEY), market_id.clone());
-        let no_key = (Symbol::new(&env, POOL_NO_RESERVE_KEY), market_id.clone());
+        let yes_key = DataKey::PoolYesReserve(market_id.clone());
+        let no_key = DataKey::PoolNoReserve(market_id.clone());
 
         let yes_reserve: u128 = env.storage().persistent().get(&yes_key).unwrap_or(0);
         let no_reserve: u128 = env.storage().persistent().get(&no_key).unwrap_or(0);
@@ -554,38 +623,34 @@ impl AMM {
         lp_provider: Address,
         market_id: BytesN<32>,
         lp_tokens: u128,
-    ) -> (u128, u128) {
+    ) -> Result<(u128, u128), AmmError> {
         // Require LP provider authentication
         lp_provider.require_auth();
 
         // Validate lp_tokens > 0
         if lp_tokens == 0 {
-            panic!("lp tokens must be positive");
+            return Err(AmmError::InvalidAmount);
         }
 
         // Check if pool exists for this market
-        let pool_exists_key = (Symbol::new(&env, POOL_EXISTS_KEY), market_id.clone());
+        let pool_exists_key = DataKey::PoolExists(market_id.clone());
         if !env.storage().persistent().has(&pool_exists_key) {
-            panic!("pool does not exist");
+            return Err(AmmError::PoolNotFound);
         }
 
         // Create storage keys for this pool
-        let yes_reserve_key = (Symbol::new(&env, POOL_YES_RESERVE_KEY), market_id.clone());
-        let no_reserve_key = (Symbol::new(&env, POOL_NO_RESERVE_KEY), market_id.clone());
-        let k_key = (Symbol::new(&env, POOL_K_KEY), market_id.clone());
-        let lp_supply_key = (Symbol::new(&env, POOL_LP_SUPPLY_KEY), market_id.clone());
-        let lp_balance_key = (
-            Symbol::new(&env, POOL_LP_TOKENS_KEY),
-            market_id.clone(),
-            lp_provider.clone(),
-        );
+        let yes_reserve_key = DataKey::PoolYesReserve(market_id.clone());
+        let no_reserve_key = DataKey::PoolNoReserve(market_id.clone());
+        let k_key = DataKey::PoolK(market_id.clone());
+        let lp_supply_key = DataKey::PoolLpSupply(market_id.clone());
+        let lp_balance_key = DataKey::PoolLpTokens(market_id.clone(), lp_provider.clone());
 
         // Get LP provider's current balance
         let lp_balance: u128 = env.storage().persistent().get(&lp_balance_key).unwrap_or(0);
 
         // Validate user has enough LP tokens
         if lp_balance < lp_tokens {
-            panic!("insufficient lp tokens");
+            return Err(AmmError::InsufficientLpTokens);
         }
 
         // Get current reserves
@@ -593,19 +658,19 @@ impl AMM {
             .storage()
             .persistent()
             .get(&yes_reserve_key)
-            .expect("yes reserve not found");
+            .ok_or(AmmError::PoolNotFound)?;
         let no_reserve: u128 = env
             .storage()
             .persistent()
             .get(&no_reserve_key)
-            .expect("no reserve not found");
+            .ok_or(AmmError::PoolNotFound)?;
 
         // Get current LP token supply
         let current_lp_supply: u128 = env
             .storage()
             .persistent()
             .get(&lp_supply_key)
-            .expect("lp supply not found");
+            .ok_or(AmmError::PoolNotFound)?;
 
         // Calculate proportional YES and NO amounts to withdraw
         // yes_amount = (lp_tokens / current_lp_supply) * yes_reserve
@@ -613,7 +678,7 @@ impl AMM {
         let no_amount = (lp_tokens * no_reserve) / current_lp_supply;
 
         if yes_amount == 0 || no_amount == 0 {
-            panic!("withdrawal amount too small");
+            return Err(AmmError::WithdrawalTooSmall);
         }
 
         // Update reserves
@@ -622,7 +687,7 @@ impl AMM {
 
         // Validate minimum liquidity remains (prevent draining pool completely)
         if new_yes_reserve == 0 || new_no_reserve == 0 {
-            panic!("cannot drain pool completely");
+            return Err(AmmError::CannotDrainPool);
         }
 
         // Update k
@@ -653,13 +718,17 @@ impl AMM {
             .persistent()
             .set(&lp_supply_key, &new_lp_supply);
 
+        extend_instance(&env);
+        extend_pool(&env, &market_id);
+        extend_persistent(&env, &lp_balance_key);
+
         // Transfer USDC back to user (YES and NO reserves are in USDC)
         // The user receives their proportional share of the pool's liquidity
         let usdc_token: Address = env
             .storage()
-            .persistent()
-            .get(&Symbol::new(&env, USDC_KEY))
-            .expect("usdc token not set");
+            .instance()
+            .get(&DataKey::Usdc)
+            .ok_or(AmmError::NotInitialized)?;
 
         let token_client = token::Client::new(&env, &usdc_token);
         let total_withdrawal = yes_amount + no_amount;
@@ -679,21 +748,21 @@ impl AMM {
         }
         .publish(&env);
 
-        (yes_amount, no_amount)
+        Ok((yes_amount, no_amount))
     }
 
     /// Get current pool state (reserves, liquidity depth)
     /// Returns pool information for frontend display
     pub fn get_pool_state(env: Env, market_id: BytesN<32>) -> (u128, u128, u128, u32, u32) {
         // Check if pool exists
-        let pool_exists_key = (Symbol::new(&env, POOL_EXISTS_KEY), market_id.clone());
+        let pool_exists_key = DataKey::PoolExists(market_id.clone());
         if !env.storage().persistent().has(&pool_exists_key) {
             return (0, 0, 0, 5000, 5000); // No pool: zero reserves, 50/50 odds
         }
 
         // Get pool reserves
-        let yes_key = (Symbol::new(&env, POOL_YES_RESERVE_KEY), market_id.clone());
-        let no_key = (Symbol::new(&env, POOL_NO_RESERVE_KEY), market_id.clone());
+        let yes_key = DataKey::PoolYesReserve(market_id.clone());
+        let no_key = DataKey::PoolNoReserve(market_id.clone());
 
         let yes_reserve: u128 = env.storage().persistent().get(&yes_key).unwrap_or(0);
         let no_reserve: u128 = env.storage().persistent().get(&no_key).unwrap_or(0);
@@ -717,14 +786,14 @@ impl AMM {
     /// Returns (0, 0) for invalid inputs (zero reserves)
     pub fn get_current_prices(env: Env, market_id: BytesN<32>) -> (u32, u32) {
         // Check if pool exists
-        let pool_exists_key = (Symbol::new(&env, POOL_EXISTS_KEY), market_id.clone());
+        let pool_exists_key = DataKey::PoolExists(market_id.clone());
         if !env.storage().persistent().has(&pool_exists_key) {
             return (0, 0); // No pool exists
         }
 
         // Get pool reserves
-        let yes_key = (Symbol::new(&env, POOL_YES_RESERVE_KEY), market_id.clone());
-        let no_key = (Symbol::new(&env, POOL_NO_RESERVE_KEY), market_id.clone());
+        let yes_key = DataKey::PoolYesReserve(market_id.clone());
+        let no_key = DataKey::PoolNoReserve(market_id.clone());
 
         let yes_reserve: u128 = env.storage().persistent().get(&yes_key).unwrap_or(0);
         let no_reserve: u128 = env.storage().persistent().get(&no_key).unwrap_or(0);
@@ -737,8 +806,8 @@ impl AMM {
         // Get trading fee (default 20 basis points = 0.2%)
         let trading_fee_bps: u128 = env
             .storage()
-            .persistent()
-            .get(&Symbol::new(&env, TRADING_FEE_KEY))
+            .instance()
+            .get(&DataKey::TradingFee)
             .unwrap_or(20);
 
         let total_liquidity = yes_reserve + no_reserve;
@@ -761,9 +830,97 @@ impl AMM {
         (yes_price, no_price)
     }
 
+    /// Keep a market's pool entries and the AMM instance from being archived
+    ///
+    /// Permissionless: anyone may pay the rent for a long-lived pool. Per-user
+    /// share and LP balances are extended whenever their owner trades.
+    pub fn bump(env: Env, market_id: BytesN<32>) -> Result<(), AmmError> {
+        let pool_exists_key = DataKey::PoolExists(market_id.clone());
+        if !env.storage().persistent().has(&pool_exists_key) {
+            return Err(AmmError::PoolNotFound);
+        }
+
+        extend_instance(&env);
+        extend_pool(&env, &market_id);
+        Ok(())
+    }
+
     // TODO: Implement remaining AMM functions
     // - add_liquidity()
     // - get_lp_position() / claim_lp_fees()
     // - calculate_spot_price()
     // - get_trade_history()
+
+    /// Get the storage schema version of this contract
+    pub fn get_schema_version(env: Env) -> u32 {
+        upgrade::get_schema_version(&env)
+    }
+
+    /// Admin: Set the upgrade timelock in seconds (0 = upgrades apply immediately)
+    ///
+    /// A reduction only applies once the current delay has passed. Returns
+    /// when the new delay is in force.
+    pub fn set_upgrade_delay(env: Env, delay: u64) -> Result<u64, AmmError> {
+        require_admin(&env)?;
+        Ok(upgrade::set_upgrade_delay(&env, delay))
+    }
+
+    /// Admin: Queue a WASM upgrade behind the timelock, returns its eta
+    pub fn propose_upgrade(env: Env, new_wasm_hash: BytesN<32>) -> Result<u64, AmmError> {
+        require_admin(&env)?;
+        Ok(upgrade::propose_upgrade(&env, new_wasm_hash))
+    }
+
+    /// Admin: Cancel the queued upgrade during the timelock
+    pub fn cancel_upgrade(env: Env) -> Result<(), AmmError> {
+        require_admin(&env)?;
+        upgrade::cancel_upgrade(&env)?;
+        Ok(())
+    }
+
+    /// Get the queued upgrade, if any
+    pub fn get_pending_upgrade(env: Env) -> Option<PendingUpgrade> {
+        upgrade::get_pending_upgrade(&env)
+    }
+
+    /// Admin: Replace the AMM WASM
+    ///
+    /// The admin may be a multisig account. When a timelock is set the hash
+    /// must match a proposal whose eta has passed.
+    pub fn upgrade(env: Env, new_wasm_hash: BytesN<32>) -> Result<(), AmmError> {
+        require_admin(&env)?;
+        upgrade::apply_upgrade(&env, new_wasm_hash)?;
+        Ok(())
+    }
+
+    /// Admin: Migrate storage to this code's schema version after an upgrade
+    pub fn migrate(env: Env) -> Result<(), AmmError> {
+        require_admin(&env)?;
+        upgrade::migrate(&env, SCHEMA_VERSION, |_env, _from_version| {})?;
+        Ok(())
+    }
+}
+
+/// Load the stored admin and require its authorization
+fn require_admin(env: &Env) -> Result<Address, AmmError> {
+    let admin: Address = env
+        .storage()
+        .instance()
+        .get(&DataKey::Admin)
+        .ok_or(AmmError::NotInitialized)?;
+    admin.require_auth();
+    Ok(admin)
+}
+
+/// Extend the TTL of every pool-wide entry for a market
+fn extend_pool(env: &Env, market_id: &BytesN<32>) {
+    for key in [
+        DataKey::PoolExists(market_id.clone()),
+        DataKey::PoolYesReserve(market_id.clone()),
+        DataKey::PoolNoReserve(market_id.clone()),
+        DataKey::PoolK(market_id.clone()),
+        DataKey::PoolLpSupply(market_id.clone()),
+    ] {
+        extend_persistent(env, &key);
+    }
 }
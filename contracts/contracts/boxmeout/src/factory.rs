@@ -2,9 +2,15 @@
 // Handles market creation and lifecycle management
 
 use soroban_sdk::{
-    contract, contractevent, contractimpl, Address, Bytes, BytesN, Env, IntoVal, Symbol, Vec,
+    contract, contracterror, contractevent, contractimpl, contracttype, token, xdr::ToXdr, Address,
+    Bytes, BytesN, Env, IntoVal, Symbol, Vec,
 };
 
+use soroban_sdk::auth::{ContractContext, InvokerContractAuthEntry, SubContractInvocation};
+
+use crate::helpers::{extend_instance, extend_persistent, FeeKind};
+use crate::upgrade::{self, PendingUpgrade, UpgradeError};
+
 #[contractevent]
 pub struct FactoryInitializedEvent {
     pub admin: Address,
@@ -19,11 +25,74 @@ pub struct MarketCreatedEvent {
     pub closing_time: u64,
 }
 
-// Storage keys
-const ADMIN_KEY: &str = "admin";
-const USDC_KEY: &str = "usdc";
-const TREASURY_KEY: &str = "treasury";
-const MARKET_COUNT_KEY: &str = "market_count";
+#[contractevent]
+pub struct MarketUpgradeProposedEvent {
+    pub market: Address,
+    pub wasm_hash: BytesN<32>,
+    pub eta: u64,
+}
+
+#[contractevent]
+pub struct MarketUpgradeCancelledEvent {
+    pub market: Address,
+    pub wasm_hash: BytesN<32>,
+}
+
+/// Storage schema version written by this code
+const SCHEMA_VERSION: u32 = 1;
+
+/// Storage keys
+///
+/// Config and the market counter live in instance storage; each market's
+/// registry flag and metadata are persistent.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum DataKey {
+    Admin,
+    Usdc,
+    Treasury,
+    MarketCount,
+    Market(BytesN<32>),
+    MarketMeta(BytesN<32>),
+    /// Upgrade queued for a market contract behind the factory's timelock
+    MarketUpgrade(Address),
+}
+
+/// Error codes returned by the factory
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum FactoryError {
+    /// Factory has not been initialized
+    NotInitialized = 1,
+    /// Factory is already initialized
+    AlreadyInitialized = 2,
+    /// Closing time must be in the future and before resolution time
+    InvalidTimestamps = 3,
+    /// A market with this ID is already registered
+    MarketAlreadyExists = 4,
+    /// No market with this ID is registered
+    MarketNotFound = 5,
+    /// No upgrade has been proposed
+    NoPendingUpgrade = 100,
+    /// Hash differs from the proposed upgrade
+    WasmHashMismatch = 101,
+    /// Upgrade timelock has not elapsed
+    TimelockNotElapsed = 102,
+    /// Storage schema is already current
+    SchemaUpToDate = 103,
+}
+
+impl From<UpgradeError> for FactoryError {
+    fn from(err: UpgradeError) -> Self {
+        match err {
+            UpgradeError::NoPendingUpgrade => FactoryError::NoPendingUpgrade,
+            UpgradeError::WasmHashMismatch => FactoryError::WasmHashMismatch,
+            UpgradeError::TimelockNotElapsed => FactoryError::TimelockNotElapsed,
+            UpgradeError::SchemaUpToDate => FactoryError::SchemaUpToDate,
+        }
+    }
+}
 
 /// MARKET FACTORY - Handles market creation, fee collection, and market registry
 #[contract]
@@ -32,38 +101,34 @@ pub struct MarketFactory;
 #[contractimpl]
 impl MarketFactory {
     /// Initialize factory with admin, USDC token, and treasury address
-    pub fn initialize(env: Env, admin: Address, usdc: Address, treasury: Address) {
+    pub fn initialize(
+        env: Env,
+        admin: Address,
+        usdc: Address,
+        treasury: Address,
+    ) -> Result<(), FactoryError> {
         // Check if already initialized
-        if env
-            .storage()
-            .persistent()
-            .has(&Symbol::new(&env, ADMIN_KEY))
-        {
-            panic!("already initialized");
+        if env.storage().instance().has(&DataKey::Admin) {
+            return Err(FactoryError::AlreadyInitialized);
         }
 
         // Verify admin signature
         admin.require_auth();
 
         // Store admin address
-        env.storage()
-            .persistent()
-            .set(&Symbol::new(&env, ADMIN_KEY), &admin);
+        env.storage().instance().set(&DataKey::Admin, &admin);
 
         // Store USDC token contract address
-        env.storage()
-            .persistent()
-            .set(&Symbol::new(&env, USDC_KEY), &usdc);
+        env.storage().instance().set(&DataKey::Usdc, &usdc);
 
         // Store Treasury contract address
-        env.storage()
-            .persistent()
-            .set(&Symbol::new(&env, TREASURY_KEY), &treasury);
+        env.storage().instance().set(&DataKey::Treasury, &treasury);
 
         // Initialize market counter at 0
-        env.storage()
-            .persistent()
-            .set(&Symbol::new(&env, MARKET_COUNT_KEY), &0u32);
+        env.storage().instance().set(&DataKey::MarketCount, &0u32);
+
+        upgrade::init_schema_version(&env, SCHEMA_VERSION);
+        extend_instance(&env);
 
         // Emit initialization event
         FactoryInitializedEvent {
@@ -72,67 +137,116 @@ impl MarketFactory {
             treasury,
         }
         .publish(&env);
+
+        Ok(())
     }
 
     /// Get total markets created
     pub fn get_market_count(env: Env) -> u32 {
         env.storage()
-            .persistent()
-            .get(&Symbol::new(&env, MARKET_COUNT_KEY))
+            .instance()
+            .get(&DataKey::MarketCount)
             .unwrap_or(0)
     }
 
     /// Get treasury address
-    pub fn get_treasury(env: Env) -> Address {
+    pub fn get_treasury(env: Env) -> Result<Address, FactoryError> {
         env.storage()
-            .persistent()
-            .get(&Symbol::new(&env, TREASURY_KEY))
-            .expect("Treasury not set")
+            .instance()
+            .get(&DataKey::Treasury)
+            .ok_or(FactoryError::NotInitialized)
+    }
+
+    /// Compute the market_id `create_market` would assign for these inputs.
+    ///
+    /// Read-only: lets clients learn the ID before submitting the transaction.
+    /// `create_market` only records the market in the registry and deploys no
+    /// contract, so the ID is the only identifier there is to precompute.
+    #[allow(clippy::too_many_arguments)]
+    pub fn compute_market_id(
+        env: Env,
+        creator: Address,
+        nonce: u64,
+        title: Symbol,
+        description: Symbol,
+        category: Symbol,
+        closing_time: u64,
+        resolution_time: u64,
+    ) -> BytesN<32> {
+        let params_hash = hash_market_params(
+            &env,
+            &title,
+            &description,
+            &category,
+            closing_time,
+            resolution_time,
+        );
+        derive_market_id(&env, &creator, nonce, &params_hash)
+    }
+
+    /// Check whether a market_id is already present in the registry
+    pub fn market_exists(env: Env, market_id: BytesN<32>) -> bool {
+        env.storage().persistent().has(&DataKey::Market(market_id))
     }
 
     /// Create a new market instance
+    ///
+    /// The market_id is derived from the factory address, the creator, a
+    /// caller-supplied nonce and a hash of the market parameters, so it cannot
+    /// be predicted from the market counter and never collides across creators.
+    #[allow(clippy::too_many_arguments)]
     pub fn create_market(
         env: Env,
         creator: Address,
+        nonce: u64,
         title: Symbol,
         description: Symbol,
         category: Symbol,
         closing_time: u64,
         resolution_time: u64,
-    ) -> BytesN<32> {
+    ) -> Result<BytesN<32>, FactoryError> {
         // Require creator authentication
         creator.require_auth();
 
         // Validate closing_time > now and < resolution_time
         let current_time = env.ledger().timestamp();
         if closing_time <= current_time {
-            panic!("invalid timestamps");
+            return Err(FactoryError::InvalidTimestamps);
         }
         if closing_time >= resolution_time {
-            panic!("invalid timestamps");
+            return Err(FactoryError::InvalidTimestamps);
         }
 
         // Get market count and increment
         let market_count: u32 = env
             .storage()
-            .persistent()
-            .get(&Symbol::new(&env, MARKET_COUNT_KEY))
+            .instance()
+            .get(&DataKey::MarketCount)
             .unwrap_or(0);
 
-        // Generate unique market_id using SHA256
-        let mut hash_input = Bytes::new(&env);
-        hash_input.extend_from_array(&market_count.to_be_bytes());
-        hash_input.extend_from_array(&current_time.to_be_bytes());
+        // Derive market_id from factory, creator, nonce and parameter hash
+        let market_id = Self::compute_market_id(
+            env.clone(),
+            creator.clone(),
+            nonce,
+            title.clone(),
+            description.clone(),
+            category.clone(),
+            closing_time,
+            resolution_time,
+        );
 
-        let hash = env.crypto().sha256(&hash_input);
-        let market_id = BytesN::from_array(&env, &hash.to_array());
+        // Reject IDs already present in the registry (e.g. a reused nonce)
+        if Self::market_exists(env.clone(), market_id.clone()) {
+            return Err(FactoryError::MarketAlreadyExists);
+        }
 
         // Store market in registry
-        let market_key = (Symbol::new(&env, "market"), market_id.clone());
+        let market_key = DataKey::Market(market_id.clone());
         env.storage().persistent().set(&market_key, &true);
 
         // Store market metadata
-        let metadata_key = (Symbol::new(&env, "market_meta"), market_id.clone());
+        let metadata_key = DataKey::MarketMeta(market_id.clone());
         let metadata = (
             creator.clone(),
             title.clone(),
@@ -145,23 +259,61 @@ impl MarketFactory {
 
         // Increment market counter
         env.storage()
-            .persistent()
-            .set(&Symbol::new(&env, MARKET_COUNT_KEY), &(market_count + 1));
+            .instance()
+            .set(&DataKey::MarketCount, &(market_count + 1));
+
+        extend_instance(&env);
+        extend_market_entries(&env, &market_id);
 
         // Charge creation fee (1 USDC = 10^7 stroops, assuming 7 decimals)
         let creation_fee: i128 = 10_000_000; // 1 USDC
         let treasury_address: Address = env
             .storage()
-            .persistent()
-            .get(&Symbol::new(&env, TREASURY_KEY))
-            .expect("Treasury address not set");
+            .instance()
+            .get(&DataKey::Treasury)
+            .ok_or(FactoryError::NotInitialized)?;
+
+        let usdc: Address = env
+            .storage()
+            .instance()
+            .get(&DataKey::Usdc)
+            .ok_or(FactoryError::NotInitialized)?;
+
+        // The treasury only accepts deposits from registered sources, so the
+        // factory collects the fee and deposits it itself
+        let factory_address = env.current_contract_address();
+        token::Client::new(&env, &usdc).transfer(&creator, &factory_address, &creation_fee);
+        // Let the treasury pull the fee from the factory in `deposit_fees`
+        env.authorize_as_current_contract(Vec::from_array(
+            &env,
+            [InvokerContractAuthEntry::Contract(SubContractInvocation {
+                context: ContractContext {
+                    contract: usdc.clone(),
+                    fn_name: Symbol::new(&env, "transfer"),
+                    args: (
+                        factory_address.clone(),
+                        treasury_address.clone(),
+                        creation_fee,
+                    )
+                        .into_val(&env),
+                },
+                sub_invocations: Vec::new(&env),
+            })],
+        ));
 
         // Cross-contract call to Treasury using contract address
         // This works because we're calling by address at runtime, not compile-time module reference
         env.invoke_contract::<()>(
             &treasury_address,
             &Symbol::new(&env, "deposit_fees"),
-            (creator.clone(), creation_fee).into_val(&env),
+            (
+                factory_address,
+                usdc,
+                FeeKind::Creation,
+                None::<Address>,
+                creation_fee,
+            )
+                .into_val(&env),
         );
 
         // Emit MarketCreated event
@@ -172,7 +324,20 @@ impl MarketFactory {
         }
         .publish(&env);
 
-        market_id
+        Ok(market_id)
+    }
+
+    /// Keep a market's registry entries and the factory instance from being archived
+    ///
+    /// Permissionless: anyone may pay the rent for a long-lived market.
+    pub fn bump(env: Env, market_id: BytesN<32>) -> Result<(), FactoryError> {
+        if !Self::market_exists(env.clone(), market_id.clone()) {
+            return Err(FactoryError::MarketNotFound);
+        }
+
+        extend_instance(&env);
+        extend_market_entries(&env, &market_id);
+        Ok(())
     }
 
     /// Get market info by market_id
@@ -214,4 +379,185 @@ impl MarketFactory {
     pub fn withdraw_fees(_env: Env, _amount: i128) {
         todo!("See withdraw fees TODO above")
     }
+
+    /// Admin: Queue a market WASM upgrade behind the factory's timelock, returns its eta
+    pub fn propose_market_upgrade(
+        env: Env,
+        market: Address,
+        new_wasm_hash: BytesN<32>,
+    ) -> Result<u64, FactoryError> {
+        require_admin(&env)?;
+
+        let pending = upgrade::queue_upgrade(&env, new_wasm_hash.clone());
+        let key = DataKey::MarketUpgrade(market.clone());
+        env.storage().persistent().set(&key, &pending);
+        extend_persistent(&env, &key);
+
+        MarketUpgradeProposedEvent {
+            market,
+            wasm_hash: new_wasm_hash,
+            eta: pending.eta,
+        }
+        .publish(&env);
+
+        Ok(pending.eta)
+    }
+
+    /// Admin: Cancel a queued market upgrade during the timelock
+    pub fn cancel_market_upgrade(env: Env, market: Address) -> Result<(), FactoryError> {
+        require_admin(&env)?;
+
+        let pending = Self::get_pending_market_upgrade(env.clone(), market.clone())
+            .ok_or(FactoryError::NoPendingUpgrade)?;
+        env.storage()
+            .persistent()
+            .remove(&DataKey::MarketUpgrade(market.clone()));
+
+        MarketUpgradeCancelledEvent {
+            market,
+            wasm_hash: pending.wasm_hash,
+        }
+        .publish(&env);
+
+        Ok(())
+    }
+
+    /// Get the upgrade queued for a market, if any
+    pub fn get_pending_market_upgrade(env: Env, market: Address) -> Option<PendingUpgrade> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::MarketUpgrade(market))
+            .map(|pending| upgrade::retime(&env, pending))
+    }
+
+    /// Admin: Upgrade a market contract created by this factory
+    ///
+    /// Subject to the factory's timelock: when a delay is set the hash must
+    /// match a `propose_market_upgrade` whose eta has passed.
+    pub fn upgrade_market(
+        env: Env,
+        market: Address,
+        new_wasm_hash: BytesN<32>,
+    ) -> Result<(), FactoryError> {
+        require_admin(&env)?;
+
+        let pending = Self::get_pending_market_upgrade(env.clone(), market.clone());
+        upgrade::require_ready(&env, pending, &new_wasm_hash)?;
+        env.storage()
+            .persistent()
+            .remove(&DataKey::MarketUpgrade(market.clone()));
+
+        env.invoke_contract::<()>(
+            &market,
+            &Symbol::new(&env, "upgrade"),
+            (new_wasm_hash,).into_val(&env),
+        );
+        Ok(())
+    }
+
+    /// Admin: Run the storage migration on an upgraded market contract
+    pub fn migrate_market(env: Env, market: Address) -> Result<(), FactoryError> {
+        require_admin(&env)?;
+        env.invoke_contract::<()>(&market, &Symbol::new(&env, "migrate"), ().into_val(&env));
+        Ok(())
+    }
+
+    /// Get the storage schema version of this contract
+    pub fn get_schema_version(env: Env) -> u32 {
+        upgrade::get_schema_version(&env)
+    }
+
+    /// Admin: Set the upgrade timelock in seconds (0 = upgrades apply immediately)
+    ///
+    /// A reduction only applies once the current delay has passed. Returns
+    /// when the new delay is in force.
+    pub fn set_upgrade_delay(env: Env, delay: u64) -> Result<u64, FactoryError> {
+        require_admin(&env)?;
+        Ok(upgrade::set_upgrade_delay(&env, delay))
+    }
+
+    /// Admin: Queue a WASM upgrade behind the timelock, returns its eta
+    pub fn propose_upgrade(env: Env, new_wasm_hash: BytesN<32>) -> Result<u64, FactoryError> {
+        require_admin(&env)?;
+        Ok(upgrade::propose_upgrade(&env, new_wasm_hash))
+    }
+
+    /// Admin: Cancel the queued upgrade during the timelock
+    pub fn cancel_upgrade(env: Env) -> Result<(), FactoryError> {
+        require_admin(&env)?;
+        upgrade::cancel_upgrade(&env)?;
+        Ok(())
+    }
+
+    /// Get the queued upgrade, if any
+    pub fn get_pending_upgrade(env: Env) -> Option<PendingUpgrade> {
+        upgrade::get_pending_upgrade(&env)
+    }
+
+    /// Admin: Replace the factory WASM
+    ///
+    /// The admin may be a multisig account. When a timelock is set the hash
+    /// must match a proposal whose eta has passed.
+    pub fn upgrade(env: Env, new_wasm_hash: BytesN<32>) -> Result<(), FactoryError> {
+        require_admin(&env)?;
+        upgrade::apply_upgrade(&env, new_wasm_hash)?;
+        Ok(())
+    }
+
+    /// Admin: Migrate storage to this code's schema version after an upgrade
+    pub fn migrate(env: Env) -> Result<(), FactoryError> {
+        require_admin(&env)?;
+        upgrade::migrate(&env, SCHEMA_VERSION, |_env, _from_version| {})?;
+        Ok(())
+    }
+}
+
+/// Load the stored admin and require its authorization
+fn require_admin(env: &Env) -> Result<Address, FactoryError> {
+    let admin: Address = env
+        .storage()
+        .instance()
+        .get(&DataKey::Admin)
+        .ok_or(FactoryError::NotInitialized)?;
+    admin.require_auth();
+    Ok(admin)
+}
+
+/// Extend the TTL of a market's registry and metadata entries
+fn extend_market_entries(env: &Env, market_id: &BytesN<32>) {
+    extend_persistent(env, &DataKey::Market(market_id.clone()));
+    extend_persistent(env, &DataKey::MarketMeta(market_id.clone()));
+}
+
+/// Hash the user-facing market parameters into a single 32-byte digest
+fn hash_market_params(
+    env: &Env,
+    title: &Symbol,
+    description: &Symbol,
+    category: &Symbol,
+    closing_time: u64,
+    resolution_time: u64,
+) -> BytesN<32> {
+    let mut input = Bytes::new(env);
+    input.append(&title.clone().to_xdr(env));
+    input.append(&description.clone().to_xdr(env));
+    input.append(&category.clone().to_xdr(env));
+    input.extend_from_array(&closing_time.to_be_bytes());
+    input.extend_from_array(&resolution_time.to_be_bytes());
+    env.crypto().sha256(&input).into()
+}
+
+/// market_id = sha256(factory || creator || nonce || params_hash)
+fn derive_market_id(
+    env: &Env,
+    creator: &Address,
+    nonce: u64,
+    params_hash: &BytesN<32>,
+) -> BytesN<32> {
+    let mut input = Bytes::new(env);
+    input.append(&env.current_contract_address().to_xdr(env));
+    input.append(&creator.clone().to_xdr(env));
+    input.extend_from_array(&nonce.to_be_bytes());
+    input.extend_from_array(&params_hash.to_array());
+    env.crypto().sha256(&input).into()
 }
@@ -1,22 +1,36 @@
 // File for resuable helper functions
 
-use soroban_sdk::{Address, BytesN, Env, Symbol};
-// use crate::helpers::*;
+use soroban_sdk::{contracttype, Env, IntoVal, Val};
+#[cfg(any(feature = "amm", test, feature = "testutils"))]
+use soroban_sdk::{Address, BytesN};
 
-#[allow(dead_code)]
-const POOL_YES_RESERVE: &str = "pool_yes_reserve";
-#[allow(dead_code)]
-const POOL_NO_RESERVE: &str = "pool_no_reserve";
-#[allow(dead_code)]
-const POOL_K: &str = "pool_k";
-#[allow(dead_code)]
-const POOL_EXISTS: &str = "pool_exists";
-#[allow(dead_code)]
-const TRADE_COUNT: &str = "trade_count";
-#[allow(dead_code)]
-const USER_SHARES_YES: &str = "user_shares_yes";
-#[allow(dead_code)]
-const USER_SHARES_NO: &str = "user_shares_no";
+// AMM storage helpers read and write the AMM's own layout
+#[cfg(any(feature = "amm", test, feature = "testutils"))]
+use crate::amm::DataKey as AmmKey;
+
+/// Ledgers closed per day (~5s per ledger)
+pub const DAY_IN_LEDGERS: u32 = 17_280;
+
+/// Instance storage (contract-wide config and the WASM) is extended to ~30 days
+/// once its remaining TTL drops below ~29 days, so at most once a day
+pub const INSTANCE_BUMP_AMOUNT: u32 = 30 * DAY_IN_LEDGERS;
+pub const INSTANCE_LIFETIME_THRESHOLD: u32 = INSTANCE_BUMP_AMOUNT - DAY_IN_LEDGERS;
+
+/// Persistent entries (per-market, per-user, per-oracle) are extended to ~60 days
+/// whenever they are touched and have less than ~53 days left
+pub const PERSISTENT_BUMP_AMOUNT: u32 = 60 * DAY_IN_LEDGERS;
+pub const PERSISTENT_LIFETIME_THRESHOLD: u32 = PERSISTENT_BUMP_AMOUNT - 7 * DAY_IN_LEDGERS;
+
+/// What a treasury fee deposit was charged for; shared so the factory,
+/// markets and AMM can tag deposits without linking the treasury
+#[contracttype]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum FeeKind {
+    Creation,
+    Trading,
+    Winnings,
+    Slashing,
+}
 
 /// Create test environment (test-only utility)
 /// Note: Call env.mock_all_auths() manually in your tests after creating the env
@@ -25,69 +39,87 @@ pub fn create_test_env() -> Env {
     Env::default()
 }
 
+/// Keep the calling contract's instance storage and code alive
+pub fn extend_instance(env: &Env) {
+    env.storage()
+        .instance()
+        .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+}
+
+/// Extend a persistent entry's TTL; missing keys are skipped
+pub fn extend_persistent<K>(env: &Env, key: &K)
+where
+    K: IntoVal<Env, Val>,
+{
+    if env.storage().persistent().has(key) {
+        env.storage().persistent().extend_ttl(
+            key,
+            PERSISTENT_LIFETIME_THRESHOLD,
+            PERSISTENT_BUMP_AMOUNT,
+        );
+    }
+}
+
 /// Get pool reserves for a market
+#[cfg(any(feature = "amm", test, feature = "testutils"))]
 #[allow(dead_code)]
 pub fn get_pool_reserves(env: &Env, market_id: &BytesN<32>) -> (u128, u128) {
     let yes_reserve: u128 = env
         .storage()
         .persistent()
-        .get(&(Symbol::new(env, POOL_YES_RESERVE), market_id).clone())
+        .get(&AmmKey::PoolYesReserve(market_id.clone()))
         .unwrap_or(0);
     let no_reserve: u128 = env
         .storage()
         .persistent()
-        .get(&(Symbol::new(env, POOL_NO_RESERVE), market_id.clone()))
+        .get(&AmmKey::PoolNoReserve(market_id.clone()))
         .unwrap_or(0);
 
     (yes_reserve, no_reserve)
 }
 
 /// Check if pool exists for a market
+#[cfg(any(feature = "amm", test, feature = "testutils"))]
 #[allow(dead_code)]
 pub fn pool_exists(env: &Env, market_id: &BytesN<32>) -> bool {
     env.storage()
         .persistent()
-        .get(&(Symbol::new(env, POOL_EXISTS), market_id.clone()))
+        .get(&AmmKey::PoolExists(market_id.clone()))
         .unwrap_or(false)
 }
 
 /// Update pool reserves in storage
+#[cfg(any(feature = "amm", test, feature = "testutils"))]
 #[allow(dead_code)]
 pub fn set_pool_reserves(env: &Env, market_id: &BytesN<32>, yes_reserve: u128, no_reserve: u128) {
+    env.storage()
+        .persistent()
+        .set(&AmmKey::PoolYesReserve(market_id.clone()), &yes_reserve);
+    env.storage()
+        .persistent()
+        .set(&AmmKey::PoolNoReserve(market_id.clone()), &no_reserve);
     env.storage().persistent().set(
-        &(Symbol::new(env, POOL_YES_RESERVE), market_id.clone()),
-        &yes_reserve,
-    );
-    env.storage().persistent().set(
-        &(Symbol::new(env, POOL_NO_RESERVE), market_id.clone()),
-        &no_reserve,
-    );
-    env.storage().persistent().set(
-        &(Symbol::new(env, POOL_K), market_id.clone()),
+        &AmmKey::PoolK(market_id.clone()),
         &(yes_reserve * no_reserve),
     );
 }
 
 /// Get user's share balance for a specific outcome
+#[cfg(any(feature = "amm", test, feature = "testutils"))]
 #[allow(dead_code)]
 pub fn get_user_shares(env: &Env, user: &Address, market_id: &BytesN<32>, outcome: u32) -> u128 {
-    let key = if outcome == 1 {
-        (
-            Symbol::new(env, USER_SHARES_YES),
-            user.clone(),
+    env.storage()
+        .persistent()
+        .get(&AmmKey::UserShares(
             market_id.clone(),
-        )
-    } else {
-        (
-            Symbol::new(env, USER_SHARES_NO),
             user.clone(),
-            market_id.clone(),
-        )
-    };
-    env.storage().persistent().get(&key).unwrap_or(0)
+            outcome,
+        ))
+        .unwrap_or(0)
 }
 
 /// Update user's share balance for a specific outcome
+#[cfg(any(feature = "amm", test, feature = "testutils"))]
 #[allow(dead_code)]
 pub fn set_user_shares(
     env: &Env,
@@ -96,38 +128,30 @@ pub fn set_user_shares(
     outcome: u32,
     shares: u128,
 ) {
-    let key = if outcome == 1 {
-        (
-            Symbol::new(env, USER_SHARES_YES),
-            user.clone(),
-            market_id.clone(),
-        )
-    } else {
-        (
-            Symbol::new(env, USER_SHARES_NO),
-            user.clone(),
-            market_id.clone(),
-        )
-    };
-    env.storage().persistent().set(&key, &shares);
+    env.storage().persistent().set(
+        &AmmKey::UserShares(market_id.clone(), user.clone(), outcome),
+        &shares,
+    );
 }
 
 /// Get trade count for a market
+#[cfg(any(feature = "amm", test, feature = "testutils"))]
 #[allow(dead_code)]
 pub fn get_trade_count(env: &Env, market_id: &BytesN<32>) -> u32 {
     env.storage()
         .persistent()
-        .get(&(Symbol::new(env, TRADE_COUNT), market_id.clone()))
+        .get(&AmmKey::TradeCount(market_id.clone()))
         .unwrap_or(0)
 }
 
 /// Increment and return new trade count
+#[cfg(any(feature = "amm", test, feature = "testutils"))]
 #[allow(dead_code)]
 pub fn increment_trade_count(env: &Env, market_id: &BytesN<32>) -> u32 {
     let count = get_trade_count(env, market_id) + 1;
     env.storage()
         .persistent()
-        .set(&(Symbol::new(env, TRADE_COUNT), market_id.clone()), &count);
+        .set(&AmmKey::TradeCount(market_id.clone()), &count);
     count
 }
 
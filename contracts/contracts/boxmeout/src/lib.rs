@@ -13,18 +13,24 @@ pub mod oracle;
 pub mod treasury;
 
 pub mod helpers;
+pub mod upgrade;
 
-// Feature-gated exports for WASM builds
+// Feature-gated exports for WASM builds. Each contract defines its own
+// storage `DataKey`; with several features enabled use the module path.
 #[cfg(feature = "market")]
+#[allow(ambiguous_glob_reexports)]
 pub use market::*;
 
 #[cfg(feature = "oracle")]
+#[allow(ambiguous_glob_reexports)]
 pub use oracle::*;
 
 #[cfg(feature = "factory")]
+#[allow(ambiguous_glob_reexports)]
 pub use factory::*;
 
 #[cfg(feature = "treasury")]
+#[allow(ambiguous_glob_reexports)]
 pub use treasury::*;
 
 // AMM exports: available via feature flag OR during tests
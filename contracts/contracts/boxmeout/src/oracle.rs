@@ -2,15 +2,48 @@
 // Handles multi-source oracle consensus for market resolution
 
 use soroban_sdk::{
-    contract, contractevent, contractimpl, contracttype, Address, BytesN, Env, Symbol, Vec,
+    contract, contracterror, contractevent, contractimpl, contracttype, token, Address, Bytes,
+    BytesN, Env, Symbol, Vec,
 };
 
+use crate::helpers::{extend_instance, extend_persistent};
+use crate::upgrade::{self, PendingUpgrade, UpgradeError};
+
 #[contractevent]
 pub struct OracleInitializedEvent {
     pub admin: Address,
     pub required_consensus: u32,
 }
 
+// Default topic would exceed the 32-character symbol limit
+#[contractevent(topics = ["consensus_threshold_updated"])]
+pub struct ConsensusThresholdUpdatedEvent {
+    pub old_threshold: u32,
+    pub new_threshold: u32,
+}
+
+#[contractevent]
+pub struct SignerAddedEvent {
+    pub signer: Address,
+}
+
+#[contractevent]
+pub struct SignerRemovedEvent {
+    pub signer: Address,
+}
+
+#[contractevent]
+pub struct RequiredSigsUpdatedEvent {
+    pub old_required: u32,
+    pub new_required: u32,
+}
+
+#[contractevent]
+pub struct OverrideCooldownUpdatedEvent {
+    pub old_cooldown: u64,
+    pub new_cooldown: u64,
+}
+
 #[contractevent]
 pub struct OracleRegisteredEvent {
     pub oracle: Address,
@@ -24,10 +57,44 @@ pub struct OracleDeregisteredEvent {
     pub timestamp: u64,
 }
 
+#[contractevent]
+pub struct OracleReactivatedEvent {
+    pub oracle: Address,
+    pub timestamp: u64,
+}
+
+#[contractevent]
+pub struct StakingConfiguredEvent {
+    pub stake_token: Address,
+    pub min_stake: i128,
+    pub unbonding_period: u64,
+}
+
+#[contractevent]
+pub struct OracleStakedEvent {
+    pub oracle: Address,
+    pub amount: i128,
+    pub total_stake: i128,
+}
+
+#[contractevent]
+pub struct OracleUnstakedEvent {
+    pub oracle: Address,
+    pub amount: i128,
+    pub release_time: u64,
+}
+
+#[contractevent]
+pub struct StakeWithdrawnEvent {
+    pub oracle: Address,
+    pub amount: i128,
+}
+
 #[contractevent]
 pub struct MarketRegisteredEvent {
     pub market_id: BytesN<32>,
     pub resolution_time: u64,
+    pub consensus_mode: ConsensusMode,
 }
 
 #[contractevent]
@@ -37,6 +104,58 @@ pub struct AttestationSubmittedEvent {
     pub attestation_result: u32,
 }
 
+#[contractevent]
+pub struct CommitRevealEnabledEvent {
+    pub market_id: BytesN<32>,
+    pub commit_deadline: u64,
+    pub reveal_deadline: u64,
+}
+
+#[contractevent]
+pub struct AttestationCommittedEvent {
+    pub market_id: BytesN<32>,
+    pub oracle: Address,
+    pub commitment: BytesN<32>,
+}
+
+#[contractevent]
+pub struct RevealMissedEvent {
+    pub market_id: BytesN<32>,
+    pub oracle: Address,
+    pub new_reputation: u32,
+}
+
+#[contractevent]
+pub struct OptimisticEnabledEvent {
+    pub market_id: BytesN<32>,
+    pub bond: i128,
+    pub liveness: u64,
+}
+
+#[contractevent]
+pub struct OutcomeProposedEvent {
+    pub market_id: BytesN<32>,
+    pub proposer: Address,
+    pub outcome: u32,
+    pub bond: i128,
+    pub expires_at: u64,
+}
+
+#[contractevent]
+pub struct ProposalDisputedEvent {
+    pub market_id: BytesN<32>,
+    pub disputer: Address,
+}
+
+#[contractevent]
+pub struct ProposalSettledEvent {
+    pub market_id: BytesN<32>,
+    pub outcome: u32,
+    pub winner: Address,
+    pub payout: i128,
+    pub disputed: bool,
+}
+
 #[contractevent]
 pub struct ResolutionFinalizedEvent {
     pub market_id: BytesN<32>,
@@ -61,19 +180,348 @@ pub struct ChallengeResolvedEvent {
     pub slashed_amount: i128,
 }
 
-// Storage keys
-const ADMIN_KEY: &str = "admin";
-const REQUIRED_CONSENSUS_KEY: &str = "required_consensus";
-const ORACLE_COUNT_KEY: &str = "oracle_count";
-const MARKET_RES_TIME_KEY: &str = "mkt_res_time"; // Market resolution time storage
-const ATTEST_COUNT_YES_KEY: &str = "attest_yes"; // Attestation count for YES outcome
-const ATTEST_COUNT_NO_KEY: &str = "attest_no"; // Attestation count for NO outcome
-const ADMIN_SIGNERS_KEY: &str = "admin_signers"; // Multi-sig admin addresses
-const REQUIRED_SIGNATURES_KEY: &str = "required_sigs"; // Required signatures for multi-sig
-const LAST_OVERRIDE_TIME_KEY: &str = "last_override"; // Timestamp of last emergency override
-const OVERRIDE_COOLDOWN_KEY: &str = "override_cooldown"; // Cooldown period in seconds (default 86400 = 24h)
+#[contractevent]
+pub struct RewardsClaimedEvent {
+    pub claimant: Address,
+    pub amount: i128,
+}
+
+#[contractevent]
+pub struct OverrideProposedEvent {
+    pub proposal_id: u32,
+    pub market_id: BytesN<32>,
+    pub proposer: Address,
+    pub forced_outcome: u32,
+    pub expires_at: u64,
+}
+
+#[contractevent]
+pub struct OverrideApprovedEvent {
+    pub proposal_id: u32,
+    pub signer: Address,
+    pub approvals: u32,
+}
+
+#[contractevent]
+pub struct OverrideCancelledEvent {
+    pub proposal_id: u32,
+}
+
+/// Storage schema version written by this code
+const SCHEMA_VERSION: u32 = 1;
+
 const CHALLENGE_STAKE_AMOUNT: i128 = 1000; // Minimum stake required to challenge
-const ORACLE_STAKE_KEY: &str = "oracle_stake"; // Oracle's staked amount
+const MAX_ORACLES: u32 = 10;
+const BPS_DENOMINATOR: u32 = 10_000;
+const MISSED_REVEAL_PENALTY: u32 = 10; // Accuracy lost for committing without revealing
+const OVERRIDE_PROPOSAL_LIFETIME: u64 = 3 * 86400; // Seconds an override proposal stays open
+const DEFAULT_UNBONDING_PERIOD: u64 = 7 * 86400; // Seconds unstaked tokens stay slashable
+
+/// Storage keys
+///
+/// Admin, consensus and multisig settings are instance entries; oracle
+/// registrations and per-market resolution state are persistent.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum DataKey {
+    Admin,
+    RequiredConsensus,
+    OracleCount,
+    /// Multi-sig admin addresses
+    AdminSigners,
+    /// Required signatures for multi-sig
+    RequiredSignatures,
+    /// Timestamp of last emergency override
+    LastOverrideTime,
+    /// Cooldown period in seconds (default 86400 = 24h)
+    OverrideCooldown,
+    /// Token oracles lock as stake
+    StakeToken,
+    /// Bonded stake an oracle needs to attest
+    MinStake,
+    /// Seconds between unstake and withdrawal
+    UnbondingPeriod,
+    /// Whether the oracle is active
+    Oracle(Address),
+    OracleName(Address),
+    OracleAccuracy(Address),
+    /// Oracle's staked amount
+    OracleStake(Address),
+    OracleTimestamp(Address),
+    /// Stake waiting out the unbonding period
+    Unbonding(Address),
+    /// Unresolved challenges against the oracle, which lock its stake
+    OpenChallenges(Address),
+    /// Market resolution time
+    MarketResTime(BytesN<32>),
+    /// Attestation count for YES outcome
+    AttestCountYes(BytesN<32>),
+    /// Attestation count for NO outcome
+    AttestCountNo(BytesN<32>),
+    /// How votes on the market are counted
+    MarketConsensus(BytesN<32>),
+    /// Commit and reveal deadlines for a commit-reveal market
+    RevealWindow(BytesN<32>),
+    /// Oracles that committed on a commit-reveal market
+    Committers(BytesN<32>),
+    /// Whether missed reveals have been penalized
+    RevealsSettled(BytesN<32>),
+    /// Required consensus snapshotted when the market was registered
+    MarketThreshold(BytesN<32>),
+    /// Bond and liveness for optimistic resolution
+    Optimistic(BytesN<32>),
+    /// Bonded outcome proposal on an optimistic market
+    Proposal(BytesN<32>),
+    Vote(BytesN<32>, Address),
+    /// Weight of an oracle's vote, fixed when it attests on a weighted market
+    VoteWeight(BytesN<32>, Address),
+    /// Sealed attestation awaiting reveal
+    AttestCommit(BytesN<32>, Address),
+    Attestation(BytesN<32>, Address),
+    Voters(BytesN<32>),
+    Challenge(BytesN<32>, Address),
+    MarketChallenged(BytesN<32>),
+    ChallengerReward(Address),
+    OracleReward(Address),
+    ConsensusResult(BytesN<32>),
+    ManualOverride(BytesN<32>),
+    OverrideRecord(BytesN<32>),
+    /// Number of override proposals created, also the next proposal id
+    OverrideProposalCount,
+    /// Pending or closed override proposal by id
+    OverrideProposal(u32),
+    /// Every oracle ever registered, active or not, in registration order
+    OracleList,
+    /// Per-oracle activity counters
+    OracleStats(Address),
+    /// Number of markets with a consensus result
+    MarketsResolved,
+}
+
+/// Error codes returned by the oracle manager
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum OracleError {
+    /// Oracle manager has not been initialized
+    NotInitialized = 1,
+    /// Oracle set is full
+    MaxOraclesReached = 2,
+    /// Oracle is already registered
+    OracleAlreadyRegistered = 3,
+    /// Oracle is not registered or no longer active
+    OracleNotRegistered = 4,
+    /// Market has not been registered for resolution
+    MarketNotRegistered = 5,
+    /// Attestation submitted before the market's resolution time
+    ResolutionTimeNotReached = 6,
+    /// Outcome must be 0 (NO) or 1 (YES)
+    InvalidOutcome = 7,
+    /// Oracle already attested on this market
+    AlreadyAttested = 8,
+    /// Oracles have not reached consensus
+    ConsensusNotReached = 9,
+    /// Dispute period after resolution time has not elapsed
+    DisputePeriodNotElapsed = 10,
+    /// No consensus result has been recorded for this market
+    ConsensusResultNotFound = 11,
+    /// Oracle has no attestation on this market
+    AttestationNotFound = 12,
+    /// Attestation is already under challenge
+    ChallengeAlreadyExists = 13,
+    /// No challenge exists for this oracle and market
+    ChallengeNotFound = 14,
+    /// Challenge has already been resolved
+    ChallengeAlreadyResolved = 15,
+    // 16-19 are reported as `OverrideError`
+    /// Oracle is already inactive
+    OracleAlreadyInactive = 20,
+    /// Oracle is already active
+    OracleAlreadyActive = 21,
+    /// No stake token has been configured
+    StakingNotConfigured = 22,
+    /// Stake amount must be positive and within the oracle's stake
+    InvalidStakeAmount = 23,
+    /// Oracle's bonded stake is below the minimum
+    InsufficientStake = 24,
+    /// Oracle has nothing unbonding
+    NoUnbondingStake = 25,
+    /// Unbonding period has not elapsed
+    UnbondingNotElapsed = 26,
+    /// Address has no challenge or oracle rewards to claim
+    NoRewardsToClaim = 27,
+    /// Weighted threshold must be above 50% and at most 100%, quorum at least 1
+    InvalidConsensusConfig = 28,
+    /// Market takes sealed attestations through commit and reveal
+    CommitRevealRequired = 29,
+    /// Commit deadline has passed
+    CommitPhaseClosed = 30,
+    /// Reveals open at the commit deadline
+    RevealPhaseNotOpen = 31,
+    /// Reveal deadline has passed
+    RevealPhaseClosed = 32,
+    /// Reveal deadline has not passed yet
+    RevealPhaseNotOver = 33,
+    /// Oracle has no commitment on this market
+    CommitmentNotFound = 34,
+    /// Revealed vote does not hash to the commitment
+    CommitmentMismatch = 35,
+    /// Deadlines must follow resolution time in order, before any attestation
+    InvalidRevealWindow = 36,
+    /// Market does not use commit-reveal attestations
+    CommitRevealNotEnabled = 37,
+    /// Bond must be positive and liveness non-zero
+    InvalidOptimisticConfig = 38,
+    /// Market does not accept optimistic proposals
+    OptimisticNotEnabled = 39,
+    /// An outcome has already been proposed for this market
+    ProposalAlreadyExists = 40,
+    /// No outcome has been proposed for this market
+    ProposalNotFound = 41,
+    /// Proposal is already disputed
+    ProposalAlreadyDisputed = 42,
+    /// Liveness window has closed
+    LivenessElapsed = 43,
+    /// Liveness window is still open
+    LivenessNotElapsed = 44,
+    /// Proposal has already been settled
+    ProposalAlreadySettled = 45,
+    /// Threshold must be non-zero and no more than the active oracles
+    InvalidConsensusThreshold = 46,
+    /// Market is already registered
+    MarketAlreadyRegistered = 47,
+    /// Oracle manager is already initialized
+    AlreadyInitialized = 48,
+    /// Stake cannot leave while a challenge against the oracle is open
+    StakeUnderChallenge = 49,
+    /// Unbonding period must be non-zero
+    InvalidUnbondingPeriod = 50,
+    /// No upgrade has been proposed
+    NoPendingUpgrade = 100,
+    /// Hash differs from the proposed upgrade
+    WasmHashMismatch = 101,
+    /// Upgrade timelock has not elapsed
+    TimelockNotElapsed = 102,
+    /// Storage schema is already current
+    SchemaUpToDate = 103,
+}
+
+/// Error codes returned by multisig-governed override operations
+///
+/// Kept apart from `OracleError`, which is at the 50-case contract error
+/// limit.
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum OverrideError {
+    /// Oracle manager has not been initialized
+    NotInitialized = 200,
+    /// Fewer approvers than the required signatures
+    InsufficientApprovers = 201,
+    /// Approver is not an admin signer
+    InvalidApprover = 202,
+    /// Same admin listed more than once
+    DuplicateApprovers = 203,
+    /// Override cooldown has not elapsed
+    CooldownNotElapsed = 204,
+    /// Outcome must be 0 (NO) or 1 (YES)
+    InvalidOutcome = 205,
+    /// Market has not been registered with the oracle
+    MarketNotRegistered = 206,
+    /// Address is already an override signer
+    SignerAlreadyExists = 207,
+    /// Address is not an override signer
+    SignerNotFound = 208,
+    /// Required signatures must be between 1 and the number of signers
+    InvalidSignerConfig = 209,
+    /// No override proposal with this id
+    ProposalNotFound = 210,
+    /// Override proposal is past its expiry
+    ProposalExpired = 211,
+    /// Override proposal was already executed or cancelled
+    ProposalNotPending = 212,
+    /// Signer has already approved this proposal
+    AlreadyApproved = 213,
+    /// Only the proposer may cancel an override proposal
+    NotProposer = 214,
+}
+
+impl From<UpgradeError> for OracleError {
+    fn from(err: UpgradeError) -> Self {
+        match err {
+            UpgradeError::NoPendingUpgrade => OracleError::NoPendingUpgrade,
+            UpgradeError::WasmHashMismatch => OracleError::WasmHashMismatch,
+            UpgradeError::TimelockNotElapsed => OracleError::TimelockNotElapsed,
+            UpgradeError::SchemaUpToDate => OracleError::SchemaUpToDate,
+        }
+    }
+}
+
+/// What an oracle's vote is weighted by in weighted consensus
+#[contracttype]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum VoteWeighting {
+    /// Bonded stake
+    Stake,
+    /// Accuracy score (0-100)
+    Accuracy,
+    /// Bonded stake scaled by accuracy
+    StakeAndAccuracy,
+}
+
+/// Weighted consensus parameters for a market
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WeightedConsensus {
+    pub weighting: VoteWeighting,
+    /// Share of the voted weight the winning outcome needs, in basis points
+    pub threshold_bps: u32,
+    /// Minimum number of oracles that must vote
+    pub quorum: u32,
+}
+
+/// How attestations on a market are turned into consensus
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ConsensusMode {
+    /// One vote per oracle against the required consensus count
+    Count,
+    /// Votes weighted per oracle against a weight-fraction threshold
+    Weighted(WeightedConsensus),
+}
+
+/// Deadlines for a commit-reveal market
+///
+/// Commits are accepted from the resolution time until `commit_deadline`,
+/// reveals from then until `reveal_deadline`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RevealWindow {
+    pub commit_deadline: u64,
+    pub reveal_deadline: u64,
+}
+
+/// Optimistic resolution parameters for a market
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct OptimisticConfig {
+    /// Stake-token bond posted by the proposer and by a disputer
+    pub bond: i128,
+    /// Seconds a proposal can be disputed
+    pub liveness: u64,
+}
+
+/// Outcome proposed on an optimistic market
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct OptimisticProposal {
+    pub proposer: Address,
+    pub outcome: u32,
+    pub bond: i128,
+    pub expires_at: u64,
+    pub disputer: Option<Address>,
+    pub settled: bool,
+}
 
 /// Attestation record for market resolution
 #[contracttype]
@@ -81,9 +529,48 @@ const ORACLE_STAKE_KEY: &str = "oracle_stake"; // Oracle's staked amount
 pub struct Attestation {
     pub attestor: Address,
     pub outcome: u32,
+    pub data_hash: BytesN<32>,
     pub timestamp: u64,
 }
 
+/// Activity counters kept per oracle as events happen
+#[contracttype]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct OracleStats {
+    pub attestations: u32,
+    pub challenges_received: u32,
+    /// Challenges resolved in the oracle's favour
+    pub challenges_won: u32,
+    /// Challenges upheld against the oracle
+    pub challenges_lost: u32,
+    pub missed_reveals: u32,
+}
+
+/// Registration details, reputation and activity of an oracle
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct OracleInfo {
+    pub oracle: Address,
+    pub name: Symbol,
+    pub active: bool,
+    pub joined_at: u64,
+    pub accuracy: u32,
+    pub stake: i128,
+    pub stats: OracleStats,
+}
+
+/// Oracle-set performance summary
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ConsensusReport {
+    pub markets_resolved: u32,
+    pub total_attestations: u32,
+    pub total_challenges: u32,
+    /// Challenges upheld against the oracle
+    pub successful_challenges: u32,
+    pub oracles: Vec<OracleInfo>,
+}
+
 /// Emergency override approval record
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -100,9 +587,41 @@ pub struct EmergencyOverrideRecord {
     pub forced_outcome: u32,
     pub justification_hash: BytesN<32>,
     pub approvers: Vec<Address>,
+    /// Who approved and when; synchronous overrides share one timestamp
+    pub approvals: Vec<OverrideApproval>,
     pub timestamp: u64,
 }
 
+/// Lifecycle of an override proposal
+#[contracttype]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum OverrideStatus {
+    Pending,
+    Executed,
+    Cancelled,
+}
+
+/// Emergency override collecting approvals across transactions
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct OverrideProposal {
+    pub market_id: BytesN<32>,
+    pub forced_outcome: u32,
+    pub justification_hash: BytesN<32>,
+    pub proposer: Address,
+    pub approvals: Vec<OverrideApproval>,
+    pub expires_at: u64,
+    pub status: OverrideStatus,
+}
+
+/// Stake released by `unstake`, withdrawable after `release_time`
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct UnbondingStake {
+    pub amount: i128,
+    pub release_time: u64,
+}
+
 /// Challenge record for disputed attestations
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -123,47 +642,60 @@ pub struct OracleManager;
 #[contractimpl]
 impl OracleManager {
     /// Initialize oracle system with validator set and multi-sig admins
-    pub fn initialize(env: Env, admin: Address, required_consensus: u32) {
+    pub fn initialize(
+        env: Env,
+        admin: Address,
+        required_consensus: u32,
+    ) -> Result<(), OracleError> {
+        // Check if already initialized
+        if env.storage().instance().has(&DataKey::Admin) {
+            return Err(OracleError::AlreadyInitialized);
+        }
+
         // Verify admin signature
         admin.require_auth();
 
         // Store admin
-        env.storage()
-            .persistent()
-            .set(&Symbol::new(&env, ADMIN_KEY), &admin);
+        env.storage().instance().set(&DataKey::Admin, &admin);
 
         // Store required consensus threshold
-        env.storage().persistent().set(
-            &Symbol::new(&env, REQUIRED_CONSENSUS_KEY),
-            &required_consensus,
-        );
+        env.storage()
+            .instance()
+            .set(&DataKey::RequiredConsensus, &required_consensus);
 
         // Initialize oracle counter
+        env.storage().instance().set(&DataKey::OracleCount, &0u32);
+
+        // Default unbonding period: 7 days, until staking is configured
         env.storage()
-            .persistent()
-            .set(&Symbol::new(&env, ORACLE_COUNT_KEY), &0u32);
+            .instance()
+            .set(&DataKey::UnbondingPeriod, &DEFAULT_UNBONDING_PERIOD);
 
         // Initialize multi-sig with single admin (can be updated later)
         let mut admin_signers = Vec::new(&env);
         admin_signers.push_back(admin.clone());
         env.storage()
-            .persistent()
-            .set(&Symbol::new(&env, ADMIN_SIGNERS_KEY), &admin_signers);
+            .instance()
+            .set(&DataKey::AdminSigners, &admin_signers);
 
-        // Default: require 2 of 3 signatures for emergency override
+        // Default: require 2 signatures for emergency override (capped at the
+        // signer count until more signers are added)
         env.storage()
-            .persistent()
-            .set(&Symbol::new(&env, REQUIRED_SIGNATURES_KEY), &2u32);
+            .instance()
+            .set(&DataKey::RequiredSignatures, &2u32);
 
         // Default cooldown: 24 hours (86400 seconds)
         env.storage()
-            .persistent()
-            .set(&Symbol::new(&env, OVERRIDE_COOLDOWN_KEY), &86400u64);
+            .instance()
+            .set(&DataKey::OverrideCooldown, &86400u64);
 
         // Initialize last override time to 0
         env.storage()
-            .persistent()
-            .set(&Symbol::new(&env, LAST_OVERRIDE_TIME_KEY), &0u64);
+            .instance()
+            .set(&DataKey::LastOverrideTime, &0u64);
+
+        upgrade::init_schema_version(&env, SCHEMA_VERSION);
+        extend_instance(&env);
 
         // Emit initialization event
         OracleInitializedEvent {
@@ -171,69 +703,76 @@ impl OracleManager {
             required_consensus,
         }
         .publish(&env);
+
+        Ok(())
     }
 
     /// Register a new oracle node
-    pub fn register_oracle(env: Env, oracle: Address, oracle_name: Symbol) {
+    pub fn register_oracle(
+        env: Env,
+        oracle: Address,
+        oracle_name: Symbol,
+    ) -> Result<(), OracleError> {
         // Require admin authentication
-        let admin: Address = env
-            .storage()
-            .persistent()
-            .get(&Symbol::new(&env, ADMIN_KEY))
-            .unwrap();
-        admin.require_auth();
+        require_admin(&env)?;
 
         // Get current oracle count
         let oracle_count: u32 = env
             .storage()
-            .persistent()
-            .get(&Symbol::new(&env, ORACLE_COUNT_KEY))
+            .instance()
+            .get(&DataKey::OracleCount)
             .unwrap_or(0);
 
         // Validate total_oracles < max_oracles (max 10 oracles)
-        if oracle_count >= 10 {
-            panic!("Maximum oracle limit reached");
+        if oracle_count >= MAX_ORACLES {
+            return Err(OracleError::MaxOraclesReached);
         }
 
         // Create storage key for this oracle using the oracle address
-        let oracle_key = (Symbol::new(&env, "oracle"), oracle.clone());
+        let oracle_key = DataKey::Oracle(oracle.clone());
 
         // Check if oracle already registered
         let is_registered: bool = env.storage().persistent().has(&oracle_key);
 
         if is_registered {
-            panic!("Oracle already registered");
+            return Err(OracleError::OracleAlreadyRegistered);
         }
 
         // Store oracle metadata
         env.storage().persistent().set(&oracle_key, &true);
 
         // Store oracle name
-        let oracle_name_key = (Symbol::new(&env, "oracle_name"), oracle.clone());
+        let oracle_name_key = DataKey::OracleName(oracle.clone());
         env.storage()
             .persistent()
             .set(&oracle_name_key, &oracle_name);
 
         // Initialize oracle's accuracy score at 100%
-        let accuracy_key = (Symbol::new(&env, "oracle_accuracy"), oracle.clone());
+        let accuracy_key = DataKey::OracleAccuracy(oracle.clone());
         env.storage().persistent().set(&accuracy_key, &100u32);
 
-        // Initialize oracle's stake (required for slashing)
-        let stake_key = (Symbol::new(&env, ORACLE_STAKE_KEY), oracle.clone());
-        env.storage()
-            .persistent()
-            .set(&stake_key, &(CHALLENGE_STAKE_AMOUNT * 10)); // 10x challenge stake
+        // Stake starts empty; the oracle locks tokens through `stake`
+        let stake_key = DataKey::OracleStake(oracle.clone());
+        env.storage().persistent().set(&stake_key, &0i128);
 
         // Store registration timestamp
-        let timestamp_key = (Symbol::new(&env, "oracle_timestamp"), oracle.clone());
+        let timestamp_key = DataKey::OracleTimestamp(oracle.clone());
         env.storage()
             .persistent()
             .set(&timestamp_key, &env.ledger().timestamp());
 
-        // Increment oracle counter
+        // Increment oracle counter and add to the registry
         env.storage()
-            .persistent()
-            .set(&Symbol::new(&env, ORACLE_COUNT_KEY), &(oracle_count + 1));
+            .instance()
+            .set(&DataKey::OracleCount, &(oracle_count + 1));
+        let mut oracle_list = Self::get_oracles(env.clone());
+        oracle_list.push_back(oracle.clone());
+        env.storage()
+            .instance()
+            .set(&DataKey::OracleList, &oracle_list);
+
+        extend_instance(&env);
+        extend_oracle(&env, &oracle);
 
         // Emit OracleRegistered event
         OracleRegisteredEvent {
@@ -242,769 +781,2465 @@ impl OracleManager {
             timestamp: env.ledger().timestamp(),
         }
         .publish(&env);
+
+        Ok(())
     }
 
     /// Deregister an oracle node
     ///
-    /// TODO: Deregister Oracle
-    /// - Require admin authentication
-    /// - Validate oracle is registered
-    /// - Remove oracle from active_oracles list
-    /// - Mark as inactive (don't delete, keep for history)
-    /// - Prevent oracle from submitting new attestations
-    /// - Don't affect existing attestations
-    /// - Emit OracleDeregistered(oracle_address, timestamp)
-    pub fn deregister_oracle(_env: Env, _oracle: Address) {
-        todo!("See deregister oracle TODO above")
+    /// The oracle is marked inactive rather than deleted: its name, accuracy,
+    /// stake and past attestations are kept, and votes it already cast still
+    /// count. It can no longer submit new attestations until reactivated.
+    pub fn deregister_oracle(env: Env, oracle: Address) -> Result<(), OracleError> {
+        require_admin(&env)?;
+
+        let oracle_key = DataKey::Oracle(oracle.clone());
+        let is_active: bool = env
+            .storage()
+            .persistent()
+            .get(&oracle_key)
+            .ok_or(OracleError::OracleNotRegistered)?;
+        if !is_active {
+            return Err(OracleError::OracleAlreadyInactive);
+        }
+
+        deactivate_oracle(&env, &oracle);
+        extend_instance(&env);
+        extend_oracle(&env, &oracle);
+
+        Ok(())
     }
 
-    /// Register a market with its resolution time for attestation validation
-    /// Must be called before oracles can submit attestations for this market.
-    pub fn register_market(env: Env, market_id: BytesN<32>, resolution_time: u64) {
-        // Require admin authentication
-        let admin: Address = env
+    /// Reactivate a previously deregistered oracle
+    ///
+    /// Counts against the oracle limit again; history is carried over as-is.
+    pub fn reactivate_oracle(env: Env, oracle: Address) -> Result<(), OracleError> {
+        require_admin(&env)?;
+
+        let oracle_key = DataKey::Oracle(oracle.clone());
+        let is_active: bool = env
             .storage()
             .persistent()
-            .get(&Symbol::new(&env, ADMIN_KEY))
-            .expect("Oracle not initialized");
-        admin.require_auth();
+            .get(&oracle_key)
+            .ok_or(OracleError::OracleNotRegistered)?;
+        if is_active {
+            return Err(OracleError::OracleAlreadyActive);
+        }
 
-        // Store market resolution time
-        let market_key = (Symbol::new(&env, MARKET_RES_TIME_KEY), market_id.clone());
+        let oracle_count: u32 = env
+            .storage()
+            .instance()
+            .get(&DataKey::OracleCount)
+            .unwrap_or(0);
+        if oracle_count >= MAX_ORACLES {
+            return Err(OracleError::MaxOraclesReached);
+        }
+
+        env.storage().persistent().set(&oracle_key, &true);
         env.storage()
-            .persistent()
-            .set(&market_key, &resolution_time);
+            .instance()
+            .set(&DataKey::OracleCount, &(oracle_count + 1));
 
-        // Initialize attestation counts for this market
-        let yes_count_key = (Symbol::new(&env, ATTEST_COUNT_YES_KEY), market_id.clone());
-        let no_count_key = (Symbol::new(&env, ATTEST_COUNT_NO_KEY), market_id.clone());
-        env.storage().persistent().set(&yes_count_key, &0u32);
-        env.storage().persistent().set(&no_count_key, &0u32);
+        extend_instance(&env);
+        extend_oracle(&env, &oracle);
 
-        // Emit market registered event
-        MarketRegisteredEvent {
-            market_id,
-            resolution_time,
+        OracleReactivatedEvent {
+            oracle,
+            timestamp: env.ledger().timestamp(),
         }
         .publish(&env);
-    }
 
-    /// Get market resolution time (helper function)
-    pub fn get_market_resolution_time(env: Env, market_id: BytesN<32>) -> Option<u64> {
-        let market_key = (Symbol::new(&env, MARKET_RES_TIME_KEY), market_id);
-        env.storage().persistent().get(&market_key)
+        Ok(())
     }
 
-    /// Get attestation counts for a market
-    pub fn get_attestation_counts(env: Env, market_id: BytesN<32>) -> (u32, u32) {
-        let yes_count_key = (Symbol::new(&env, ATTEST_COUNT_YES_KEY), market_id.clone());
-        let no_count_key = (Symbol::new(&env, ATTEST_COUNT_NO_KEY), market_id);
-
-        let yes_count: u32 = env.storage().persistent().get(&yes_count_key).unwrap_or(0);
-        let no_count: u32 = env.storage().persistent().get(&no_count_key).unwrap_or(0);
-
-        (yes_count, no_count)
+    /// Check whether an oracle is registered and currently active
+    pub fn is_oracle_active(env: Env, oracle: Address) -> bool {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Oracle(oracle))
+            .unwrap_or(false)
     }
 
-    /// Get attestation record for an oracle on a market
-    pub fn get_attestation(
-        env: Env,
-        market_id: BytesN<32>,
-        oracle: Address,
-    ) -> Option<Attestation> {
-        let attestation_key = (Symbol::new(&env, "attestation"), market_id, oracle);
-        env.storage().persistent().get(&attestation_key)
+    /// Get the number of active oracles
+    pub fn get_oracle_count(env: Env) -> u32 {
+        env.storage()
+            .instance()
+            .get(&DataKey::OracleCount)
+            .unwrap_or(0)
     }
 
-    /// Submit oracle attestation for market result
+    /// Admin: Set the stake token, minimum bonded stake and unbonding period
     ///
-    /// Validates:
-    /// - Caller is a trusted attestor (registered oracle)
-    /// - Market is past resolution_time
-    /// - Outcome is valid (0=NO, 1=YES)
-    /// - Oracle hasn't already attested
-    pub fn submit_attestation(
+    /// Oracles below `min_stake` stay registered but cannot attest. The
+    /// unbonding period must be non-zero so unstaked tokens stay slashable.
+    pub fn configure_staking(
         env: Env,
-        oracle: Address,
-        market_id: BytesN<32>,
-        attestation_result: u32,
-        _data_hash: BytesN<32>,
-    ) {
-        // 1. Require oracle authentication
-        oracle.require_auth();
+        stake_token: Address,
+        min_stake: i128,
+        unbonding_period: u64,
+    ) -> Result<(), OracleError> {
+        require_admin(&env)?;
+
+        if min_stake < 0 {
+            return Err(OracleError::InvalidStakeAmount);
+        }
+        if unbonding_period == 0 {
+            return Err(OracleError::InvalidUnbondingPeriod);
+        }
 
-        // 2. Validate oracle is registered (trusted attestor)
-        let oracle_key = (Symbol::new(&env, "oracle"), oracle.clone());
-        let is_registered: bool = env.storage().persistent().get(&oracle_key).unwrap_or(false);
-        if !is_registered {
-            panic!("Oracle not registered");
+        env.storage()
+            .instance()
+            .set(&DataKey::StakeToken, &stake_token);
+        env.storage().instance().set(&DataKey::MinStake, &min_stake);
+        env.storage()
+            .instance()
+            .set(&DataKey::UnbondingPeriod, &unbonding_period);
+        extend_instance(&env);
+
+        StakingConfiguredEvent {
+            stake_token,
+            min_stake,
+            unbonding_period,
         }
+        .publish(&env);
 
-        // 3. Validate market is registered and past resolution_time
-        let market_key = (Symbol::new(&env, MARKET_RES_TIME_KEY), market_id.clone());
-        let resolution_time: u64 = env
+        Ok(())
+    }
+
+    /// Oracle: Lock stake tokens in the contract
+    pub fn stake(env: Env, oracle: Address, amount: i128) -> Result<(), OracleError> {
+        oracle.require_auth();
+
+        if amount <= 0 {
+            return Err(OracleError::InvalidStakeAmount);
+        }
+        if !env
             .storage()
             .persistent()
-            .get(&market_key)
-            .expect("Market not registered");
-
-        let current_time = env.ledger().timestamp();
-        if current_time < resolution_time {
-            panic!("Cannot attest before resolution time");
+            .has(&DataKey::Oracle(oracle.clone()))
+        {
+            return Err(OracleError::OracleNotRegistered);
         }
+        let stake_token = get_stake_token(&env)?;
 
-        // 4. Validate result is binary (0 or 1)
-        if attestation_result > 1 {
-            panic!("Invalid attestation result");
+        let contract_address = env.current_contract_address();
+        token::TokenClient::new(&env, &stake_token).transfer(&oracle, &contract_address, &amount);
+
+        let stake_key = DataKey::OracleStake(oracle.clone());
+        let total_stake: i128 = env.storage().persistent().get(&stake_key).unwrap_or(0) + amount;
+        env.storage().persistent().set(&stake_key, &total_stake);
+
+        extend_instance(&env);
+        extend_oracle(&env, &oracle);
+
+        OracleStakedEvent {
+            oracle,
+            amount,
+            total_stake,
         }
+        .publish(&env);
 
-        // 5. Check if oracle already attested
-        let vote_key = (Symbol::new(&env, "vote"), market_id.clone(), oracle.clone());
-        if env.storage().persistent().has(&vote_key) {
-            panic!("Oracle already attested");
+        Ok(())
+    }
+
+    /// Oracle: Start unbonding part of the stake
+    ///
+    /// Unbonding stake no longer counts toward the minimum but can still be
+    /// slashed. A further unstake restarts the unbonding period for the total.
+    /// Not allowed while a challenge against the oracle is open.
+    pub fn unstake(env: Env, oracle: Address, amount: i128) -> Result<u64, OracleError> {
+        oracle.require_auth();
+        require_unchallenged(&env, &oracle)?;
+
+        let stake_key = DataKey::OracleStake(oracle.clone());
+        let bonded: i128 = env.storage().persistent().get(&stake_key).unwrap_or(0);
+        if amount <= 0 || amount > bonded {
+            return Err(OracleError::InvalidStakeAmount);
         }
 
-        // 6. Store vote for consensus
-        env.storage()
+        let unbonding_period: u64 = env
+            .storage()
+            .instance()
+            .get(&DataKey::UnbondingPeriod)
+            .unwrap_or(DEFAULT_UNBONDING_PERIOD);
+        let release_time = env.ledger().timestamp() + unbonding_period;
+
+        let unbonding_key = DataKey::Unbonding(oracle.clone());
+        let pending: i128 = env
+            .storage()
             .persistent()
-            .set(&vote_key, &attestation_result);
+            .get::<_, UnbondingStake>(&unbonding_key)
+            .map(|u| u.amount)
+            .unwrap_or(0);
 
-        // 7. Store attestation with timestamp
-        let attestation = Attestation {
-            attestor: oracle.clone(),
-            outcome: attestation_result,
-            timestamp: current_time,
-        };
-        let attestation_key = (
-            Symbol::new(&env, "attestation"),
-            market_id.clone(),
-            oracle.clone(),
-        );
         env.storage()
             .persistent()
-            .set(&attestation_key, &attestation);
+            .set(&stake_key, &(bonded - amount));
+        env.storage().persistent().set(
+            &unbonding_key,
+            &UnbondingStake {
+                amount: pending + amount,
+                release_time,
+            },
+        );
+
+        extend_instance(&env);
+        extend_oracle(&env, &oracle);
+
+        OracleUnstakedEvent {
+            oracle,
+            amount,
+            release_time,
+        }
+        .publish(&env);
+
+        Ok(release_time)
+    }
+
+    /// Oracle: Withdraw stake whose unbonding period has elapsed
+    ///
+    /// Not allowed while a challenge against the oracle is open.
+    pub fn withdraw_stake(env: Env, oracle: Address) -> Result<i128, OracleError> {
+        oracle.require_auth();
+        require_unchallenged(&env, &oracle)?;
 
-        // 8. Track oracle in market's voter list
-        let voters_key = (Symbol::new(&env, "voters"), market_id.clone());
-        let mut voters: Vec<Address> = env
+        let unbonding_key = DataKey::Unbonding(oracle.clone());
+        let unbonding: UnbondingStake = env
             .storage()
             .persistent()
-            .get(&voters_key)
-            .unwrap_or(Vec::new(&env));
+            .get(&unbonding_key)
+            .ok_or(OracleError::NoUnbondingStake)?;
+        if env.ledger().timestamp() < unbonding.release_time {
+            return Err(OracleError::UnbondingNotElapsed);
+        }
 
-        voters.push_back(oracle.clone());
-        env.storage().persistent().set(&voters_key, &voters);
+        env.storage().persistent().remove(&unbonding_key);
+        let stake_token = get_stake_token(&env)?;
+        token::TokenClient::new(&env, &stake_token).transfer(
+            &env.current_contract_address(),
+            &oracle,
+            &unbonding.amount,
+        );
 
-        // 9. Update attestation count per outcome
-        if attestation_result == 1 {
-            let yes_count_key = (Symbol::new(&env, ATTEST_COUNT_YES_KEY), market_id.clone());
-            let current_count: u32 = env.storage().persistent().get(&yes_count_key).unwrap_or(0);
-            env.storage()
-                .persistent()
-                .set(&yes_count_key, &(current_count + 1));
-        } else {
-            let no_count_key = (Symbol::new(&env, ATTEST_COUNT_NO_KEY), market_id.clone());
-            let current_count: u32 = env.storage().persistent().get(&no_count_key).unwrap_or(0);
-            env.storage()
-                .persistent()
-                .set(&no_count_key, &(current_count + 1));
-        }
+        extend_instance(&env);
 
-        // 10. Emit AttestationSubmitted(market_id, attestor, outcome)
-        AttestationSubmittedEvent {
-            market_id,
+        StakeWithdrawnEvent {
             oracle,
-            attestation_result,
+            amount: unbonding.amount,
         }
         .publish(&env);
+
+        Ok(unbonding.amount)
     }
 
-    /// Check if consensus has been reached for market
-    pub fn check_consensus(env: Env, market_id: BytesN<32>) -> (bool, u32) {
-        // 1. Query attestations for market_id
-        let voters_key = (Symbol::new(&env, "voters"), market_id.clone());
-        let voters: Vec<Address> = env
-            .storage()
+    /// Get stake an oracle has waiting out the unbonding period
+    pub fn get_unbonding_stake(env: Env, oracle: Address) -> Option<UnbondingStake> {
+        env.storage().persistent().get(&DataKey::Unbonding(oracle))
+    }
+
+    /// Get the configured stake token, if staking has been set up
+    pub fn get_stake_token(env: Env) -> Option<Address> {
+        env.storage().instance().get(&DataKey::StakeToken)
+    }
+
+    /// Get the minimum bonded stake required to attest
+    pub fn get_min_stake(env: Env) -> i128 {
+        env.storage()
+            .instance()
+            .get(&DataKey::MinStake)
+            .unwrap_or(0)
+    }
+
+    /// Register a market with its resolution time for attestation validation
+    /// Must be called before oracles can submit attestations for this market.
+    ///
+    /// `consensus_mode` selects flat vote counting or weighted consensus.
+    pub fn register_market(
+        env: Env,
+        market_id: BytesN<32>,
+        resolution_time: u64,
+        consensus_mode: ConsensusMode,
+    ) -> Result<(), OracleError> {
+        // Require admin authentication
+        require_admin(&env)?;
+
+        // Re-registering would reset the threshold snapshot and votes mid-vote
+        let market_key = DataKey::MarketResTime(market_id.clone());
+        if env.storage().persistent().has(&market_key) {
+            return Err(OracleError::MarketAlreadyRegistered);
+        }
+
+        if let ConsensusMode::Weighted(config) = &consensus_mode {
+            if config.threshold_bps <= BPS_DENOMINATOR / 2
+                || config.threshold_bps > BPS_DENOMINATOR
+                || config.quorum == 0
+            {
+                return Err(OracleError::InvalidConsensusConfig);
+            }
+        }
+
+        // Store market resolution time
+        env.storage()
             .persistent()
-            .get(&voters_key)
-            .unwrap_or(Vec::new(&env));
+            .set(&market_key, &resolution_time);
+
+        // Initialize attestation counts for this market
+        let yes_count_key = DataKey::AttestCountYes(market_id.clone());
+        let no_count_key = DataKey::AttestCountNo(market_id.clone());
+        env.storage().persistent().set(&yes_count_key, &0u32);
+        env.storage().persistent().set(&no_count_key, &0u32);
 
-        // 2. Get required threshold
+        env.storage().persistent().set(
+            &DataKey::MarketConsensus(market_id.clone()),
+            &consensus_mode,
+        );
+
+        // Later threshold changes must not move the goalposts mid-vote
         let threshold: u32 = env
             .storage()
-            .persistent()
-            .get(&Symbol::new(&env, REQUIRED_CONSENSUS_KEY))
+            .instance()
+            .get(&DataKey::RequiredConsensus)
             .unwrap_or(0);
+        env.storage()
+            .persistent()
+            .set(&DataKey::MarketThreshold(market_id.clone()), &threshold);
 
-        if voters.len() < threshold {
-            return (false, 0);
-        }
-
-        // 3. Count votes for each outcome
-        let mut yes_votes = 0;
-        let mut no_votes = 0;
+        extend_instance(&env);
+        extend_market(&env, &market_id);
 
-        for oracle in voters.iter() {
-            let vote_key = (Symbol::new(&env, "vote"), market_id.clone(), oracle);
-            let vote: u32 = env.storage().persistent().get(&vote_key).unwrap_or(0);
-            if vote == 1 {
-                yes_votes += 1;
-            } else {
-                no_votes += 1;
-            }
+        // Emit market registered event
+        MarketRegisteredEvent {
+            market_id,
+            resolution_time,
+            consensus_mode,
         }
+        .publish(&env);
 
-        // 4. Compare counts against threshold
-        // Winner is the one that reached the threshold first
-        // If both reach threshold (possible if threshold is low), we favor the one with more votes
-        // If tied and both >= threshold, return false (no clear winner yet)
-        if yes_votes >= threshold && yes_votes > no_votes {
-            (true, 1)
-        } else if no_votes >= threshold && no_votes > yes_votes {
-            (true, 0)
-        } else if yes_votes >= threshold && no_votes >= threshold && yes_votes == no_votes {
-            // Tie scenario appropriately handled: no consensus if tied but threshold met
-            (false, 0)
-        } else {
-            (false, 0)
-        }
+        Ok(())
     }
 
-    /// Get the consensus result for a market
-    pub fn get_consensus_result(env: Env, market_id: BytesN<32>) -> u32 {
-        let result_key = (Symbol::new(&env, "consensus_result"), market_id.clone());
+    /// Get how votes on a market are counted
+    ///
+    /// Markets registered before consensus modes existed use `Count`.
+    pub fn get_consensus_mode(env: Env, market_id: BytesN<32>) -> ConsensusMode {
         env.storage()
             .persistent()
-            .get(&result_key)
-            .expect("Consensus result not found")
+            .get(&DataKey::MarketConsensus(market_id))
+            .unwrap_or(ConsensusMode::Count)
     }
 
-    /// Finalize market resolution after consensus and dispute period
+    /// Get the consensus threshold a market votes against
     ///
-    /// Called after consensus reached and dispute period elapsed.
-    /// Makes cross-contract call to Market.resolve_market().
-    /// Locks in final outcome permanently.
-    pub fn finalize_resolution(env: Env, market_id: BytesN<32>, _market_address: Address) {
-        // 1. Validate market is registered
-        let market_key = (Symbol::new(&env, MARKET_RES_TIME_KEY), market_id.clone());
-        let resolution_time: u64 = env
-            .storage()
+    /// Markets registered before snapshotting existed use the current global
+    /// threshold.
+    pub fn get_market_threshold(env: Env, market_id: BytesN<32>) -> u32 {
+        env.storage()
             .persistent()
-            .get(&market_key)
-            .expect("Market not registered");
+            .get(&DataKey::MarketThreshold(market_id))
+            .unwrap_or_else(|| Self::get_consensus_threshold(env.clone()))
+    }
 
-        // 2. Validate consensus reached
-        let (consensus_reached, final_outcome) =
-            Self::check_consensus(env.clone(), market_id.clone());
-        if !consensus_reached {
-            panic!("Consensus not reached");
-        }
+    /// Get market resolution time (helper function)
+    pub fn get_market_resolution_time(env: Env, market_id: BytesN<32>) -> Option<u64> {
+        let market_key = DataKey::MarketResTime(market_id);
+        env.storage().persistent().get(&market_key)
+    }
 
-        // 3. Validate dispute period elapsed (7 days = 604800 seconds)
-        let current_time = env.ledger().timestamp();
-        let dispute_period = 604800u64;
-        if current_time < resolution_time + dispute_period {
-            panic!("Dispute period not elapsed");
-        }
+    /// Get attestation counts for a market
+    pub fn get_attestation_counts(env: Env, market_id: BytesN<32>) -> (u32, u32) {
+        let yes_count_key = DataKey::AttestCountYes(market_id.clone());
+        let no_count_key = DataKey::AttestCountNo(market_id);
+
+        let yes_count: u32 = env.storage().persistent().get(&yes_count_key).unwrap_or(0);
+        let no_count: u32 = env.storage().persistent().get(&no_count_key).unwrap_or(0);
 
-        // 4. Store consensus result permanently
-        let result_key = (Symbol::new(&env, "consensus_result"), market_id.clone());
-        env.storage().persistent().set(&result_key, &final_outcome);
+        (yes_count, no_count)
+    }
 
-        // 5. Cross-contract call to Market.resolve_market()
-        #[cfg(feature = "market")]
+    /// Get attestation record for an oracle on a market
+    pub fn get_attestation(
+        env: Env,
+        market_id: BytesN<32>,
+        oracle: Address,
+    ) -> Option<Attestation> {
+        let attestation_key = DataKey::Attestation(market_id, oracle);
+        env.storage().persistent().get(&attestation_key)
+    }
+
+    /// Admin: Switch a registered market to commit-reveal attestations
+    ///
+    /// Must be set before any oracle attests. Afterwards `submit_attestation`
+    /// is rejected for the market and only revealed votes count.
+    pub fn enable_commit_reveal(
+        env: Env,
+        market_id: BytesN<32>,
+        commit_deadline: u64,
+        reveal_deadline: u64,
+    ) -> Result<(), OracleError> {
+        require_admin(&env)?;
+
+        let resolution_time: u64 = env
+            .storage()
+            .persistent()
+            .get(&DataKey::MarketResTime(market_id.clone()))
+            .ok_or(OracleError::MarketNotRegistered)?;
+        let has_attestations = env
+            .storage()
+            .persistent()
+            .has(&DataKey::Voters(market_id.clone()))
+            || env
+                .storage()
+                .persistent()
+                .has(&DataKey::Committers(market_id.clone()));
+        if commit_deadline <= resolution_time
+            || reveal_deadline <= commit_deadline
+            || has_attestations
         {
-            use crate::market::PredictionMarketClient;
-            let market_client = PredictionMarketClient::new(&env, &_market_address);
-            market_client.resolve_market(&market_id);
+            return Err(OracleError::InvalidRevealWindow);
         }
 
-        // 6. Emit ResolutionFinalized event
-        ResolutionFinalizedEvent {
+        env.storage().persistent().set(
+            &DataKey::RevealWindow(market_id.clone()),
+            &RevealWindow {
+                commit_deadline,
+                reveal_deadline,
+            },
+        );
+
+        extend_instance(&env);
+        extend_market(&env, &market_id);
+
+        CommitRevealEnabledEvent {
             market_id,
-            final_outcome,
-            timestamp: current_time,
+            commit_deadline,
+            reveal_deadline,
         }
         .publish(&env);
+
+        Ok(())
     }
 
-    /// Challenge an attestation (dispute oracle honesty)
+    /// Get a market's commit and reveal deadlines, if it uses commit-reveal
+    pub fn get_reveal_window(env: Env, market_id: BytesN<32>) -> Option<RevealWindow> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::RevealWindow(market_id))
+    }
+
+    /// Hash an oracle commits to: sha256(outcome || data_hash || salt)
     ///
-    /// Allows users to challenge attestations with stake.
-    /// Requires challenger to put up stake that will be slashed if challenge is invalid.
-    pub fn challenge_attestation(
+    /// `outcome` is encoded as 4 big-endian bytes.
+    pub fn attestation_commitment(
+        env: Env,
+        outcome: u32,
+        data_hash: BytesN<32>,
+        salt: BytesN<32>,
+    ) -> BytesN<32> {
+        let mut input = Bytes::new(&env);
+        input.extend_from_array(&outcome.to_be_bytes());
+        input.extend_from_array(&data_hash.to_array());
+        input.extend_from_array(&salt.to_array());
+        env.crypto().sha256(&input).into()
+    }
+
+    /// Commit a sealed attestation on a commit-reveal market
+    pub fn commit_attestation(
         env: Env,
-        challenger: Address,
         oracle: Address,
         market_id: BytesN<32>,
-        challenge_reason: Symbol,
-    ) {
-        // 1. Require challenger authentication
-        challenger.require_auth();
+        commitment: BytesN<32>,
+    ) -> Result<(), OracleError> {
+        oracle.require_auth();
+        require_attestor(&env, &oracle)?;
 
-        // 2. Validate oracle is registered
-        let oracle_key = (Symbol::new(&env, "oracle"), oracle.clone());
-        let is_registered: bool = env.storage().persistent().get(&oracle_key).unwrap_or(false);
-        if !is_registered {
-            panic!("Oracle not registered");
-        }
+        let window = Self::get_reveal_window(env.clone(), market_id.clone())
+            .ok_or(OracleError::CommitRevealNotEnabled)?;
+        let resolution_time: u64 = env
+            .storage()
+            .persistent()
+            .get(&DataKey::MarketResTime(market_id.clone()))
+            .ok_or(OracleError::MarketNotRegistered)?;
 
-        // 3. Validate attestation exists
-        let attestation_key = (
-            Symbol::new(&env, "attestation"),
-            market_id.clone(),
-            oracle.clone(),
-        );
-        let attestation: Option<Attestation> = env.storage().persistent().get(&attestation_key);
-        if attestation.is_none() {
-            panic!("Attestation not found");
+        let current_time = env.ledger().timestamp();
+        if current_time < resolution_time {
+            return Err(OracleError::ResolutionTimeNotReached);
         }
-
-        // 4. Check if challenge already exists for this oracle/market
-        let challenge_key = (
-            Symbol::new(&env, "challenge"),
-            market_id.clone(),
-            oracle.clone(),
-        );
-        if env.storage().persistent().has(&challenge_key) {
-            panic!("Challenge already exists");
+        if current_time >= window.commit_deadline {
+            return Err(OracleError::CommitPhaseClosed);
         }
 
-        // 5. Create challenge record
-        let challenge = Challenge {
-            challenger: challenger.clone(),
-            oracle: oracle.clone(),
-            market_id: market_id.clone(),
-            reason: challenge_reason.clone(),
-            stake: CHALLENGE_STAKE_AMOUNT,
-            timestamp: env.ledger().timestamp(),
-            resolved: false,
-        };
+        let commit_key = DataKey::AttestCommit(market_id.clone(), oracle.clone());
+        if env.storage().persistent().has(&commit_key) {
+            return Err(OracleError::AlreadyAttested);
+        }
+        env.storage().persistent().set(&commit_key, &commitment);
 
-        // 6. Store challenge
-        env.storage().persistent().set(&challenge_key, &challenge);
+        let committers_key = DataKey::Committers(market_id.clone());
+        let mut committers: Vec<Address> = env
+            .storage()
+            .persistent()
+            .get(&committers_key)
+            .unwrap_or(Vec::new(&env));
+        committers.push_back(oracle.clone());
+        env.storage().persistent().set(&committers_key, &committers);
 
-        // 7. Mark market as having active challenge (pause finalization)
-        let market_challenge_key = (Symbol::new(&env, "market_challenged"), market_id.clone());
-        env.storage().persistent().set(&market_challenge_key, &true);
+        extend_instance(&env);
+        extend_oracle(&env, &oracle);
+        extend_market(&env, &market_id);
 
-        // 8. Emit AttestationChallenged event
-        AttestationChallengedEvent {
-            oracle,
-            challenger,
+        AttestationCommittedEvent {
             market_id,
-            challenge_reason,
+            oracle,
+            commitment,
         }
         .publish(&env);
+
+        Ok(())
     }
 
-    /// Resolve a challenge and update oracle reputation
-    ///
-    /// Admin arbitration or multi-oracle re-vote to resolve challenges.
-    /// Slashes dishonest oracle's stake on successful challenge.
-    pub fn resolve_challenge(
+    /// Reveal a committed attestation; only revealed votes count toward consensus
+    pub fn reveal_attestation(
         env: Env,
         oracle: Address,
         market_id: BytesN<32>,
-        challenge_valid: bool,
-    ) {
-        // 1. Require admin authentication
-        let admin: Address = env
-            .storage()
+        attestation_result: u32,
+        data_hash: BytesN<32>,
+        salt: BytesN<32>,
+    ) -> Result<(), OracleError> {
+        oracle.require_auth();
+
+        // Still registered and staked when the vote starts to count
+        require_attestor(&env, &oracle)?;
+
+        let window = Self::get_reveal_window(env.clone(), market_id.clone())
+            .ok_or(OracleError::CommitRevealNotEnabled)?;
+        let current_time = env.ledger().timestamp();
+        if current_time < window.commit_deadline {
+            return Err(OracleError::RevealPhaseNotOpen);
+        }
+        if current_time >= window.reveal_deadline {
+            return Err(OracleError::RevealPhaseClosed);
+        }
+
+        let commitment: BytesN<32> = env
+            .storage()
+            .persistent()
+            .get(&DataKey::AttestCommit(market_id.clone(), oracle.clone()))
+            .ok_or(OracleError::CommitmentNotFound)?;
+        if env
+            .storage()
+            .persistent()
+            .has(&DataKey::Vote(market_id.clone(), oracle.clone()))
+        {
+            return Err(OracleError::AlreadyAttested);
+        }
+        if Self::attestation_commitment(env.clone(), attestation_result, data_hash.clone(), salt)
+            != commitment
+        {
+            return Err(OracleError::CommitmentMismatch);
+        }
+        if attestation_result > 1 {
+            return Err(OracleError::InvalidOutcome);
+        }
+
+        record_vote(&env, &oracle, &market_id, attestation_result, data_hash);
+
+        extend_instance(&env);
+        extend_oracle(&env, &oracle);
+        extend_market(&env, &market_id);
+
+        AttestationSubmittedEvent {
+            market_id,
+            oracle,
+            attestation_result,
+        }
+        .publish(&env);
+
+        Ok(())
+    }
+
+    /// Penalize oracles that committed on a market but never revealed
+    ///
+    /// Permissionless once the reveal deadline has passed; each missed reveal
+    /// costs accuracy, and oracles that fall below 50% are deregistered.
+    /// Returns the number of oracles penalized (0 if already settled).
+    pub fn settle_reveals(env: Env, market_id: BytesN<32>) -> Result<u32, OracleError> {
+        let window = Self::get_reveal_window(env.clone(), market_id.clone())
+            .ok_or(OracleError::CommitRevealNotEnabled)?;
+        if env.ledger().timestamp() < window.reveal_deadline {
+            return Err(OracleError::RevealPhaseNotOver);
+        }
+
+        let penalized = settle_missed_reveals(&env, &market_id);
+        extend_instance(&env);
+        extend_market(&env, &market_id);
+        Ok(penalized)
+    }
+
+    /// Submit oracle attestation for market result
+    ///
+    /// Validates:
+    /// - Caller is a trusted attestor (registered oracle)
+    /// - Market is past resolution_time
+    /// - Outcome is valid (0=NO, 1=YES)
+    /// - Oracle hasn't already attested
+    pub fn submit_attestation(
+        env: Env,
+        oracle: Address,
+        market_id: BytesN<32>,
+        attestation_result: u32,
+        data_hash: BytesN<32>,
+    ) -> Result<(), OracleError> {
+        // 1. Require oracle authentication
+        oracle.require_auth();
+
+        // 2. Validate oracle is registered (trusted attestor) and staked
+        require_attestor(&env, &oracle)?;
+
+        // 3. Validate market is registered and past resolution_time
+        let market_key = DataKey::MarketResTime(market_id.clone());
+        let resolution_time: u64 = env
+            .storage()
+            .persistent()
+            .get(&market_key)
+            .ok_or(OracleError::MarketNotRegistered)?;
+
+        let current_time = env.ledger().timestamp();
+        if current_time < resolution_time {
+            return Err(OracleError::ResolutionTimeNotReached);
+        }
+        if env
+            .storage()
+            .persistent()
+            .has(&DataKey::RevealWindow(market_id.clone()))
+        {
+            return Err(OracleError::CommitRevealRequired);
+        }
+
+        // 4. Validate result is binary (0 or 1)
+        if attestation_result > 1 {
+            return Err(OracleError::InvalidOutcome);
+        }
+
+        // 5. Check if oracle already attested
+        let vote_key = DataKey::Vote(market_id.clone(), oracle.clone());
+        if env.storage().persistent().has(&vote_key) {
+            return Err(OracleError::AlreadyAttested);
+        }
+
+        // 6. Store the vote, attestation, voter list entry and outcome count
+        record_vote(&env, &oracle, &market_id, attestation_result, data_hash);
+
+        extend_instance(&env);
+        extend_oracle(&env, &oracle);
+        extend_market(&env, &market_id);
+
+        // 7. Emit AttestationSubmitted(market_id, attestor, outcome)
+        AttestationSubmittedEvent {
+            market_id,
+            oracle,
+            attestation_result,
+        }
+        .publish(&env);
+
+        Ok(())
+    }
+
+    /// Check if consensus has been reached for market
+    pub fn check_consensus(env: Env, market_id: BytesN<32>) -> (bool, u32) {
+        // 1. Query attestations for market_id
+        let voters_key = DataKey::Voters(market_id.clone());
+        let voters: Vec<Address> = env
+            .storage()
+            .persistent()
+            .get(&voters_key)
+            .unwrap_or(Vec::new(&env));
+
+        if let ConsensusMode::Weighted(config) =
+            Self::get_consensus_mode(env.clone(), market_id.clone())
+        {
+            return check_weighted_consensus(&env, &market_id, &voters, &config);
+        }
+
+        // 2. Get the threshold snapshotted at registration
+        let threshold = Self::get_market_threshold(env.clone(), market_id.clone());
+
+        if voters.len() < threshold {
+            return (false, 0);
+        }
+
+        // 3. Count votes for each outcome
+        let mut yes_votes = 0;
+        let mut no_votes = 0;
+
+        for oracle in voters.iter() {
+            let vote_key = DataKey::Vote(market_id.clone(), oracle);
+            let vote: u32 = env.storage().persistent().get(&vote_key).unwrap_or(0);
+            if vote == 1 {
+                yes_votes += 1;
+            } else {
+                no_votes += 1;
+            }
+        }
+
+        // 4. Compare counts against threshold
+        // Winner is the one that reached the threshold first
+        // If both reach threshold (possible if threshold is low), we favor the one with more votes
+        // If tied and both >= threshold, return false (no clear winner yet)
+        if yes_votes >= threshold && yes_votes > no_votes {
+            (true, 1)
+        } else if no_votes >= threshold && no_votes > yes_votes {
+            (true, 0)
+        } else if yes_votes >= threshold && no_votes >= threshold && yes_votes == no_votes {
+            // Tie scenario appropriately handled: no consensus if tied but threshold met
+            (false, 0)
+        } else {
+            (false, 0)
+        }
+    }
+
+    /// Get the consensus result for a market
+    pub fn get_consensus_result(env: Env, market_id: BytesN<32>) -> Result<u32, OracleError> {
+        let result_key = DataKey::ConsensusResult(market_id.clone());
+        env.storage()
+            .persistent()
+            .get(&result_key)
+            .ok_or(OracleError::ConsensusResultNotFound)
+    }
+
+    /// Finalize market resolution after consensus and dispute period
+    ///
+    /// Called after consensus reached and dispute period elapsed.
+    /// Makes cross-contract call to Market.resolve_market().
+    /// Locks in final outcome permanently.
+    pub fn finalize_resolution(
+        env: Env,
+        market_id: BytesN<32>,
+        _market_address: Address,
+    ) -> Result<(), OracleError> {
+        // 1. Validate market is registered
+        let market_key = DataKey::MarketResTime(market_id.clone());
+        let resolution_time: u64 = env
+            .storage()
+            .persistent()
+            .get(&market_key)
+            .ok_or(OracleError::MarketNotRegistered)?;
+
+        // An undisputed proposal that settled the market keeps its result
+        if let Some(proposal) = Self::get_proposal(env.clone(), market_id.clone()) {
+            if proposal.settled && proposal.disputer.is_none() {
+                return Err(OracleError::ProposalAlreadySettled);
+            }
+        }
+
+        // 2. On commit-reveal markets, wait out reveals and penalize non-revealers
+        if let Some(window) = Self::get_reveal_window(env.clone(), market_id.clone()) {
+            if env.ledger().timestamp() < window.reveal_deadline {
+                return Err(OracleError::RevealPhaseNotOver);
+            }
+            settle_missed_reveals(&env, &market_id);
+        }
+
+        // 3. Validate consensus reached
+        let (consensus_reached, final_outcome) =
+            Self::check_consensus(env.clone(), market_id.clone());
+        if !consensus_reached {
+            return Err(OracleError::ConsensusNotReached);
+        }
+
+        // 4. Validate dispute period elapsed (7 days = 604800 seconds)
+        let current_time = env.ledger().timestamp();
+        let dispute_period = 604800u64;
+        if current_time < resolution_time + dispute_period {
+            return Err(OracleError::DisputePeriodNotElapsed);
+        }
+
+        // 5. Store consensus result permanently
+        store_consensus_result(&env, &market_id, final_outcome);
+
+        extend_instance(&env);
+        extend_market(&env, &market_id);
+
+        // 6. Cross-contract call to Market.resolve_market()
+        #[cfg(feature = "market")]
+        {
+            use crate::market::PredictionMarketClient;
+            let market_client = PredictionMarketClient::new(&env, &_market_address);
+            market_client.resolve_market(&market_id);
+        }
+
+        // 7. Emit ResolutionFinalized event
+        ResolutionFinalizedEvent {
+            market_id,
+            final_outcome,
+            timestamp: current_time,
+        }
+        .publish(&env);
+
+        Ok(())
+    }
+
+    /// Admin: Let anyone resolve a registered market by bonded proposal
+    ///
+    /// An undisputed proposal becomes the consensus result once `liveness`
+    /// has passed; a disputed one falls back to the multi-oracle vote.
+    pub fn enable_optimistic(
+        env: Env,
+        market_id: BytesN<32>,
+        bond: i128,
+        liveness: u64,
+    ) -> Result<(), OracleError> {
+        require_admin(&env)?;
+
+        if !env
+            .storage()
+            .persistent()
+            .has(&DataKey::MarketResTime(market_id.clone()))
+        {
+            return Err(OracleError::MarketNotRegistered);
+        }
+        if bond <= 0 || liveness == 0 {
+            return Err(OracleError::InvalidOptimisticConfig);
+        }
+
+        env.storage().persistent().set(
+            &DataKey::Optimistic(market_id.clone()),
+            &OptimisticConfig { bond, liveness },
+        );
+
+        extend_instance(&env);
+        extend_market(&env, &market_id);
+
+        OptimisticEnabledEvent {
+            market_id,
+            bond,
+            liveness,
+        }
+        .publish(&env);
+
+        Ok(())
+    }
+
+    /// Propose a market outcome, escrowing the market's bond in stake tokens
+    ///
+    /// Returns the time the liveness window closes.
+    pub fn propose_outcome(
+        env: Env,
+        proposer: Address,
+        market_id: BytesN<32>,
+        outcome: u32,
+    ) -> Result<u64, OracleError> {
+        proposer.require_auth();
+
+        let config: OptimisticConfig = env
+            .storage()
+            .persistent()
+            .get(&DataKey::Optimistic(market_id.clone()))
+            .ok_or(OracleError::OptimisticNotEnabled)?;
+        let resolution_time: u64 = env
+            .storage()
+            .persistent()
+            .get(&DataKey::MarketResTime(market_id.clone()))
+            .ok_or(OracleError::MarketNotRegistered)?;
+
+        let current_time = env.ledger().timestamp();
+        if current_time < resolution_time {
+            return Err(OracleError::ResolutionTimeNotReached);
+        }
+        if outcome > 1 {
+            return Err(OracleError::InvalidOutcome);
+        }
+        let proposal_key = DataKey::Proposal(market_id.clone());
+        if env.storage().persistent().has(&proposal_key) {
+            return Err(OracleError::ProposalAlreadyExists);
+        }
+
+        let stake_token = get_stake_token(&env)?;
+        let contract_address = env.current_contract_address();
+        token::TokenClient::new(&env, &stake_token).transfer(
+            &proposer,
+            &contract_address,
+            &config.bond,
+        );
+
+        let expires_at = current_time + config.liveness;
+        env.storage().persistent().set(
+            &proposal_key,
+            &OptimisticProposal {
+                proposer: proposer.clone(),
+                outcome,
+                bond: config.bond,
+                expires_at,
+                disputer: None,
+                settled: false,
+            },
+        );
+
+        extend_instance(&env);
+        extend_market(&env, &market_id);
+
+        OutcomeProposedEvent {
+            market_id,
+            proposer,
+            outcome,
+            bond: config.bond,
+            expires_at,
+        }
+        .publish(&env);
+
+        Ok(expires_at)
+    }
+
+    /// Dispute a proposal within its liveness window, matching its bond
+    ///
+    /// The market then resolves through the multi-oracle vote.
+    pub fn dispute_proposal(
+        env: Env,
+        disputer: Address,
+        market_id: BytesN<32>,
+    ) -> Result<(), OracleError> {
+        disputer.require_auth();
+
+        let proposal_key = DataKey::Proposal(market_id.clone());
+        let mut proposal: OptimisticProposal = env
+            .storage()
+            .persistent()
+            .get(&proposal_key)
+            .ok_or(OracleError::ProposalNotFound)?;
+        if proposal.disputer.is_some() {
+            return Err(OracleError::ProposalAlreadyDisputed);
+        }
+        if env.ledger().timestamp() >= proposal.expires_at {
+            return Err(OracleError::LivenessElapsed);
+        }
+
+        let stake_token = get_stake_token(&env)?;
+        let contract_address = env.current_contract_address();
+        token::TokenClient::new(&env, &stake_token).transfer(
+            &disputer,
+            &contract_address,
+            &proposal.bond,
+        );
+
+        proposal.disputer = Some(disputer.clone());
+        env.storage().persistent().set(&proposal_key, &proposal);
+
+        extend_instance(&env);
+        extend_market(&env, &market_id);
+
+        ProposalDisputedEvent {
+            market_id,
+            disputer,
+        }
+        .publish(&env);
+
+        Ok(())
+    }
+
+    /// Settle a proposal's bonds
+    ///
+    /// Undisputed: after liveness the proposed outcome becomes the consensus
+    /// result and the proposer gets the bond back, unless the market already
+    /// resolved to the other outcome, which forfeits the bond to the admin.
+    /// Disputed: once the consensus result is stored by `finalize_resolution`,
+    /// whichever side it agrees with takes both bonds.
+    pub fn settle_proposal(env: Env, market_id: BytesN<32>) -> Result<u32, OracleError> {
+        let proposal_key = DataKey::Proposal(market_id.clone());
+        let mut proposal: OptimisticProposal = env
+            .storage()
+            .persistent()
+            .get(&proposal_key)
+            .ok_or(OracleError::ProposalNotFound)?;
+        if proposal.settled {
+            return Err(OracleError::ProposalAlreadySettled);
+        }
+
+        let stored_result: Option<u32> = env
+            .storage()
+            .persistent()
+            .get(&DataKey::ConsensusResult(market_id.clone()));
+        let (outcome, winner, payout) = match proposal.disputer.clone() {
+            None => {
+                if env.ledger().timestamp() < proposal.expires_at {
+                    return Err(OracleError::LivenessNotElapsed);
+                }
+                match stored_result {
+                    Some(outcome) if outcome != proposal.outcome => {
+                        let admin: Address = env
+                            .storage()
+                            .instance()
+                            .get(&DataKey::Admin)
+                            .ok_or(OracleError::NotInitialized)?;
+                        (outcome, admin, proposal.bond)
+                    }
+                    Some(outcome) => (outcome, proposal.proposer.clone(), proposal.bond),
+                    None => {
+                        store_consensus_result(&env, &market_id, proposal.outcome);
+                        (proposal.outcome, proposal.proposer.clone(), proposal.bond)
+                    }
+                }
+            }
+            Some(disputer) => {
+                // Settle against the final result, not a count that may still move
+                let outcome = stored_result.ok_or(OracleError::ConsensusResultNotFound)?;
+                let winner = if outcome == proposal.outcome {
+                    proposal.proposer.clone()
+                } else {
+                    disputer
+                };
+                (outcome, winner, proposal.bond * 2)
+            }
+        };
+
+        proposal.settled = true;
+        env.storage().persistent().set(&proposal_key, &proposal);
+
+        let stake_token = get_stake_token(&env)?;
+        let contract_address = env.current_contract_address();
+        token::TokenClient::new(&env, &stake_token).transfer(&contract_address, &winner, &payout);
+
+        extend_instance(&env);
+        extend_market(&env, &market_id);
+
+        ProposalSettledEvent {
+            market_id,
+            outcome,
+            winner,
+            payout,
+            disputed: proposal.disputer.is_some(),
+        }
+        .publish(&env);
+
+        Ok(outcome)
+    }
+
+    /// Get the outcome proposal on an optimistic market
+    pub fn get_proposal(env: Env, market_id: BytesN<32>) -> Option<OptimisticProposal> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Proposal(market_id))
+    }
+
+    /// Challenge an attestation (dispute oracle honesty)
+    ///
+    /// Allows users to challenge attestations with stake.
+    /// The challenger's stake is escrowed in stake tokens and forfeited to the
+    /// oracle if the challenge is invalid.
+    pub fn challenge_attestation(
+        env: Env,
+        challenger: Address,
+        oracle: Address,
+        market_id: BytesN<32>,
+        challenge_reason: Symbol,
+    ) -> Result<(), OracleError> {
+        // 1. Require challenger authentication
+        challenger.require_auth();
+
+        // 2. Validate oracle is registered
+        let oracle_key = DataKey::Oracle(oracle.clone());
+        let is_registered: bool = env.storage().persistent().get(&oracle_key).unwrap_or(false);
+        if !is_registered {
+            return Err(OracleError::OracleNotRegistered);
+        }
+
+        // 3. Validate attestation exists
+        let attestation_key = DataKey::Attestation(market_id.clone(), oracle.clone());
+        let attestation: Option<Attestation> = env.storage().persistent().get(&attestation_key);
+        if attestation.is_none() {
+            return Err(OracleError::AttestationNotFound);
+        }
+
+        // 4. Check if challenge already exists for this oracle/market
+        let challenge_key = DataKey::Challenge(market_id.clone(), oracle.clone());
+        if env.storage().persistent().has(&challenge_key) {
+            return Err(OracleError::ChallengeAlreadyExists);
+        }
+
+        // 5. Escrow the challenger's stake
+        let stake_token = get_stake_token(&env)?;
+        let contract_address = env.current_contract_address();
+        token::TokenClient::new(&env, &stake_token).transfer(
+            &challenger,
+            &contract_address,
+            &CHALLENGE_STAKE_AMOUNT,
+        );
+
+        // 6. Create challenge record
+        let challenge = Challenge {
+            challenger: challenger.clone(),
+            oracle: oracle.clone(),
+            market_id: market_id.clone(),
+            reason: challenge_reason.clone(),
+            stake: CHALLENGE_STAKE_AMOUNT,
+            timestamp: env.ledger().timestamp(),
+            resolved: false,
+        };
+
+        // 7. Store challenge and lock the oracle's stake until it is resolved
+        env.storage().persistent().set(&challenge_key, &challenge);
+        update_stats(&env, &oracle, |stats| stats.challenges_received += 1);
+        let open_key = DataKey::OpenChallenges(oracle.clone());
+        let open: u32 = env.storage().persistent().get(&open_key).unwrap_or(0);
+        env.storage().persistent().set(&open_key, &(open + 1));
+        extend_oracle(&env, &oracle);
+
+        // 8. Mark market as having active challenge (pause finalization)
+        let market_challenge_key = DataKey::MarketChallenged(market_id.clone());
+        env.storage().persistent().set(&market_challenge_key, &true);
+
+        extend_instance(&env);
+        extend_market(&env, &market_id);
+
+        // 9. Emit AttestationChallenged event
+        AttestationChallengedEvent {
+            oracle,
+            challenger,
+            market_id,
+            challenge_reason,
+        }
+        .publish(&env);
+
+        Ok(())
+    }
+
+    /// Resolve a challenge and update oracle reputation
+    ///
+    /// Admin arbitration or multi-oracle re-vote to resolve challenges.
+    /// Slashes dishonest oracle's stake on successful challenge.
+    pub fn resolve_challenge(
+        env: Env,
+        oracle: Address,
+        market_id: BytesN<32>,
+        challenge_valid: bool,
+    ) -> Result<(), OracleError> {
+        // 1. Require admin authentication
+        require_admin(&env)?;
+
+        // 2. Query challenge record
+        let challenge_key = DataKey::Challenge(market_id.clone(), oracle.clone());
+        let mut challenge: Challenge = env
+            .storage()
+            .persistent()
+            .get(&challenge_key)
+            .ok_or(OracleError::ChallengeNotFound)?;
+
+        // 3. Validate challenge not already resolved
+        if challenge.resolved {
+            return Err(OracleError::ChallengeAlreadyResolved);
+        }
+
+        // 4. Get oracle's current accuracy score
+        let accuracy_key = DataKey::OracleAccuracy(oracle.clone());
+        let mut accuracy: u32 = env.storage().persistent().get(&accuracy_key).unwrap_or(100);
+
+        // 5. Get oracle's stake, bonded and unbonding
+        let stake_key = DataKey::OracleStake(oracle.clone());
+        let oracle_stake: i128 = env.storage().persistent().get(&stake_key).unwrap_or(0);
+        let unbonding_key = DataKey::Unbonding(oracle.clone());
+        let unbonding: Option<UnbondingStake> = env.storage().persistent().get(&unbonding_key);
+        let unbonding_amount = unbonding.as_ref().map(|u| u.amount).unwrap_or(0);
+
+        let new_reputation: u32;
+        let slashed_amount: i128;
+
+        if challenge_valid {
+            // Challenge is valid - oracle was dishonest
+
+            // 6a. Reduce oracle's reputation/accuracy score (reduce by 20%)
+            accuracy = accuracy.saturating_sub(20);
+            new_reputation = accuracy;
+
+            // 6b. Slash 50% of the oracle's stake, bonded first, then unbonding
+            slashed_amount = (oracle_stake + unbonding_amount) / 2;
+            let from_bonded = slashed_amount.min(oracle_stake);
+            env.storage()
+                .persistent()
+                .set(&stake_key, &(oracle_stake - from_bonded));
+            if let Some(mut unbonding) = unbonding {
+                unbonding.amount -= slashed_amount - from_bonded;
+                if unbonding.amount == 0 {
+                    env.storage().persistent().remove(&unbonding_key);
+                } else {
+                    env.storage().persistent().set(&unbonding_key, &unbonding);
+                }
+            }
+
+            // 6c. Return the challenger's escrow plus the slashed tokens
+            credit_reward(
+                &env,
+                DataKey::ChallengerReward(challenge.challenger.clone()),
+                challenge.stake + slashed_amount,
+            );
+
+            update_stats(&env, &oracle, |stats| stats.challenges_lost += 1);
+
+            // 6d. If accuracy drops below threshold (50%), deregister oracle
+            if accuracy < 50 && Self::is_oracle_active(env.clone(), oracle.clone()) {
+                deactivate_oracle(&env, &oracle);
+            }
+        } else {
+            // Challenge is invalid - oracle was honest
+
+            // 7a. Increase oracle's reputation (increase by 5%)
+            accuracy = if accuracy <= 95 { accuracy + 5 } else { 100 };
+            new_reputation = accuracy;
+            slashed_amount = 0;
+
+            // 7b. Penalize false challenger (forfeit their stake)
+            // Challenger's escrowed stake goes to oracle
+            credit_reward(&env, DataKey::OracleReward(oracle.clone()), challenge.stake);
+            update_stats(&env, &oracle, |stats| stats.challenges_won += 1);
+        }
+
+        // 8. Update oracle's accuracy score
+        env.storage()
+            .persistent()
+            .set(&accuracy_key, &new_reputation);
+
+        // 9. Mark challenge as resolved, unlocking the stake once none are open
+        challenge.resolved = true;
+        env.storage().persistent().set(&challenge_key, &challenge);
+        let open_key = DataKey::OpenChallenges(oracle.clone());
+        let open: u32 = env.storage().persistent().get(&open_key).unwrap_or(0);
+        if open > 1 {
+            env.storage().persistent().set(&open_key, &(open - 1));
+        } else {
+            env.storage().persistent().remove(&open_key);
+        }
+
+        // 10. Remove market challenge flag (allow finalization)
+        let market_challenge_key = DataKey::MarketChallenged(market_id.clone());
+        env.storage().persistent().remove(&market_challenge_key);
+
+        extend_instance(&env);
+        extend_oracle(&env, &oracle);
+        extend_market(&env, &market_id);
+        extend_persistent(
+            &env,
+            &DataKey::ChallengerReward(challenge.challenger.clone()),
+        );
+        extend_persistent(&env, &DataKey::OracleReward(oracle.clone()));
+
+        // 11. Emit ChallengeResolved event
+        ChallengeResolvedEvent {
+            oracle,
+            challenger: challenge.challenger,
+            challenge_valid,
+            new_reputation,
+            slashed_amount,
+        }
+        .publish(&env);
+
+        Ok(())
+    }
+
+    /// Pay out an address's accumulated challenger and oracle rewards
+    ///
+    /// Rewards are held in stake tokens: returned challenge escrow plus
+    /// slashed stake for upheld challenges, forfeited escrow for rejected ones.
+    pub fn claim_rewards(env: Env, claimant: Address) -> Result<i128, OracleError> {
+        claimant.require_auth();
+
+        let challenger_key = DataKey::ChallengerReward(claimant.clone());
+        let oracle_key = DataKey::OracleReward(claimant.clone());
+        let amount: i128 = env
+            .storage()
+            .persistent()
+            .get::<_, i128>(&challenger_key)
+            .unwrap_or(0)
+            + env
+                .storage()
+                .persistent()
+                .get::<_, i128>(&oracle_key)
+                .unwrap_or(0);
+        if amount == 0 {
+            return Err(OracleError::NoRewardsToClaim);
+        }
+
+        env.storage().persistent().remove(&challenger_key);
+        env.storage().persistent().remove(&oracle_key);
+
+        let stake_token = get_stake_token(&env)?;
+        let contract_address = env.current_contract_address();
+        token::TokenClient::new(&env, &stake_token).transfer(&contract_address, &claimant, &amount);
+
+        extend_instance(&env);
+
+        RewardsClaimedEvent { claimant, amount }.publish(&env);
+
+        Ok(amount)
+    }
+
+    /// Get an address's unclaimed challenger and oracle rewards
+    pub fn get_claimable_rewards(env: Env, claimant: Address) -> i128 {
+        let challenger: i128 = env
+            .storage()
+            .persistent()
+            .get(&DataKey::ChallengerReward(claimant.clone()))
+            .unwrap_or(0);
+        let oracle: i128 = env
+            .storage()
+            .persistent()
+            .get(&DataKey::OracleReward(claimant))
+            .unwrap_or(0);
+        challenger + oracle
+    }
+
+    /// Keep a market's resolution entries and the oracle instance from being archived
+    ///
+    /// Permissionless: extends the market's registration, counts, consensus
+    /// and override records plus every vote, attestation and challenge on it.
+    pub fn bump(env: Env, market_id: BytesN<32>) -> Result<(), OracleError> {
+        let market_key = DataKey::MarketResTime(market_id.clone());
+        if !env.storage().persistent().has(&market_key) {
+            return Err(OracleError::MarketNotRegistered);
+        }
+
+        extend_instance(&env);
+        extend_market(&env, &market_id);
+        Ok(())
+    }
+
+    /// Get all attestations that count toward a market's consensus
+    ///
+    /// In voting order. Vote counts and consensus status are available from
+    /// `get_attestation_counts` and `check_consensus`.
+    pub fn get_attestations(env: Env, market_id: BytesN<32>) -> Vec<Attestation> {
+        let voters: Vec<Address> = env
+            .storage()
             .persistent()
-            .get(&Symbol::new(&env, ADMIN_KEY))
-            .expect("Oracle not initialized");
-        admin.require_auth();
+            .get(&DataKey::Voters(market_id.clone()))
+            .unwrap_or(Vec::new(&env));
 
-        // 2. Query challenge record
-        let challenge_key = (
-            Symbol::new(&env, "challenge"),
-            market_id.clone(),
-            oracle.clone(),
-        );
-        let mut challenge: Challenge = env
+        let mut attestations = Vec::new(&env);
+        for oracle in voters.iter() {
+            if let Some(attestation) = Self::get_attestation(env.clone(), market_id.clone(), oracle)
+            {
+                attestations.push_back(attestation);
+            }
+        }
+        attestations
+    }
+
+    /// Get oracle info and reputation
+    pub fn get_oracle_info(env: Env, oracle: Address) -> Result<OracleInfo, OracleError> {
+        let active: bool = env
             .storage()
             .persistent()
-            .get(&challenge_key)
-            .expect("Challenge not found");
+            .get(&DataKey::Oracle(oracle.clone()))
+            .ok_or(OracleError::OracleNotRegistered)?;
 
-        // 3. Validate challenge not already resolved
-        if challenge.resolved {
-            panic!("Challenge already resolved");
+        Ok(OracleInfo {
+            name: env
+                .storage()
+                .persistent()
+                .get(&DataKey::OracleName(oracle.clone()))
+                .unwrap_or(Symbol::new(&env, "")),
+            active,
+            joined_at: env
+                .storage()
+                .persistent()
+                .get(&DataKey::OracleTimestamp(oracle.clone()))
+                .unwrap_or(0),
+            accuracy: Self::get_oracle_accuracy(env.clone(), oracle.clone()),
+            stake: env
+                .storage()
+                .persistent()
+                .get(&DataKey::OracleStake(oracle.clone()))
+                .unwrap_or(0),
+            stats: Self::get_oracle_stats(env.clone(), oracle.clone()),
+            oracle,
+        })
+    }
+
+    /// Get an oracle's activity counters
+    pub fn get_oracle_stats(env: Env, oracle: Address) -> OracleStats {
+        env.storage()
+            .persistent()
+            .get(&DataKey::OracleStats(oracle))
+            .unwrap_or_default()
+    }
+
+    /// Get every registered oracle, active or not, in registration order
+    pub fn get_oracles(env: Env) -> Vec<Address> {
+        env.storage()
+            .instance()
+            .get(&DataKey::OracleList)
+            .unwrap_or(Vec::new(&env))
+    }
+
+    /// Get all active oracles, highest reputation first
+    ///
+    /// Oracles with equal accuracy keep their registration order.
+    pub fn get_active_oracles(env: Env) -> Vec<Address> {
+        let mut ranked: Vec<(Address, u32)> = Vec::new(&env);
+        for oracle in Self::get_oracles(env.clone()).iter() {
+            if !Self::is_oracle_active(env.clone(), oracle.clone()) {
+                continue;
+            }
+            let accuracy = Self::get_oracle_accuracy(env.clone(), oracle.clone());
+
+            // Insert after every oracle with at least this accuracy
+            let mut index = ranked.len();
+            for (i, (_, other)) in ranked.iter().enumerate() {
+                if other < accuracy {
+                    index = i as u32;
+                    break;
+                }
+            }
+            ranked.insert(index, (oracle, accuracy));
         }
 
-        // 4. Get oracle's current accuracy score
-        let accuracy_key = (Symbol::new(&env, "oracle_accuracy"), oracle.clone());
-        let mut accuracy: u32 = env.storage().persistent().get(&accuracy_key).unwrap_or(100);
+        let mut active = Vec::new(&env);
+        for (oracle, _) in ranked.iter() {
+            active.push_back(oracle);
+        }
+        active
+    }
 
-        // 5. Get oracle's stake
-        let stake_key = (Symbol::new(&env, ORACLE_STAKE_KEY), oracle.clone());
-        let oracle_stake: i128 = env.storage().persistent().get(&stake_key).unwrap_or(0);
+    /// Admin: Update oracle consensus threshold
+    ///
+    /// Applies to markets registered afterwards; existing markets keep the
+    /// threshold they were registered with. Must be between 1 and the number
+    /// of active oracles.
+    pub fn set_consensus_threshold(env: Env, new_threshold: u32) -> Result<(), OracleError> {
+        require_admin(&env)?;
+
+        let active_oracles = Self::get_oracle_count(env.clone());
+        if new_threshold == 0 || new_threshold > active_oracles {
+            return Err(OracleError::InvalidConsensusThreshold);
+        }
 
-        let new_reputation: u32;
-        let slashed_amount: i128;
+        let old_threshold = Self::get_consensus_threshold(env.clone());
+        env.storage()
+            .instance()
+            .set(&DataKey::RequiredConsensus, &new_threshold);
 
-        if challenge_valid {
-            // Challenge is valid - oracle was dishonest
+        extend_instance(&env);
 
-            // 6a. Reduce oracle's reputation/accuracy score (reduce by 20%)
-            accuracy = accuracy.saturating_sub(20);
-            new_reputation = accuracy;
+        ConsensusThresholdUpdatedEvent {
+            old_threshold,
+            new_threshold,
+        }
+        .publish(&env);
 
-            // 6b. Slash oracle's stake (50% of stake)
-            slashed_amount = oracle_stake / 2;
-            let remaining_stake = oracle_stake - slashed_amount;
-            env.storage().persistent().set(&stake_key, &remaining_stake);
+        Ok(())
+    }
 
-            // 6c. Reward challenger with slashed amount
-            let challenger_reward_key = (
-                Symbol::new(&env, "challenger_reward"),
-                challenge.challenger.clone(),
-            );
-            let current_rewards: i128 = env
+    /// Get the consensus threshold applied to newly registered markets
+    pub fn get_consensus_threshold(env: Env) -> u32 {
+        env.storage()
+            .instance()
+            .get(&DataKey::RequiredConsensus)
+            .unwrap_or(0)
+    }
+
+    /// Get consensus report
+    ///
+    /// Totals are summed from each registered oracle's counters.
+    pub fn get_consensus_report(env: Env) -> ConsensusReport {
+        let mut report = ConsensusReport {
+            markets_resolved: env
                 .storage()
-                .persistent()
-                .get(&challenger_reward_key)
-                .unwrap_or(0);
-            env.storage()
-                .persistent()
-                .set(&challenger_reward_key, &(current_rewards + slashed_amount));
+                .instance()
+                .get(&DataKey::MarketsResolved)
+                .unwrap_or(0),
+            total_attestations: 0,
+            total_challenges: 0,
+            successful_challenges: 0,
+            oracles: Vec::new(&env),
+        };
+        for oracle in Self::get_oracles(env.clone()).iter() {
+            if let Ok(info) = Self::get_oracle_info(env.clone(), oracle) {
+                report.total_attestations += info.stats.attestations;
+                report.total_challenges += info.stats.challenges_received;
+                report.successful_challenges += info.stats.challenges_lost;
+                report.oracles.push_back(info);
+            }
+        }
+        report
+    }
 
-            // 6d. If accuracy drops below threshold (50%), deregister oracle
-            if accuracy < 50 {
-                let oracle_key = (Symbol::new(&env, "oracle"), oracle.clone());
-                env.storage().persistent().set(&oracle_key, &false);
-
-                // Decrement oracle count
-                let oracle_count: u32 = env
-                    .storage()
-                    .persistent()
-                    .get(&Symbol::new(&env, ORACLE_COUNT_KEY))
-                    .unwrap_or(0);
-                if oracle_count > 0 {
-                    env.storage()
-                        .persistent()
-                        .set(&Symbol::new(&env, ORACLE_COUNT_KEY), &(oracle_count - 1));
-                }
+    /// Get challenge information for a specific oracle and market
+    pub fn get_challenge(env: Env, oracle: Address, market_id: BytesN<32>) -> Option<Challenge> {
+        let challenge_key = DataKey::Challenge(market_id, oracle);
+        env.storage().persistent().get(&challenge_key)
+    }
 
-                // Emit OracleDeregistered event
-                OracleDeregisteredEvent {
-                    oracle: oracle.clone(),
-                    timestamp: env.ledger().timestamp(),
-                }
-                .publish(&env);
+    /// Check if a market has an active (unresolved) challenge
+    pub fn has_active_challenge(env: Env, market_id: BytesN<32>) -> bool {
+        let market_challenge_key = DataKey::MarketChallenged(market_id);
+        env.storage()
+            .persistent()
+            .get(&market_challenge_key)
+            .unwrap_or(false)
+    }
+
+    /// Get oracle's current stake
+    pub fn get_oracle_stake(env: Env, oracle: Address) -> i128 {
+        let stake_key = DataKey::OracleStake(oracle);
+        env.storage().persistent().get(&stake_key).unwrap_or(0)
+    }
+
+    /// Get oracle's accuracy score
+    pub fn get_oracle_accuracy(env: Env, oracle: Address) -> u32 {
+        let accuracy_key = DataKey::OracleAccuracy(oracle);
+        env.storage().persistent().get(&accuracy_key).unwrap_or(0)
+    }
+
+    /// Emergency: Override oracle consensus if all oracles compromised
+    ///
+    /// Security Features:
+    /// - M-of-N multi-sig requirement (see `set_required_signatures`)
+    /// - Cooldown period between overrides (default 24h, see `set_override_cooldown`)
+    /// - Justification hash for audit trail
+    /// - Complete override record stored permanently
+    /// - EmergencyOverride event with all details
+    ///
+    /// Parameters:
+    /// - approvers: Vec of admin addresses approving this override
+    /// - market_id: Market to override
+    /// - forced_outcome: Outcome to set (0=NO, 1=YES)
+    /// - justification_hash: Hash of justification document (for transparency)
+    pub fn emergency_override(
+        env: Env,
+        approvers: Vec<Address>,
+        market_id: BytesN<32>,
+        forced_outcome: u32,
+        justification_hash: BytesN<32>,
+    ) -> Result<(), OverrideError> {
+        // 1. Validate forced_outcome is binary (0 or 1)
+        if forced_outcome > 1 {
+            return Err(OverrideError::InvalidOutcome);
+        }
+
+        // 2-5. Require M-of-N distinct signer approvals
+        require_signer_approval(&env, &approvers)?;
+
+        let current_time = env.ledger().timestamp();
+        let mut approvals = Vec::new(&env);
+        for admin in approvers.iter() {
+            approvals.push_back(OverrideApproval {
+                admin,
+                timestamp: current_time,
+            });
+        }
+
+        // 6-12. Check cooldown and market, then record and apply the override
+        apply_override(
+            &env,
+            &market_id,
+            forced_outcome,
+            &justification_hash,
+            approvals,
+        )
+    }
+
+    /// Multisig: Propose an emergency override that signers approve one by one
+    ///
+    /// The proposer's approval is recorded immediately. The proposal expires
+    /// `OVERRIDE_PROPOSAL_LIFETIME` seconds after creation. Returns its id.
+    pub fn propose_override(
+        env: Env,
+        proposer: Address,
+        market_id: BytesN<32>,
+        forced_outcome: u32,
+        justification_hash: BytesN<32>,
+    ) -> Result<u32, OverrideError> {
+        proposer.require_auth();
+        require_signer(&env, &proposer)?;
+
+        if forced_outcome > 1 {
+            return Err(OverrideError::InvalidOutcome);
+        }
+        if !env
+            .storage()
+            .persistent()
+            .has(&DataKey::MarketResTime(market_id.clone()))
+        {
+            return Err(OverrideError::MarketNotRegistered);
+        }
+
+        let proposal_id: u32 = env
+            .storage()
+            .instance()
+            .get(&DataKey::OverrideProposalCount)
+            .unwrap_or(0);
+        env.storage()
+            .instance()
+            .set(&DataKey::OverrideProposalCount, &(proposal_id + 1));
+
+        let current_time = env.ledger().timestamp();
+        let expires_at = current_time + OVERRIDE_PROPOSAL_LIFETIME;
+        let mut approvals = Vec::new(&env);
+        approvals.push_back(OverrideApproval {
+            admin: proposer.clone(),
+            timestamp: current_time,
+        });
+
+        let proposal_key = DataKey::OverrideProposal(proposal_id);
+        env.storage().persistent().set(
+            &proposal_key,
+            &OverrideProposal {
+                market_id: market_id.clone(),
+                forced_outcome,
+                justification_hash,
+                proposer: proposer.clone(),
+                approvals,
+                expires_at,
+                status: OverrideStatus::Pending,
+            },
+        );
+
+        extend_instance(&env);
+        extend_persistent(&env, &proposal_key);
+
+        OverrideProposedEvent {
+            proposal_id,
+            market_id,
+            proposer,
+            forced_outcome,
+            expires_at,
+        }
+        .publish(&env);
+
+        Ok(proposal_id)
+    }
+
+    /// Multisig: Approve a pending override proposal
+    ///
+    /// Executes the override once approvals reach the required signatures and
+    /// the cooldown has elapsed; returns whether it executed. An approved
+    /// proposal held back by the cooldown can be run with `execute_override`.
+    pub fn approve_override(
+        env: Env,
+        signer: Address,
+        proposal_id: u32,
+    ) -> Result<bool, OverrideError> {
+        signer.require_auth();
+        require_signer(&env, &signer)?;
+
+        let mut proposal = load_open_proposal(&env, proposal_id)?;
+        for approval in proposal.approvals.iter() {
+            if approval.admin == signer {
+                return Err(OverrideError::AlreadyApproved);
             }
-        } else {
-            // Challenge is invalid - oracle was honest
+        }
+        proposal.approvals.push_back(OverrideApproval {
+            admin: signer.clone(),
+            timestamp: env.ledger().timestamp(),
+        });
 
-            // 7a. Increase oracle's reputation (increase by 5%)
-            accuracy = if accuracy <= 95 { accuracy + 5 } else { 100 };
-            new_reputation = accuracy;
-            slashed_amount = 0;
+        let proposal_key = DataKey::OverrideProposal(proposal_id);
+        env.storage().persistent().set(&proposal_key, &proposal);
 
-            // 7b. Penalize false challenger (forfeit their stake)
-            // Challenger's stake goes to oracle
-            let oracle_reward_key = (Symbol::new(&env, "oracle_reward"), oracle.clone());
-            let current_rewards: i128 = env
-                .storage()
-                .persistent()
-                .get(&oracle_reward_key)
-                .unwrap_or(0);
-            env.storage().persistent().set(
-                &oracle_reward_key,
-                &(current_rewards + CHALLENGE_STAKE_AMOUNT),
-            );
+        extend_instance(&env);
+        extend_persistent(&env, &proposal_key);
+
+        OverrideApprovedEvent {
+            proposal_id,
+            signer,
+            approvals: proposal.approvals.len(),
         }
+        .publish(&env);
 
-        // 8. Update oracle's accuracy score
+        if !override_cooldown_elapsed(&env) {
+            return Ok(false);
+        }
+        match counted_approvals(&env, &proposal) {
+            Some(approvals) => {
+                execute_proposal(&env, proposal_id, proposal, approvals)?;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    /// Execute an approved override proposal once the cooldown allows it
+    pub fn execute_override(env: Env, proposal_id: u32) -> Result<(), OverrideError> {
+        let proposal = load_open_proposal(&env, proposal_id)?;
+        let approvals =
+            counted_approvals(&env, &proposal).ok_or(OverrideError::InsufficientApprovers)?;
+        execute_proposal(&env, proposal_id, proposal, approvals)
+    }
+
+    /// Withdraw a pending override proposal (proposer only)
+    pub fn cancel_override(
+        env: Env,
+        proposer: Address,
+        proposal_id: u32,
+    ) -> Result<(), OverrideError> {
+        proposer.require_auth();
+
+        let proposal_key = DataKey::OverrideProposal(proposal_id);
+        let mut proposal: OverrideProposal = env
+            .storage()
+            .persistent()
+            .get(&proposal_key)
+            .ok_or(OverrideError::ProposalNotFound)?;
+        if proposal.proposer != proposer {
+            return Err(OverrideError::NotProposer);
+        }
+        if proposal.status != OverrideStatus::Pending {
+            return Err(OverrideError::ProposalNotPending);
+        }
+
+        proposal.status = OverrideStatus::Cancelled;
+        env.storage().persistent().set(&proposal_key, &proposal);
+
+        extend_instance(&env);
+        extend_persistent(&env, &proposal_key);
+
+        OverrideCancelledEvent { proposal_id }.publish(&env);
+
+        Ok(())
+    }
+
+    /// Get an override proposal by id
+    pub fn get_override_proposal(env: Env, proposal_id: u32) -> Option<OverrideProposal> {
         env.storage()
             .persistent()
-            .set(&accuracy_key, &new_reputation);
+            .get(&DataKey::OverrideProposal(proposal_id))
+    }
 
-        // 9. Mark challenge as resolved
-        challenge.resolved = true;
-        env.storage().persistent().set(&challenge_key, &challenge);
+    /// Get emergency override record for a market (for audit purposes)
+    pub fn get_override_record(env: Env, market_id: BytesN<32>) -> Option<EmergencyOverrideRecord> {
+        let override_record_key = DataKey::OverrideRecord(market_id);
+        env.storage().persistent().get(&override_record_key)
+    }
 
-        // 10. Remove market challenge flag (allow finalization)
-        let market_challenge_key = (Symbol::new(&env, "market_challenged"), market_id.clone());
-        env.storage().persistent().remove(&market_challenge_key);
+    /// Check if market was manually overridden
+    pub fn is_manual_override(env: Env, market_id: BytesN<32>) -> bool {
+        let override_flag_key = DataKey::ManualOverride(market_id);
+        env.storage()
+            .persistent()
+            .get(&override_flag_key)
+            .unwrap_or(false)
+    }
 
-        // 11. Emit ChallengeResolved event
-        ChallengeResolvedEvent {
-            oracle,
-            challenger: challenge.challenger,
-            challenge_valid,
-            new_reputation,
-            slashed_amount,
+    /// Multisig: Add an emergency-override signer
+    pub fn add_signer(
+        env: Env,
+        approvers: Vec<Address>,
+        signer: Address,
+    ) -> Result<(), OverrideError> {
+        require_signer_approval(&env, &approvers)?;
+
+        let mut signers = Self::get_admin_signers(env.clone());
+        if signers.contains(&signer) {
+            return Err(OverrideError::SignerAlreadyExists);
+        }
+        signers.push_back(signer.clone());
+        env.storage()
+            .instance()
+            .set(&DataKey::AdminSigners, &signers);
+
+        extend_instance(&env);
+
+        SignerAddedEvent { signer }.publish(&env);
+
+        Ok(())
+    }
+
+    /// Multisig: Remove an emergency-override signer
+    ///
+    /// Fails if fewer signers than required signatures would remain.
+    pub fn remove_signer(
+        env: Env,
+        approvers: Vec<Address>,
+        signer: Address,
+    ) -> Result<(), OverrideError> {
+        require_signer_approval(&env, &approvers)?;
+
+        let mut signers = Self::get_admin_signers(env.clone());
+        let index = signers
+            .first_index_of(&signer)
+            .ok_or(OverrideError::SignerNotFound)?;
+        if signers.len() - 1 < Self::get_required_signatures(env.clone()) {
+            return Err(OverrideError::InvalidSignerConfig);
+        }
+        signers.remove(index);
+        env.storage()
+            .instance()
+            .set(&DataKey::AdminSigners, &signers);
+
+        extend_instance(&env);
+
+        SignerRemovedEvent { signer }.publish(&env);
+
+        Ok(())
+    }
+
+    /// Multisig: Set how many signers must approve an override (M of N)
+    pub fn set_required_signatures(
+        env: Env,
+        approvers: Vec<Address>,
+        required: u32,
+    ) -> Result<(), OverrideError> {
+        require_signer_approval(&env, &approvers)?;
+
+        let signers = Self::get_admin_signers(env.clone());
+        if required == 0 || required > signers.len() {
+            return Err(OverrideError::InvalidSignerConfig);
+        }
+
+        let old_required = Self::get_required_signatures(env.clone());
+        env.storage()
+            .instance()
+            .set(&DataKey::RequiredSignatures, &required);
+
+        extend_instance(&env);
+
+        RequiredSigsUpdatedEvent {
+            old_required,
+            new_required: required,
+        }
+        .publish(&env);
+
+        Ok(())
+    }
+
+    /// Multisig: Set the minimum time between emergency overrides
+    pub fn set_override_cooldown(
+        env: Env,
+        approvers: Vec<Address>,
+        cooldown: u64,
+    ) -> Result<(), OverrideError> {
+        require_signer_approval(&env, &approvers)?;
+
+        let old_cooldown = Self::get_override_cooldown(env.clone());
+        env.storage()
+            .instance()
+            .set(&DataKey::OverrideCooldown, &cooldown);
+
+        extend_instance(&env);
+
+        OverrideCooldownUpdatedEvent {
+            old_cooldown,
+            new_cooldown: cooldown,
         }
         .publish(&env);
+
+        Ok(())
     }
 
-    /// Get all attestations for a market
-    ///
-    /// TODO: Get Attestations
-    /// - Query attestations map by market_id
-    /// - Return all oracles' attestations for this market
-    /// - Include: oracle_address, result, data_hash, timestamp
-    /// - Include: consensus status and vote counts
-    pub fn get_attestations(_env: Env, _market_id: BytesN<32>) -> Vec<Symbol> {
-        todo!("See get attestations TODO above")
+    /// Get admin signers list
+    pub fn get_admin_signers(env: Env) -> Vec<Address> {
+        env.storage()
+            .instance()
+            .get(&DataKey::AdminSigners)
+            .unwrap_or(Vec::new(&env))
     }
 
-    /// Get oracle info and reputation
-    ///
-    /// TODO: Get Oracle Info
-    /// - Query oracle_registry by oracle_address
-    /// - Return: name, reputation_score, attestations_count, accuracy_pct
-    /// - Include: joined_timestamp, status (active/inactive)
-    /// - Include: challenges_received, challenges_won
-    pub fn get_oracle_info(_env: Env, _oracle: Address) -> Symbol {
-        todo!("See get oracle info TODO above")
+    /// Get required signatures for emergency override
+    pub fn get_required_signatures(env: Env) -> u32 {
+        env.storage()
+            .instance()
+            .get(&DataKey::RequiredSignatures)
+            .unwrap_or(2)
     }
 
-    /// Get all active oracles
-    ///
-    /// TODO: Get Active Oracles
-    /// - Query oracle_registry for all oracles with status=active
-    /// - Return list of oracle addresses
-    /// - Include: reputation scores sorted by highest first
-    /// - Include: availability status
-    pub fn get_active_oracles(_env: Env) -> Vec<Address> {
-        todo!("See get active oracles TODO above")
+    /// Get override cooldown period
+    pub fn get_override_cooldown(env: Env) -> u64 {
+        env.storage()
+            .instance()
+            .get(&DataKey::OverrideCooldown)
+            .unwrap_or(86400)
     }
 
-    /// Admin: Update oracle consensus threshold
-    ///
-    /// TODO: Set Consensus Threshold
-    /// - Require admin authentication
-    /// - Validate new_threshold > 0 and <= total_oracles
-    /// - Validate reasonable (e.g., 2 of 3, 3 of 5, etc.)
-    /// - Update required_consensus
-    /// - Apply to future markets only
-    /// - Emit ConsensusThresholdUpdated(new_threshold, old_threshold)
-    pub fn set_consensus_threshold(_env: Env, _new_threshold: u32) {
-        todo!("See set consensus threshold TODO above")
+    /// Get last override timestamp
+    pub fn get_last_override_time(env: Env) -> u64 {
+        env.storage()
+            .instance()
+            .get(&DataKey::LastOverrideTime)
+            .unwrap_or(0)
     }
 
-    /// Get consensus report
-    ///
-    /// TODO: Get Consensus Report
-    /// - Compile oracle performance metrics
-    /// - Return: total_markets_resolved, consensus_efficiency, dispute_rate
-    /// - Include: by_oracle (each oracle's stats)
-    /// - Include: time: average_time_to_consensus
-    pub fn get_consensus_report(_env: Env) -> Symbol {
-        todo!("See get consensus report TODO above")
+    /// Get the storage schema version of this contract
+    pub fn get_schema_version(env: Env) -> u32 {
+        upgrade::get_schema_version(&env)
     }
 
-    /// Get challenge information for a specific oracle and market
-    pub fn get_challenge(env: Env, oracle: Address, market_id: BytesN<32>) -> Option<Challenge> {
-        let challenge_key = (Symbol::new(&env, "challenge"), market_id, oracle);
-        env.storage().persistent().get(&challenge_key)
+    /// Admin: Set the upgrade timelock in seconds (0 = upgrades apply immediately)
+    ///
+    /// A reduction only applies once the current delay has passed. Returns
+    /// when the new delay is in force.
+    pub fn set_upgrade_delay(env: Env, delay: u64) -> Result<u64, OracleError> {
+        require_admin(&env)?;
+        Ok(upgrade::set_upgrade_delay(&env, delay))
     }
 
-    /// Check if a market has an active (unresolved) challenge
-    pub fn has_active_challenge(env: Env, market_id: BytesN<32>) -> bool {
-        let market_challenge_key = (Symbol::new(&env, "market_challenged"), market_id);
-        env.storage()
-            .persistent()
-            .get(&market_challenge_key)
-            .unwrap_or(false)
+    /// Admin: Queue a WASM upgrade behind the timelock, returns its eta
+    pub fn propose_upgrade(env: Env, new_wasm_hash: BytesN<32>) -> Result<u64, OracleError> {
+        require_admin(&env)?;
+        Ok(upgrade::propose_upgrade(&env, new_wasm_hash))
     }
 
-    /// Get oracle's current stake
-    pub fn get_oracle_stake(env: Env, oracle: Address) -> i128 {
-        let stake_key = (Symbol::new(&env, ORACLE_STAKE_KEY), oracle);
-        env.storage().persistent().get(&stake_key).unwrap_or(0)
+    /// Admin: Cancel the queued upgrade during the timelock
+    pub fn cancel_upgrade(env: Env) -> Result<(), OracleError> {
+        require_admin(&env)?;
+        upgrade::cancel_upgrade(&env)?;
+        Ok(())
     }
 
-    /// Get oracle's accuracy score
-    pub fn get_oracle_accuracy(env: Env, oracle: Address) -> u32 {
-        let accuracy_key = (Symbol::new(&env, "oracle_accuracy"), oracle);
-        env.storage().persistent().get(&accuracy_key).unwrap_or(0)
+    /// Get the queued upgrade, if any
+    pub fn get_pending_upgrade(env: Env) -> Option<PendingUpgrade> {
+        upgrade::get_pending_upgrade(&env)
     }
 
-    /// Emergency: Override oracle consensus if all oracles compromised
-    ///
-    /// Security Features:
-    /// - Multi-sig requirement (configurable, default 2 of 3)
-    /// - Cooldown period between overrides (default 24h)
-    /// - Justification hash for audit trail
-    /// - Complete override record stored permanently
-    /// - EmergencyOverride event with all details
+    /// Admin: Replace the OracleManager WASM
     ///
-    /// Parameters:
-    /// - approvers: Vec of admin addresses approving this override
-    /// - market_id: Market to override
-    /// - forced_outcome: Outcome to set (0=NO, 1=YES)
-    /// - justification_hash: Hash of justification document (for transparency)
-    pub fn emergency_override(
-        env: Env,
-        approvers: Vec<Address>,
-        market_id: BytesN<32>,
-        forced_outcome: u32,
-        justification_hash: BytesN<32>,
-    ) {
-        // 1. Validate forced_outcome is binary (0 or 1)
-        if forced_outcome > 1 {
-            panic!("Invalid outcome: must be 0 or 1");
-        }
-
-        // 2. Get admin signers and required signatures
-        let admin_signers: Vec<Address> = env
-            .storage()
-            .persistent()
-            .get(&Symbol::new(&env, ADMIN_SIGNERS_KEY))
-            .expect("Oracle not initialized");
+    /// The admin may be a multisig account. When a timelock is set the hash
+    /// must match a proposal whose eta has passed.
+    pub fn upgrade(env: Env, new_wasm_hash: BytesN<32>) -> Result<(), OracleError> {
+        require_admin(&env)?;
+        upgrade::apply_upgrade(&env, new_wasm_hash)?;
+        Ok(())
+    }
 
-        let required_sigs: u32 = env
-            .storage()
-            .persistent()
-            .get(&Symbol::new(&env, REQUIRED_SIGNATURES_KEY))
-            .unwrap_or(2);
+    /// Admin: Migrate storage to this code's schema version after an upgrade
+    pub fn migrate(env: Env) -> Result<(), OracleError> {
+        require_admin(&env)?;
+        upgrade::migrate(&env, SCHEMA_VERSION, |_env, _from_version| {})?;
+        Ok(())
+    }
+}
 
-        // 3. Validate we have enough approvers
-        if approvers.len() < required_sigs {
-            panic!("Insufficient approvers");
-        }
+/// Load the stored admin and require its authorization
+fn require_admin(env: &Env) -> Result<Address, OracleError> {
+    let admin: Address = env
+        .storage()
+        .instance()
+        .get(&DataKey::Admin)
+        .ok_or(OracleError::NotInitialized)?;
+    admin.require_auth();
+    Ok(admin)
+}
 
-        // 4. Verify all approvers are valid admins and require their auth
-        let mut valid_approver_count = 0u32;
-        for approver in approvers.iter() {
-            // Require authentication from each approver
-            approver.require_auth();
+/// Approvals an override or signer change needs
+///
+/// Instances initialized with more required signatures than signers are
+/// capped at the signer count so they can still add signers.
+fn required_approvals(env: &Env, signers: &Vec<Address>) -> u32 {
+    let required: u32 = env
+        .storage()
+        .instance()
+        .get(&DataKey::RequiredSignatures)
+        .unwrap_or(2);
+    required.min(signers.len()).max(1)
+}
 
-            // Verify approver is in admin_signers list
-            let mut is_valid_admin = false;
-            for admin in admin_signers.iter() {
-                if admin == approver {
-                    is_valid_admin = true;
-                    break;
-                }
-            }
+/// Require authenticated approval from enough distinct override signers
+fn require_signer_approval(env: &Env, approvers: &Vec<Address>) -> Result<(), OverrideError> {
+    let signers: Vec<Address> = env
+        .storage()
+        .instance()
+        .get(&DataKey::AdminSigners)
+        .ok_or(OverrideError::NotInitialized)?;
+    if approvers.len() < required_approvals(env, &signers) {
+        return Err(OverrideError::InsufficientApprovers);
+    }
 
-            if !is_valid_admin {
-                panic!("Invalid approver: not an admin");
+    for (i, approver) in approvers.iter().enumerate() {
+        if !signers.contains(&approver) {
+            return Err(OverrideError::InvalidApprover);
+        }
+        // Each signer counts once towards the threshold
+        for other in approvers.iter().skip(i + 1) {
+            if other == approver {
+                return Err(OverrideError::DuplicateApprovers);
             }
-
-            valid_approver_count += 1;
         }
+        approver.require_auth();
+    }
 
-        // 5. Ensure no duplicate approvers (each admin can only approve once)
-        if valid_approver_count != approvers.len() {
-            panic!("Duplicate approvers detected");
-        }
+    Ok(())
+}
 
-        // 6. Check cooldown period
-        let last_override_time: u64 = env
-            .storage()
-            .persistent()
-            .get(&Symbol::new(&env, LAST_OVERRIDE_TIME_KEY))
-            .unwrap_or(0);
+/// Require `signer` to be one of the override signers
+fn require_signer(env: &Env, signer: &Address) -> Result<(), OverrideError> {
+    let signers: Vec<Address> = env
+        .storage()
+        .instance()
+        .get(&DataKey::AdminSigners)
+        .ok_or(OverrideError::NotInitialized)?;
+    if !signers.contains(signer) {
+        return Err(OverrideError::InvalidApprover);
+    }
+    Ok(())
+}
 
-        let cooldown_period: u64 = env
-            .storage()
-            .persistent()
-            .get(&Symbol::new(&env, OVERRIDE_COOLDOWN_KEY))
-            .unwrap_or(86400);
+/// Whether the cooldown since the last override has passed
+fn override_cooldown_elapsed(env: &Env) -> bool {
+    let last_override_time: u64 = env
+        .storage()
+        .instance()
+        .get(&DataKey::LastOverrideTime)
+        .unwrap_or(0);
+    let cooldown_period: u64 = env
+        .storage()
+        .instance()
+        .get(&DataKey::OverrideCooldown)
+        .unwrap_or(86400);
+
+    last_override_time == 0 || env.ledger().timestamp() - last_override_time >= cooldown_period
+}
 
-        let current_time = env.ledger().timestamp();
+/// Load an override proposal that can still be approved or executed
+fn load_open_proposal(env: &Env, proposal_id: u32) -> Result<OverrideProposal, OverrideError> {
+    let proposal: OverrideProposal = env
+        .storage()
+        .persistent()
+        .get(&DataKey::OverrideProposal(proposal_id))
+        .ok_or(OverrideError::ProposalNotFound)?;
+    if proposal.status != OverrideStatus::Pending {
+        return Err(OverrideError::ProposalNotPending);
+    }
+    if env.ledger().timestamp() >= proposal.expires_at {
+        return Err(OverrideError::ProposalExpired);
+    }
+    Ok(proposal)
+}
 
-        if last_override_time > 0 && (current_time - last_override_time) < cooldown_period {
-            panic!("Cooldown period not elapsed");
+/// A proposal's approvals from current signers, if they meet the threshold
+///
+/// Approvals from signers removed since approving no longer count.
+fn counted_approvals(env: &Env, proposal: &OverrideProposal) -> Option<Vec<OverrideApproval>> {
+    let signers: Vec<Address> = env
+        .storage()
+        .instance()
+        .get(&DataKey::AdminSigners)
+        .unwrap_or(Vec::new(env));
+    let mut approvals = Vec::new(env);
+    for approval in proposal.approvals.iter() {
+        if signers.contains(&approval.admin) {
+            approvals.push_back(approval);
         }
+    }
+    if approvals.len() < required_approvals(env, &signers) {
+        return None;
+    }
+    Some(approvals)
+}
 
-        // 7. Verify market exists
-        let market_key = (Symbol::new(&env, MARKET_RES_TIME_KEY), market_id.clone());
-        if !env.storage().persistent().has(&market_key) {
-            panic!("Market not registered");
-        }
+/// Apply an approved proposal and close it
+fn execute_proposal(
+    env: &Env,
+    proposal_id: u32,
+    mut proposal: OverrideProposal,
+    approvals: Vec<OverrideApproval>,
+) -> Result<(), OverrideError> {
+    apply_override(
+        env,
+        &proposal.market_id,
+        proposal.forced_outcome,
+        &proposal.justification_hash,
+        approvals,
+    )?;
+
+    proposal.status = OverrideStatus::Executed;
+    let proposal_key = DataKey::OverrideProposal(proposal_id);
+    env.storage().persistent().set(&proposal_key, &proposal);
+    extend_persistent(env, &proposal_key);
+    Ok(())
+}
 
-        // 8. Store consensus result (override any existing consensus)
-        let result_key = (Symbol::new(&env, "consensus_result"), market_id.clone());
-        env.storage().persistent().set(&result_key, &forced_outcome);
+/// Force a market's consensus result, subject to the override cooldown
+///
+/// Stores the result, the manual-override flag and the full audit record.
+fn apply_override(
+    env: &Env,
+    market_id: &BytesN<32>,
+    forced_outcome: u32,
+    justification_hash: &BytesN<32>,
+    approvals: Vec<OverrideApproval>,
+) -> Result<(), OverrideError> {
+    if !override_cooldown_elapsed(env) {
+        return Err(OverrideError::CooldownNotElapsed);
+    }
+    if !env
+        .storage()
+        .persistent()
+        .has(&DataKey::MarketResTime(market_id.clone()))
+    {
+        return Err(OverrideError::MarketNotRegistered);
+    }
 
-        // 9. Mark market as manually overridden for audit purposes
-        let override_flag_key = (Symbol::new(&env, "manual_override"), market_id.clone());
-        env.storage().persistent().set(&override_flag_key, &true);
+    // Override any existing consensus and flag the market for audit
+    store_consensus_result(env, market_id, forced_outcome);
+    env.storage()
+        .persistent()
+        .set(&DataKey::ManualOverride(market_id.clone()), &true);
 
-        // 10. Create and store complete override record
-        let override_record = EmergencyOverrideRecord {
+    let current_time = env.ledger().timestamp();
+    let mut approvers = Vec::new(env);
+    for approval in approvals.iter() {
+        approvers.push_back(approval.admin);
+    }
+    env.storage().persistent().set(
+        &DataKey::OverrideRecord(market_id.clone()),
+        &EmergencyOverrideRecord {
             market_id: market_id.clone(),
             forced_outcome,
             justification_hash: justification_hash.clone(),
             approvers: approvers.clone(),
+            approvals,
             timestamp: current_time,
-        };
+        },
+    );
+    env.storage()
+        .instance()
+        .set(&DataKey::LastOverrideTime, &current_time);
+
+    extend_instance(env);
+    extend_market(env, market_id);
+
+    #[allow(deprecated)]
+    env.events().publish(
+        (Symbol::new(env, "EmergencyOverride"),),
+        (
+            market_id.clone(),
+            forced_outcome,
+            justification_hash.clone(),
+            approvers,
+            current_time,
+        ),
+    );
 
-        let override_record_key = (Symbol::new(&env, "override_record"), market_id.clone());
-        env.storage()
-            .persistent()
-            .set(&override_record_key, &override_record);
+    Ok(())
+}
 
-        // 11. Update last override timestamp
-        env.storage()
-            .persistent()
-            .set(&Symbol::new(&env, LAST_OVERRIDE_TIME_KEY), &current_time);
+/// Load the configured stake token
+fn get_stake_token(env: &Env) -> Result<Address, OracleError> {
+    env.storage()
+        .instance()
+        .get(&DataKey::StakeToken)
+        .ok_or(OracleError::StakingNotConfigured)
+}
 
-        // 12. Emit EmergencyOverride event with all details
-        env.events().publish(
-            (Symbol::new(&env, "EmergencyOverride"),),
-            (
-                market_id,
-                forced_outcome,
-                justification_hash,
-                approvers,
-                current_time,
-            ),
+/// Require an oracle to be active and hold the minimum bonded stake
+fn require_unchallenged(env: &Env, oracle: &Address) -> Result<(), OracleError> {
+    if env
+        .storage()
+        .persistent()
+        .has(&DataKey::OpenChallenges(oracle.clone()))
+    {
+        return Err(OracleError::StakeUnderChallenge);
+    }
+    Ok(())
+}
+
+fn require_attestor(env: &Env, oracle: &Address) -> Result<(), OracleError> {
+    let is_registered: bool = env
+        .storage()
+        .persistent()
+        .get(&DataKey::Oracle(oracle.clone()))
+        .unwrap_or(false);
+    if !is_registered {
+        return Err(OracleError::OracleNotRegistered);
+    }
+
+    let stake: i128 = env
+        .storage()
+        .persistent()
+        .get(&DataKey::OracleStake(oracle.clone()))
+        .unwrap_or(0);
+    let min_stake: i128 = env
+        .storage()
+        .instance()
+        .get(&DataKey::MinStake)
+        .unwrap_or(0);
+    if stake < min_stake {
+        return Err(OracleError::InsufficientStake);
+    }
+    Ok(())
+}
+
+/// Record a vote that counts toward consensus
+///
+/// Stores the vote (and its weight on weighted markets), the attestation,
+/// the voter list entry and the per-outcome count.
+fn record_vote(
+    env: &Env,
+    oracle: &Address,
+    market_id: &BytesN<32>,
+    outcome: u32,
+    data_hash: BytesN<32>,
+) {
+    env.storage()
+        .persistent()
+        .set(&DataKey::Vote(market_id.clone(), oracle.clone()), &outcome);
+    if let ConsensusMode::Weighted(config) =
+        OracleManager::get_consensus_mode(env.clone(), market_id.clone())
+    {
+        let weight = vote_weight(env, oracle, config.weighting);
+        env.storage().persistent().set(
+            &DataKey::VoteWeight(market_id.clone(), oracle.clone()),
+            &weight,
         );
     }
 
-    /// Get emergency override record for a market (for audit purposes)
-    pub fn get_override_record(env: Env, market_id: BytesN<32>) -> Option<EmergencyOverrideRecord> {
-        let override_record_key = (Symbol::new(&env, "override_record"), market_id);
-        env.storage().persistent().get(&override_record_key)
+    let attestation = Attestation {
+        attestor: oracle.clone(),
+        outcome,
+        data_hash,
+        timestamp: env.ledger().timestamp(),
+    };
+    env.storage().persistent().set(
+        &DataKey::Attestation(market_id.clone(), oracle.clone()),
+        &attestation,
+    );
+
+    let voters_key = DataKey::Voters(market_id.clone());
+    let mut voters: Vec<Address> = env
+        .storage()
+        .persistent()
+        .get(&voters_key)
+        .unwrap_or(Vec::new(env));
+    voters.push_back(oracle.clone());
+    env.storage().persistent().set(&voters_key, &voters);
+
+    let count_key = if outcome == 1 {
+        DataKey::AttestCountYes(market_id.clone())
+    } else {
+        DataKey::AttestCountNo(market_id.clone())
+    };
+    let count: u32 = env.storage().persistent().get(&count_key).unwrap_or(0);
+    env.storage().persistent().set(&count_key, &(count + 1));
+
+    update_stats(env, oracle, |stats| stats.attestations += 1);
+}
+
+/// Cut the accuracy of every committer that did not reveal, once per market
+fn settle_missed_reveals(env: &Env, market_id: &BytesN<32>) -> u32 {
+    let settled_key = DataKey::RevealsSettled(market_id.clone());
+    if env.storage().persistent().has(&settled_key) {
+        return 0;
     }
+    env.storage().persistent().set(&settled_key, &true);
+
+    let committers: Vec<Address> = env
+        .storage()
+        .persistent()
+        .get(&DataKey::Committers(market_id.clone()))
+        .unwrap_or(Vec::new(env));
+    let mut penalized = 0u32;
+    for oracle in committers.iter() {
+        if env
+            .storage()
+            .persistent()
+            .has(&DataKey::Vote(market_id.clone(), oracle.clone()))
+        {
+            continue;
+        }
 
-    /// Check if market was manually overridden
-    pub fn is_manual_override(env: Env, market_id: BytesN<32>) -> bool {
-        let override_flag_key = (Symbol::new(&env, "manual_override"), market_id);
+        let accuracy_key = DataKey::OracleAccuracy(oracle.clone());
+        let accuracy: u32 = env.storage().persistent().get(&accuracy_key).unwrap_or(100);
+        let new_reputation = accuracy.saturating_sub(MISSED_REVEAL_PENALTY);
         env.storage()
             .persistent()
-            .get(&override_flag_key)
-            .unwrap_or(false)
+            .set(&accuracy_key, &new_reputation);
+        if new_reputation < 50 && OracleManager::is_oracle_active(env.clone(), oracle.clone()) {
+            deactivate_oracle(env, &oracle);
+        }
+        update_stats(env, &oracle, |stats| stats.missed_reveals += 1);
+        extend_oracle(env, &oracle);
+        penalized += 1;
+
+        RevealMissedEvent {
+            market_id: market_id.clone(),
+            oracle,
+            new_reputation,
+        }
+        .publish(env);
     }
+    penalized
+}
 
-    /// Get admin signers list
-    pub fn get_admin_signers(env: Env) -> Vec<Address> {
-        env.storage()
-            .persistent()
-            .get(&Symbol::new(&env, ADMIN_SIGNERS_KEY))
-            .unwrap_or(Vec::new(&env))
+/// Weight an oracle's vote carries right now
+fn vote_weight(env: &Env, oracle: &Address, weighting: VoteWeighting) -> i128 {
+    let stake: i128 = env
+        .storage()
+        .persistent()
+        .get(&DataKey::OracleStake(oracle.clone()))
+        .unwrap_or(0);
+    let accuracy: u32 = env
+        .storage()
+        .persistent()
+        .get(&DataKey::OracleAccuracy(oracle.clone()))
+        .unwrap_or(0);
+    match weighting {
+        VoteWeighting::Stake => stake,
+        VoteWeighting::Accuracy => accuracy as i128,
+        VoteWeighting::StakeAndAccuracy => stake * accuracy as i128 / 100,
     }
+}
 
-    /// Get required signatures for emergency override
-    pub fn get_required_signatures(env: Env) -> u32 {
-        env.storage()
-            .persistent()
-            .get(&Symbol::new(&env, REQUIRED_SIGNATURES_KEY))
-            .unwrap_or(2)
+/// Weighted consensus: the leading outcome must hold `threshold_bps` of the
+/// weight that voted, with at least `quorum` oracles voting. Ties never pass.
+fn check_weighted_consensus(
+    env: &Env,
+    market_id: &BytesN<32>,
+    voters: &Vec<Address>,
+    config: &WeightedConsensus,
+) -> (bool, u32) {
+    if voters.len() < config.quorum {
+        return (false, 0);
     }
 
-    /// Get override cooldown period
-    pub fn get_override_cooldown(env: Env) -> u64 {
-        env.storage()
+    let mut yes_weight: i128 = 0;
+    let mut no_weight: i128 = 0;
+    for oracle in voters.iter() {
+        let vote: u32 = env
+            .storage()
             .persistent()
-            .get(&Symbol::new(&env, OVERRIDE_COOLDOWN_KEY))
-            .unwrap_or(86400)
+            .get(&DataKey::Vote(market_id.clone(), oracle.clone()))
+            .unwrap_or(0);
+        let weight: i128 = env
+            .storage()
+            .persistent()
+            .get(&DataKey::VoteWeight(market_id.clone(), oracle))
+            .unwrap_or(0);
+        if vote == 1 {
+            yes_weight += weight;
+        } else {
+            no_weight += weight;
+        }
     }
 
-    /// Get last override timestamp
-    pub fn get_last_override_time(env: Env) -> u64 {
+    let total_weight = yes_weight + no_weight;
+    let (outcome, leading_weight) = if yes_weight > no_weight {
+        (1, yes_weight)
+    } else if no_weight > yes_weight {
+        (0, no_weight)
+    } else {
+        return (false, 0);
+    };
+
+    if leading_weight * BPS_DENOMINATOR as i128 >= total_weight * config.threshold_bps as i128 {
+        (true, outcome)
+    } else {
+        (false, 0)
+    }
+}
+
+/// Update an oracle's activity counters in place
+fn update_stats(env: &Env, oracle: &Address, update: impl FnOnce(&mut OracleStats)) {
+    let stats_key = DataKey::OracleStats(oracle.clone());
+    let mut stats: OracleStats = env
+        .storage()
+        .persistent()
+        .get(&stats_key)
+        .unwrap_or_default();
+    update(&mut stats);
+    env.storage().persistent().set(&stats_key, &stats);
+}
+
+/// Store a market's consensus result, counting markets resolved for the first time
+fn store_consensus_result(env: &Env, market_id: &BytesN<32>, outcome: u32) {
+    let result_key = DataKey::ConsensusResult(market_id.clone());
+    if !env.storage().persistent().has(&result_key) {
+        let resolved: u32 = env
+            .storage()
+            .instance()
+            .get(&DataKey::MarketsResolved)
+            .unwrap_or(0);
         env.storage()
-            .persistent()
-            .get(&Symbol::new(&env, LAST_OVERRIDE_TIME_KEY))
-            .unwrap_or(0)
+            .instance()
+            .set(&DataKey::MarketsResolved, &(resolved + 1));
+    }
+    env.storage().persistent().set(&result_key, &outcome);
+}
+
+/// Add `amount` to a claimable reward balance
+fn credit_reward(env: &Env, key: DataKey, amount: i128) {
+    let current: i128 = env.storage().persistent().get(&key).unwrap_or(0);
+    env.storage().persistent().set(&key, &(current + amount));
+}
+
+/// Mark an active oracle inactive, keeping its records, and drop it from the count
+fn deactivate_oracle(env: &Env, oracle: &Address) {
+    env.storage()
+        .persistent()
+        .set(&DataKey::Oracle(oracle.clone()), &false);
+
+    let oracle_count: u32 = env
+        .storage()
+        .instance()
+        .get(&DataKey::OracleCount)
+        .unwrap_or(0);
+    env.storage()
+        .instance()
+        .set(&DataKey::OracleCount, &oracle_count.saturating_sub(1));
+
+    OracleDeregisteredEvent {
+        oracle: oracle.clone(),
+        timestamp: env.ledger().timestamp(),
+    }
+    .publish(env);
+}
+
+/// Extend the TTL of an oracle's registration entries
+fn extend_oracle(env: &Env, oracle: &Address) {
+    for key in [
+        DataKey::Oracle(oracle.clone()),
+        DataKey::OracleName(oracle.clone()),
+        DataKey::OracleAccuracy(oracle.clone()),
+        DataKey::OracleStake(oracle.clone()),
+        DataKey::OracleTimestamp(oracle.clone()),
+        DataKey::Unbonding(oracle.clone()),
+        DataKey::OpenChallenges(oracle.clone()),
+        DataKey::OracleStats(oracle.clone()),
+    ] {
+        extend_persistent(env, &key);
+    }
+}
+
+/// Extend the TTL of a market's resolution entries, including every voter's records
+fn extend_market(env: &Env, market_id: &BytesN<32>) {
+    for key in [
+        DataKey::MarketResTime(market_id.clone()),
+        DataKey::AttestCountYes(market_id.clone()),
+        DataKey::AttestCountNo(market_id.clone()),
+        DataKey::MarketConsensus(market_id.clone()),
+        DataKey::MarketThreshold(market_id.clone()),
+        DataKey::RevealWindow(market_id.clone()),
+        DataKey::Committers(market_id.clone()),
+        DataKey::RevealsSettled(market_id.clone()),
+        DataKey::Optimistic(market_id.clone()),
+        DataKey::Proposal(market_id.clone()),
+        DataKey::Voters(market_id.clone()),
+        DataKey::ConsensusResult(market_id.clone()),
+        DataKey::MarketChallenged(market_id.clone()),
+        DataKey::ManualOverride(market_id.clone()),
+        DataKey::OverrideRecord(market_id.clone()),
+    ] {
+        extend_persistent(env, &key);
+    }
+
+    let voters: Vec<Address> = env
+        .storage()
+        .persistent()
+        .get(&DataKey::Voters(market_id.clone()))
+        .unwrap_or(Vec::new(env));
+    for oracle in voters.iter() {
+        for key in [
+            DataKey::Vote(market_id.clone(), oracle.clone()),
+            DataKey::VoteWeight(market_id.clone(), oracle.clone()),
+            DataKey::Attestation(market_id.clone(), oracle.clone()),
+            DataKey::Challenge(market_id.clone(), oracle.clone()),
+        ] {
+            extend_persistent(env, &key);
+        }
+    }
+
+    let committers: Vec<Address> = env
+        .storage()
+        .persistent()
+        .get(&DataKey::Committers(market_id.clone()))
+        .unwrap_or(Vec::new(env));
+    for oracle in committers.iter() {
+        extend_persistent(env, &DataKey::AttestCommit(market_id.clone(), oracle));
     }
 }
 
@@ -1026,6 +3261,11 @@ mod tests {
         env.mock_all_auths();
         oracle_client.initialize(&admin, &2); // Require 2 oracles for consensus
 
+        let stake_token = env
+            .register_stellar_asset_contract_v2(Address::generate(env))
+            .address();
+        oracle_client.configure_staking(&stake_token, &0, &3600);
+
         (oracle_client, admin, oracle1, oracle2)
     }
 
@@ -1043,6 +3283,28 @@ mod tests {
         BytesN::from_array(env, &[1u8; 32])
     }
 
+    /// Have `oracle` lock `amount` of the stake token
+    fn stake_oracle(
+        env: &Env,
+        oracle_client: &OracleManagerClient,
+        oracle: &Address,
+        amount: i128,
+    ) -> token::TokenClient<'static> {
+        let stake_token = oracle_client.get_stake_token().unwrap();
+        token::StellarAssetClient::new(env, &stake_token).mint(oracle, &amount);
+        oracle_client.stake(oracle, &amount);
+        token::TokenClient::new(env, &stake_token)
+    }
+
+    /// A challenger holding enough stake tokens for a few challenges
+    fn fund_challenger(env: &Env, oracle_client: &OracleManagerClient) -> Address {
+        let challenger = Address::generate(env);
+        let stake_token = oracle_client.get_stake_token().unwrap();
+        token::StellarAssetClient::new(env, &stake_token)
+            .mint(&challenger, &(CHALLENGE_STAKE_AMOUNT * 5));
+        challenger
+    }
+
     #[test]
     fn test_challenge_attestation_success() {
         let env = Env::default();
@@ -1055,7 +3317,7 @@ mod tests {
         let resolution_time = env.ledger().timestamp() + 100;
 
         // Register market
-        oracle_client.register_market(&market_id, &resolution_time);
+        oracle_client.register_market(&market_id, &resolution_time, &ConsensusMode::Count);
 
         // Move time forward past resolution
         env.ledger()
@@ -1066,7 +3328,7 @@ mod tests {
         oracle_client.submit_attestation(&oracle1, &market_id, &1, &data_hash);
 
         // Challenger challenges the attestation
-        let challenger = Address::generate(&env);
+        let challenger = fund_challenger(&env, &oracle_client);
         let reason = Symbol::new(&env, "fraud");
 
         oracle_client.challenge_attestation(&challenger, &oracle1, &market_id, &reason);
@@ -1083,12 +3345,18 @@ mod tests {
         assert_eq!(challenge.stake, CHALLENGE_STAKE_AMOUNT);
         assert!(!challenge.resolved);
 
+        // Verify the stake was escrowed
+        let stake_token = token::TokenClient::new(&env, &oracle_client.get_stake_token().unwrap());
+        assert_eq!(
+            stake_token.balance(&oracle_client.address),
+            CHALLENGE_STAKE_AMOUNT
+        );
+
         // Verify market is marked as challenged
         assert!(oracle_client.has_active_challenge(&market_id));
     }
 
     #[test]
-    #[should_panic(expected = "Attestation not found")]
     fn test_challenge_nonexistent_attestation() {
         let env = Env::default();
         env.mock_all_auths();
@@ -1097,15 +3365,16 @@ mod tests {
         register_test_oracles(&env, &oracle_client, &oracle1, &oracle2);
 
         let market_id = create_market_id(&env);
-        let challenger = Address::generate(&env);
+        let challenger = fund_challenger(&env, &oracle_client);
         let reason = Symbol::new(&env, "fraud");
 
         // Try to challenge without attestation
-        oracle_client.challenge_attestation(&challenger, &oracle1, &market_id, &reason);
+        let result =
+            oracle_client.try_challenge_attestation(&challenger, &oracle1, &market_id, &reason);
+        assert_eq!(result, Err(Ok(OracleError::AttestationNotFound)));
     }
 
     #[test]
-    #[should_panic(expected = "Challenge already exists")]
     fn test_challenge_duplicate() {
         let env = Env::default();
         env.mock_all_auths();
@@ -1116,21 +3385,23 @@ mod tests {
         let market_id = create_market_id(&env);
         let resolution_time = env.ledger().timestamp() + 100;
 
-        oracle_client.register_market(&market_id, &resolution_time);
+        oracle_client.register_market(&market_id, &resolution_time, &ConsensusMode::Count);
         env.ledger()
             .with_mut(|li| li.timestamp = resolution_time + 1);
 
         let data_hash = BytesN::from_array(&env, &[2u8; 32]);
         oracle_client.submit_attestation(&oracle1, &market_id, &1, &data_hash);
 
-        let challenger = Address::generate(&env);
+        let challenger = fund_challenger(&env, &oracle_client);
         let reason = Symbol::new(&env, "fraud");
 
         // First challenge
         oracle_client.challenge_attestation(&challenger, &oracle1, &market_id, &reason);
 
         // Try to challenge again
-        oracle_client.challenge_attestation(&challenger, &oracle1, &market_id, &reason);
+        let result =
+            oracle_client.try_challenge_attestation(&challenger, &oracle1, &market_id, &reason);
+        assert_eq!(result, Err(Ok(OracleError::ChallengeAlreadyExists)));
     }
 
     #[test]
@@ -1144,19 +3415,21 @@ mod tests {
         let market_id = create_market_id(&env);
         let resolution_time = env.ledger().timestamp() + 100;
 
-        oracle_client.register_market(&market_id, &resolution_time);
+        oracle_client.register_market(&market_id, &resolution_time, &ConsensusMode::Count);
         env.ledger()
             .with_mut(|li| li.timestamp = resolution_time + 1);
 
         let data_hash = BytesN::from_array(&env, &[2u8; 32]);
         oracle_client.submit_attestation(&oracle1, &market_id, &1, &data_hash);
+        let stake_token = stake_oracle(&env, &oracle_client, &oracle1, 10_000);
 
         // Get initial oracle stake and accuracy
         let initial_stake = oracle_client.get_oracle_stake(&oracle1);
+        assert_eq!(initial_stake, 10_000);
         let initial_accuracy = oracle_client.get_oracle_accuracy(&oracle1);
         assert_eq!(initial_accuracy, 100);
 
-        let challenger = Address::generate(&env);
+        let challenger = fund_challenger(&env, &oracle_client);
         let reason = Symbol::new(&env, "fraud");
 
         oracle_client.challenge_attestation(&challenger, &oracle1, &market_id, &reason);
@@ -1172,6 +3445,18 @@ mod tests {
         let new_stake = oracle_client.get_oracle_stake(&oracle1);
         assert_eq!(new_stake, initial_stake / 2);
 
+        // Challenger claims the escrow back plus the slashed stake
+        let reward = CHALLENGE_STAKE_AMOUNT + initial_stake / 2;
+        assert_eq!(oracle_client.get_claimable_rewards(&challenger), reward);
+        let balance_before = stake_token.balance(&challenger);
+        assert_eq!(oracle_client.claim_rewards(&challenger), reward);
+        assert_eq!(stake_token.balance(&challenger), balance_before + reward);
+        assert_eq!(
+            stake_token.balance(&oracle_client.address),
+            initial_stake / 2
+        );
+        assert_eq!(oracle_client.get_claimable_rewards(&challenger), 0);
+
         // Verify oracle's accuracy was reduced (by 20%)
         let new_accuracy = oracle_client.get_oracle_accuracy(&oracle1);
         assert_eq!(new_accuracy, 80);
@@ -1191,7 +3476,7 @@ mod tests {
         let market_id = create_market_id(&env);
         let resolution_time = env.ledger().timestamp() + 100;
 
-        oracle_client.register_market(&market_id, &resolution_time);
+        oracle_client.register_market(&market_id, &resolution_time, &ConsensusMode::Count);
         env.ledger()
             .with_mut(|li| li.timestamp = resolution_time + 1);
 
@@ -1201,7 +3486,7 @@ mod tests {
         let initial_stake = oracle_client.get_oracle_stake(&oracle1);
         let _initial_accuracy = oracle_client.get_oracle_accuracy(&oracle1);
 
-        let challenger = Address::generate(&env);
+        let challenger = fund_challenger(&env, &oracle_client);
         let reason = Symbol::new(&env, "fraud");
 
         oracle_client.challenge_attestation(&challenger, &oracle1, &market_id, &reason);
@@ -1217,6 +3502,17 @@ mod tests {
         let new_stake = oracle_client.get_oracle_stake(&oracle1);
         assert_eq!(new_stake, initial_stake);
 
+        // The challenger's escrow is forfeited to the oracle
+        assert_eq!(oracle_client.get_claimable_rewards(&challenger), 0);
+        let result = oracle_client.try_claim_rewards(&challenger);
+        assert_eq!(result, Err(Ok(OracleError::NoRewardsToClaim)));
+        assert_eq!(
+            oracle_client.claim_rewards(&oracle1),
+            CHALLENGE_STAKE_AMOUNT
+        );
+        let stake_token = token::TokenClient::new(&env, &oracle_client.get_stake_token().unwrap());
+        assert_eq!(stake_token.balance(&oracle1), CHALLENGE_STAKE_AMOUNT);
+
         // Verify oracle's accuracy was increased (by 5%)
         let new_accuracy = oracle_client.get_oracle_accuracy(&oracle1);
         assert_eq!(new_accuracy, 100); // Capped at 100
@@ -1234,7 +3530,7 @@ mod tests {
         register_test_oracles(&env, &oracle_client, &oracle1, &oracle2);
 
         // Manually set oracle accuracy to 60% (just above threshold)
-        let accuracy_key = (Symbol::new(&env, "oracle_accuracy"), oracle1.clone());
+        let accuracy_key = DataKey::OracleAccuracy(oracle1.clone());
         env.as_contract(&oracle_client.address, || {
             env.storage().persistent().set(&accuracy_key, &60u32);
         });
@@ -1242,14 +3538,14 @@ mod tests {
         let market_id = create_market_id(&env);
         let resolution_time = env.ledger().timestamp() + 100;
 
-        oracle_client.register_market(&market_id, &resolution_time);
+        oracle_client.register_market(&market_id, &resolution_time, &ConsensusMode::Count);
         env.ledger()
             .with_mut(|li| li.timestamp = resolution_time + 1);
 
         let data_hash = BytesN::from_array(&env, &[2u8; 32]);
         oracle_client.submit_attestation(&oracle1, &market_id, &1, &data_hash);
 
-        let challenger = Address::generate(&env);
+        let challenger = fund_challenger(&env, &oracle_client);
         let reason = Symbol::new(&env, "fraud");
 
         oracle_client.challenge_attestation(&challenger, &oracle1, &market_id, &reason);
@@ -1262,7 +3558,7 @@ mod tests {
         assert_eq!(new_accuracy, 40);
 
         // Verify oracle was deregistered (marked as inactive)
-        let oracle_key = (Symbol::new(&env, "oracle"), oracle1.clone());
+        let oracle_key = DataKey::Oracle(oracle1.clone());
         let is_active: bool = env
             .as_contract(&oracle_client.address, || {
                 env.storage().persistent().get(&oracle_key)
@@ -1272,7 +3568,6 @@ mod tests {
     }
 
     #[test]
-    #[should_panic(expected = "Challenge not found")]
     fn test_resolve_nonexistent_challenge() {
         let env = Env::default();
         env.mock_all_auths();
@@ -1283,11 +3578,11 @@ mod tests {
         let market_id = create_market_id(&env);
 
         // Try to resolve non-existent challenge
-        oracle_client.resolve_challenge(&oracle1, &market_id, &true);
+        let result = oracle_client.try_resolve_challenge(&oracle1, &market_id, &true);
+        assert_eq!(result, Err(Ok(OracleError::ChallengeNotFound)));
     }
 
     #[test]
-    #[should_panic(expected = "Challenge already resolved")]
     fn test_resolve_challenge_twice() {
         let env = Env::default();
         env.mock_all_auths();
@@ -1298,14 +3593,14 @@ mod tests {
         let market_id = create_market_id(&env);
         let resolution_time = env.ledger().timestamp() + 100;
 
-        oracle_client.register_market(&market_id, &resolution_time);
+        oracle_client.register_market(&market_id, &resolution_time, &ConsensusMode::Count);
         env.ledger()
             .with_mut(|li| li.timestamp = resolution_time + 1);
 
         let data_hash = BytesN::from_array(&env, &[2u8; 32]);
         oracle_client.submit_attestation(&oracle1, &market_id, &1, &data_hash);
 
-        let challenger = Address::generate(&env);
+        let challenger = fund_challenger(&env, &oracle_client);
         let reason = Symbol::new(&env, "fraud");
 
         oracle_client.challenge_attestation(&challenger, &oracle1, &market_id, &reason);
@@ -1314,11 +3609,12 @@ mod tests {
         oracle_client.resolve_challenge(&oracle1, &market_id, &true);
 
         // Try to resolve again
-        oracle_client.resolve_challenge(&oracle1, &market_id, &true);
+        let result = oracle_client.try_resolve_challenge(&oracle1, &market_id, &true);
+        assert_eq!(result, Err(Ok(OracleError::ChallengeAlreadyResolved)));
     }
 
     #[test]
-    fn test_oracle_stake_initialized_on_registration() {
+    fn test_oracle_stake_starts_empty_on_registration() {
         let env = Env::default();
         env.mock_all_auths();
 
@@ -1327,9 +3623,102 @@ mod tests {
         // Register oracle
         oracle_client.register_oracle(&oracle1, &Symbol::new(&env, "Oracle1"));
 
-        // Verify stake was initialized
-        let stake = oracle_client.get_oracle_stake(&oracle1);
-        assert_eq!(stake, CHALLENGE_STAKE_AMOUNT * 10);
+        // No tokens locked until the oracle stakes
+        assert_eq!(oracle_client.get_oracle_stake(&oracle1), 0);
+
+        let stake_token = stake_oracle(&env, &oracle_client, &oracle1, 5_000);
+        assert_eq!(oracle_client.get_oracle_stake(&oracle1), 5_000);
+        assert_eq!(stake_token.balance(&oracle_client.address), 5_000);
+    }
+
+    #[test]
+    fn test_slash_reaches_unbonding_stake() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let (oracle_client, _admin, oracle1, oracle2) = setup_oracle(&env);
+        register_test_oracles(&env, &oracle_client, &oracle1, &oracle2);
+
+        let market_id = create_market_id(&env);
+        let resolution_time = env.ledger().timestamp() + 100;
+        oracle_client.register_market(&market_id, &resolution_time, &ConsensusMode::Count);
+        env.ledger()
+            .with_mut(|li| li.timestamp = resolution_time + 1);
+        oracle_client.submit_attestation(
+            &oracle1,
+            &market_id,
+            &1,
+            &BytesN::from_array(&env, &[2u8; 32]),
+        );
+
+        // Unstaking everything ahead of the ruling does not dodge the slash
+        let stake_token = stake_oracle(&env, &oracle_client, &oracle1, 10_000);
+        oracle_client.unstake(&oracle1, &8_000);
+
+        let challenger = fund_challenger(&env, &oracle_client);
+        oracle_client.challenge_attestation(
+            &challenger,
+            &oracle1,
+            &market_id,
+            &Symbol::new(&env, "fraud"),
+        );
+        oracle_client.resolve_challenge(&oracle1, &market_id, &true);
+
+        assert_eq!(
+            oracle_client.get_claimable_rewards(&challenger),
+            CHALLENGE_STAKE_AMOUNT + 5_000
+        );
+        assert_eq!(
+            stake_token.balance(&oracle_client.address),
+            10_000 + CHALLENGE_STAKE_AMOUNT
+        );
+        assert_eq!(oracle_client.get_oracle_stake(&oracle1), 0);
+        assert_eq!(
+            oracle_client.get_unbonding_stake(&oracle1).unwrap().amount,
+            5_000
+        );
+    }
+
+    #[test]
+    fn test_stake_locked_while_challenged() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let (oracle_client, _admin, oracle1, oracle2) = setup_oracle(&env);
+        register_test_oracles(&env, &oracle_client, &oracle1, &oracle2);
+
+        let market_id = create_market_id(&env);
+        let resolution_time = env.ledger().timestamp() + 100;
+        oracle_client.register_market(&market_id, &resolution_time, &ConsensusMode::Count);
+        env.ledger()
+            .with_mut(|li| li.timestamp = resolution_time + 1);
+        oracle_client.submit_attestation(
+            &oracle1,
+            &market_id,
+            &1,
+            &BytesN::from_array(&env, &[2u8; 32]),
+        );
+        stake_oracle(&env, &oracle_client, &oracle1, 10_000);
+        let release_time = oracle_client.unstake(&oracle1, &4_000);
+
+        let challenger = fund_challenger(&env, &oracle_client);
+        oracle_client.challenge_attestation(
+            &challenger,
+            &oracle1,
+            &market_id,
+            &Symbol::new(&env, "fraud"),
+        );
+
+        // Neither more unstaking nor withdrawing can empty the stake first
+        let result = oracle_client.try_unstake(&oracle1, &6_000);
+        assert_eq!(result, Err(Ok(OracleError::StakeUnderChallenge)));
+        env.ledger().with_mut(|li| li.timestamp = release_time);
+        let result = oracle_client.try_withdraw_stake(&oracle1);
+        assert_eq!(result, Err(Ok(OracleError::StakeUnderChallenge)));
+
+        // The slash comes out of bonded stake, then the rest is released
+        oracle_client.resolve_challenge(&oracle1, &market_id, &true);
+        assert_eq!(oracle_client.withdraw_stake(&oracle1), 4_000);
     }
 
     #[test]
@@ -1366,7 +3755,7 @@ mod tests {
         let market_id = create_market_id(&env);
         let resolution_time = env.ledger().timestamp() + 100;
 
-        oracle_client.register_market(&market_id, &resolution_time);
+        oracle_client.register_market(&market_id, &resolution_time, &ConsensusMode::Count);
         env.ledger()
             .with_mut(|li| li.timestamp = resolution_time + 1);
 
@@ -1376,7 +3765,7 @@ mod tests {
         oracle_client.submit_attestation(&oracle1, &market_id, &1, &data_hash);
         oracle_client.submit_attestation(&oracle2, &market_id, &0, &data_hash);
 
-        let challenger = Address::generate(&env);
+        let challenger = fund_challenger(&env, &oracle_client);
         let reason = Symbol::new(&env, "fraud");
 
         // Challenge both oracles
@@ -1,7 +1,14 @@
 // contract/src/treasury.rs - Treasury Contract Implementation
 // Handles fee collection and reward distribution
 
-use soroban_sdk::{contract, contractevent, contractimpl, token, Address, Env, Symbol};
+use soroban_sdk::{
+    contract, contracterror, contractevent, contractimpl, symbol_short, token, vec, xdr::ToXdr,
+    Address, Bytes, BytesN, Env, Symbol, Vec,
+};
+
+pub use crate::helpers::FeeKind;
+use crate::helpers::{extend_instance, extend_persistent};
+use crate::upgrade::{self, PendingUpgrade, UpgradeError};
 
 #[contractevent]
 pub struct TreasuryInitializedEvent {
@@ -10,6 +17,11 @@ pub struct TreasuryInitializedEvent {
     pub factory: Address,
 }
 
+#[contractevent]
+pub struct TokenAddedEvent {
+    pub token: Address,
+}
+
 #[contractevent]
 pub struct FeeDistributionUpdatedEvent {
     pub platform_fee_pct: u32,
@@ -18,36 +30,343 @@ pub struct FeeDistributionUpdatedEvent {
     pub timestamp: u64,
 }
 
+#[contractevent]
+pub struct FeeSplitsUpdatedEvent {
+    pub splits: Vec<FeeSplit>,
+    pub dust_bucket: Symbol,
+}
+
+#[contractevent]
+pub struct FeeSourceUpdatedEvent {
+    pub source: Address,
+    pub kinds: Vec<FeeKind>,
+}
+
+#[contractevent]
+pub struct InsuredContractUpdatedEvent {
+    pub contract: Address,
+    pub allowed: bool,
+}
+
+#[contractevent]
+pub struct ShortfallCoveredEvent {
+    pub draw_id: u32,
+    pub market: Address,
+    pub token: Address,
+    pub amount: i128,
+    pub reason: Symbol,
+}
+
 #[contractevent]
 pub struct FeeCollectedEvent {
     pub source: Address,
+    pub token: Address,
+    pub kind: FeeKind,
+    pub creator: Option<Address>,
     pub amount: i128,
     pub timestamp: u64,
 }
 
+#[contractevent]
+pub struct CreatorRewardsClaimedEvent {
+    pub creator: Address,
+    pub token: Address,
+    pub amount: i128,
+}
+
 #[contractevent]
 pub struct CreatorRewardsEvent {
+    pub token: Address,
     pub total_amount: i128,
     pub count: u32,
 }
 
+#[contractevent]
+pub struct LeaderboardPublishedEvent {
+    pub epoch: u32,
+    pub token: Address,
+    pub merkle_root: BytesN<32>,
+    pub total: i128,
+}
+
+#[contractevent]
+pub struct LeaderboardRewardClaimedEvent {
+    pub epoch: u32,
+    pub user: Address,
+    pub amount: i128,
+}
+
+#[contractevent]
+pub struct WithdrawalPolicyUpdatedEvent {
+    pub policy: WithdrawalPolicy,
+}
+
+#[contractevent]
+pub struct WithdrawalQueuedEvent {
+    pub withdrawal_id: u32,
+    pub proposer: Address,
+    pub token: Address,
+    pub bucket: Symbol,
+    pub recipient: Address,
+    pub amount: i128,
+    pub eta: u64,
+}
+
+#[contractevent]
+pub struct WithdrawalApprovedEvent {
+    pub withdrawal_id: u32,
+    pub signer: Address,
+}
+
+#[contractevent]
+pub struct WithdrawalCancelledEvent {
+    pub withdrawal_id: u32,
+    pub signer: Address,
+}
+
+#[contractevent]
+pub struct BudgetUpdatedEvent {
+    pub budget: Symbol,
+    pub token: Address,
+    pub epoch_limit: i128,
+    pub epoch_length: u64,
+}
+
+#[contractevent]
+pub struct BudgetAllocatedEvent {
+    pub budget: Symbol,
+    pub token: Address,
+    pub amount: i128,
+}
+
+#[contractevent]
+pub struct BudgetSpenderUpdatedEvent {
+    pub budget: Symbol,
+    pub spender: Address,
+    pub allowed: bool,
+}
+
+#[contractevent]
+pub struct BudgetDrawnEvent {
+    pub budget: Symbol,
+    pub token: Address,
+    pub spender: Address,
+    pub recipient: Address,
+    pub amount: i128,
+}
+
+#[contractevent]
+pub struct StreamCreatedEvent {
+    pub stream_id: u32,
+    pub recipient: Address,
+    pub token: Address,
+    pub pool: FeePool,
+    pub total: i128,
+    pub start: u64,
+    pub cliff: u64,
+    pub end: u64,
+}
+
+#[contractevent]
+pub struct StreamWithdrawnEvent {
+    pub stream_id: u32,
+    pub recipient: Address,
+    pub amount: i128,
+}
+
+#[contractevent]
+pub struct StreamRevokedEvent {
+    pub stream_id: u32,
+    pub returned: i128,
+}
+
+#[contractevent]
+pub struct SurplusCreditedEvent {
+    pub token: Address,
+    pub pool: FeePool,
+    pub amount: i128,
+}
+
 #[contractevent]
 pub struct EmergencyWithdrawalEvent {
-    pub admin: Address,
+    pub withdrawal_id: u32,
+    pub token: Address,
+    pub bucket: Symbol,
     pub recipient: Address,
     pub amount: i128,
     pub timestamp: u64,
 }
 
-// Storage keys
-const ADMIN_KEY: &str = "admin";
-const USDC_KEY: &str = "usdc";
-const FACTORY_KEY: &str = "factory";
-const PLATFORM_FEES_KEY: &str = "platform_fees";
-const LEADERBOARD_FEES_KEY: &str = "leaderboard_fees";
-const CREATOR_FEES_KEY: &str = "creator_fees";
-const TOTAL_FEES_KEY: &str = "total_fees";
-const DISTRIBUTION_KEY: &str = "distribution";
+/// Basis points in a whole fee
+const BPS_DENOMINATOR: u32 = 10_000;
+
+/// Custom bucket backing `cover_shortfall`, which is its only payout
+const INSURANCE_BUCKET: Symbol = symbol_short!("insurance");
+
+/// Insurance fund share of the default fee split
+const DEFAULT_INSURANCE_BPS: u32 = 500;
+
+/// Storage schema version written by this code
+const SCHEMA_VERSION: u32 = 1;
+
+/// Storage keys
+///
+/// Config and pool balances live in instance storage; leaderboard epochs,
+/// claims and per-creator balances are persistent. Balances are kept per
+/// token.
+#[soroban_sdk::contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum DataKey {
+    Admin,
+    /// Token the treasury was initialized with
+    Usdc,
+    Factory,
+    /// Number of leaderboard epochs published, also the next epoch number
+    LeaderboardEpochCount,
+    /// Merkle root and funding of a leaderboard epoch
+    LeaderboardEpoch(u32),
+    /// Whether a user has claimed their reward for an epoch
+    LeaderboardClaimed(u32, Address),
+    /// Signers, delay and cap governing emergency withdrawals
+    WithdrawalPolicy,
+    /// Number of withdrawals queued, also the next withdrawal id
+    WithdrawalCount,
+    /// Queued emergency withdrawal by id
+    Withdrawal(u32),
+    /// Whether an address may draw from a budget
+    BudgetSpender(Symbol, Address),
+    /// Tokens accepted by `deposit_fees`
+    Tokens,
+    /// Fee pool balance per token
+    TokenPool(FeePool, Address),
+    /// Total fees collected per token
+    TokenTotalFees(Address),
+    /// Creator share owed to attributed creators and not yet claimed
+    TokenCreatorOwed(Address),
+    /// Lifetime and unclaimed fees attributed to a creator, per token
+    TokenCreatorEarnings(Address, Address),
+    /// Amount withdrawn in the current cap period, per token
+    TokenWithdrawalWindow(Address),
+    /// Named spending budget, per token
+    TokenBudget(Symbol, Address),
+    /// Number of streams created, also the next stream id
+    StreamCount,
+    /// Vesting stream by id
+    Stream(u32),
+    /// Funds moved out of the pools but not yet paid: unclaimed leaderboard
+    /// epochs, budget balances and unwithdrawn stream amounts, per token
+    TokenReserved(Address),
+    /// Basis point split applied by `deposit_fees`
+    FeeSplits,
+    /// Every custom bucket ever configured, so balances stay reconcilable
+    Buckets,
+    /// Balance of a custom fee bucket, per token
+    TokenBucket(Symbol, Address),
+    /// Whether a market or AMM contract may draw on the insurance fund
+    InsuredContract(Address),
+    /// Number of insurance draws, also the next draw id
+    ShortfallDrawCount,
+    /// Insurance draw by id
+    ShortfallDraw(u32),
+    /// Fee kinds a contract besides the factory may pass to `deposit_fees`
+    FeeSource(Address),
+    /// Total fees collected per deposit kind, per token
+    TokenKindFees(FeeKind, Address),
+}
+
+/// Error codes returned by the treasury
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum TreasuryError {
+    /// Treasury has not been initialized
+    NotInitialized = 1,
+    /// Treasury is already initialized
+    AlreadyInitialized = 2,
+    /// Caller is not the admin
+    Unauthorized = 3,
+    /// Amount must be positive
+    InvalidAmount = 4,
+    /// Fee split must cover 100% across distinct buckets
+    InvalidRatios = 5,
+    /// Pool balance is lower than the requested payout
+    InsufficientBalance = 6,
+    /// No leaderboard epoch with this number
+    EpochNotFound = 7,
+    /// Reward for this epoch was already claimed
+    AlreadyClaimed = 8,
+    /// Merkle proof does not match the epoch root
+    InvalidProof = 9,
+    /// Creator has no unclaimed rewards
+    NothingToClaim = 10,
+    /// Signer set, threshold or cap is invalid
+    InvalidPolicy = 11,
+    /// Fewer approvers than the required signatures
+    InsufficientApprovers = 12,
+    /// Address is not a withdrawal signer
+    InvalidApprover = 13,
+    /// Same signer listed more than once
+    DuplicateApprovers = 14,
+    /// No withdrawal with this id
+    WithdrawalNotFound = 15,
+    /// Withdrawal was already executed or cancelled
+    WithdrawalNotPending = 16,
+    /// Signer has already approved this withdrawal
+    AlreadyApproved = 17,
+    /// Withdrawal delay has not elapsed
+    WithdrawalDelayNotElapsed = 18,
+    /// Withdrawal would exceed the per-period cap
+    PeriodCapExceeded = 19,
+    /// No budget with this name
+    BudgetNotFound = 20,
+    /// Address is not a spender of this budget
+    NotBudgetSpender = 21,
+    /// Draw would exceed the budget's per-epoch limit
+    BudgetLimitExceeded = 22,
+    /// Budget limit or epoch length is invalid
+    InvalidBudget = 23,
+    /// Token is not accepted by the treasury
+    UnsupportedToken = 24,
+    /// Token is already accepted
+    TokenAlreadySupported = 25,
+    /// No stream with this id
+    StreamNotFound = 26,
+    /// Stream amount or schedule is invalid
+    InvalidStream = 27,
+    /// Stream was already revoked
+    StreamRevoked = 28,
+    /// Amount exceeds the untracked surplus
+    InsufficientSurplus = 29,
+    /// No custom fee bucket with this name
+    BucketNotFound = 30,
+    /// Caller is not a registered market or AMM contract
+    NotInsured = 31,
+    /// Depositor is not the factory or a registered fee source
+    UnregisteredSource = 32,
+    /// Insurance fund only pays out through `cover_shortfall`
+    InsuranceLocked = 33,
+    /// Source may not deposit fees of this kind
+    KindNotAllowed = 34,
+    /// No upgrade has been proposed
+    NoPendingUpgrade = 100,
+    /// Hash differs from the proposed upgrade
+    WasmHashMismatch = 101,
+    /// Upgrade timelock has not elapsed
+    TimelockNotElapsed = 102,
+    /// Storage schema is already current
+    SchemaUpToDate = 103,
+}
+
+impl From<UpgradeError> for TreasuryError {
+    fn from(err: UpgradeError) -> Self {
+        match err {
+            UpgradeError::NoPendingUpgrade => TreasuryError::NoPendingUpgrade,
+            UpgradeError::WasmHashMismatch => TreasuryError::WasmHashMismatch,
+            UpgradeError::TimelockNotElapsed => TreasuryError::TimelockNotElapsed,
+            UpgradeError::SchemaUpToDate => TreasuryError::SchemaUpToDate,
+        }
+    }
+}
 
 /// Fee distribution ratios (sum to 100)
 #[soroban_sdk::contracttype]
@@ -58,6 +377,198 @@ pub struct FeeRatios {
     pub creator: u32,
 }
 
+/// Basis points of each fee credited to a named bucket
+///
+/// `platform`, `leaderboard` and `creator` feed the matching `FeePool`; any
+/// other name is a custom bucket paid out with `emergency_withdraw`.
+#[soroban_sdk::contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FeeSplit {
+    pub bucket: Symbol,
+    pub bps: u32,
+}
+
+/// Fee split and the bucket that receives rounding dust
+#[soroban_sdk::contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FeeSplitConfig {
+    pub splits: Vec<FeeSplit>,
+    pub dust_bucket: Symbol,
+}
+
+/// Fee pool, kept separately for each token
+#[soroban_sdk::contracttype]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum FeePool {
+    Platform,
+    Leaderboard,
+    Creator,
+}
+
+impl FeePool {
+    fn key(self, token: &Address) -> DataKey {
+        DataKey::TokenPool(self, token.clone())
+    }
+
+    /// Fee split bucket name feeding this pool
+    fn bucket(self, env: &Env) -> Symbol {
+        match self {
+            FeePool::Platform => Symbol::new(env, "platform"),
+            FeePool::Leaderboard => Symbol::new(env, "leaderboard"),
+            FeePool::Creator => Symbol::new(env, "creator"),
+        }
+    }
+
+    fn from_bucket(env: &Env, bucket: &Symbol) -> Option<FeePool> {
+        [FeePool::Platform, FeePool::Leaderboard, FeePool::Creator]
+            .into_iter()
+            .find(|pool| pool.bucket(env) == *bucket)
+    }
+}
+
+/// Who may move funds out in an emergency, how fast and how much
+#[soroban_sdk::contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WithdrawalPolicy {
+    pub signers: Vec<Address>,
+    /// Approvals needed to execute a withdrawal or change this policy
+    pub required: u32,
+    /// Seconds between queueing and execution
+    pub delay: u64,
+    /// Length of a cap period in seconds
+    pub period: u64,
+    /// Most that can be withdrawn per period, in each token's own units;
+    /// 0 disables withdrawals
+    pub period_cap: i128,
+}
+
+/// Withdrawals executed in the current cap period
+#[soroban_sdk::contracttype]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct WithdrawalWindow {
+    pub start: u64,
+    pub withdrawn: i128,
+}
+
+/// Lifecycle of a queued withdrawal
+#[soroban_sdk::contracttype]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum WithdrawalStatus {
+    Pending,
+    Executed,
+    Cancelled,
+}
+
+/// Emergency withdrawal waiting out its delay
+#[soroban_sdk::contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Withdrawal {
+    pub token: Address,
+    pub bucket: Symbol,
+    pub recipient: Address,
+    pub amount: i128,
+    pub approvals: Vec<Address>,
+    pub eta: u64,
+    pub status: WithdrawalStatus,
+}
+
+/// Named spending budget funded from one token's platform pool
+///
+/// Delegated spenders may draw the allocated balance down, but no more than
+/// `epoch_limit` per `epoch_length` seconds.
+#[soroban_sdk::contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Budget {
+    /// Allocated and not yet spent
+    pub balance: i128,
+    pub epoch_limit: i128,
+    pub epoch_length: u64,
+    pub epoch_start: u64,
+    pub spent_in_epoch: i128,
+}
+
+/// Tracked obligations in one token against the tokens actually held
+///
+/// At most one of `surplus` and `deficit` is non-zero.
+#[soroban_sdk::contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Reconciliation {
+    pub token: Address,
+    /// Pools, custom buckets, creator debt and reserved payouts
+    pub tracked: i128,
+    /// Token balance of the treasury
+    pub actual: i128,
+    pub surplus: i128,
+    pub deficit: i128,
+}
+
+/// Insurance payout to a market or AMM that could not cover its obligations
+#[soroban_sdk::contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ShortfallDraw {
+    pub market: Address,
+    pub token: Address,
+    pub amount: i128,
+    /// Short code for the cause, e.g. `rounding` or `amm_short`
+    pub reason: Symbol,
+    pub timestamp: u64,
+}
+
+/// Payout vesting linearly from `start` to `end`, nothing before `cliff`
+#[soroban_sdk::contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Stream {
+    pub recipient: Address,
+    pub token: Address,
+    /// Pool the stream was funded from and returns its unvested part to
+    pub pool: FeePool,
+    pub total: i128,
+    pub withdrawn: i128,
+    pub start: u64,
+    pub cliff: u64,
+    pub end: u64,
+    /// Vesting stops at this time once revoked
+    pub revoked_at: Option<u64>,
+}
+
+impl Stream {
+    /// Amount vested by `now`
+    fn vested(&self, now: u64) -> i128 {
+        let now = match self.revoked_at {
+            Some(revoked_at) => now.min(revoked_at),
+            None => now,
+        };
+        if now < self.cliff {
+            0
+        } else if now >= self.end {
+            self.total
+        } else {
+            self.total * (now - self.start) as i128 / (self.end - self.start) as i128
+        }
+    }
+}
+
+/// Creator share of fees attributed to one creator
+#[soroban_sdk::contracttype]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct CreatorEarnings {
+    pub lifetime: i128,
+    pub unclaimed: i128,
+}
+
+/// Leaderboard payout published for one epoch
+#[soroban_sdk::contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LeaderboardEpoch {
+    pub token: Address,
+    /// Root over `leaderboard_leaf(user, amount)` leaves
+    pub merkle_root: BytesN<32>,
+    /// Funding moved out of the leaderboard pool for this epoch
+    pub total: i128,
+    pub claimed: i128,
+    pub published_at: u64,
+}
+
 /// TREASURY - Manages fees and reward distribution
 #[contract]
 pub struct Treasury;
@@ -65,60 +576,60 @@ pub struct Treasury;
 #[contractimpl]
 impl Treasury {
     /// Initialize Treasury contract
-    pub fn initialize(env: Env, admin: Address, usdc_contract: Address, factory: Address) {
+    pub fn initialize(
+        env: Env,
+        admin: Address,
+        usdc_contract: Address,
+        factory: Address,
+    ) -> Result<(), TreasuryError> {
         // Check if already initialized
-        if env
-            .storage()
-            .persistent()
-            .has(&Symbol::new(&env, ADMIN_KEY))
-        {
-            panic!("Already initialized");
+        if env.storage().instance().has(&DataKey::Admin) {
+            return Err(TreasuryError::AlreadyInitialized);
         }
 
         // Verify admin signature
         admin.require_auth();
 
         // Store admin
-        env.storage()
-            .persistent()
-            .set(&Symbol::new(&env, ADMIN_KEY), &admin);
+        env.storage().instance().set(&DataKey::Admin, &admin);
 
         // Store USDC contract
-        env.storage()
-            .persistent()
-            .set(&Symbol::new(&env, USDC_KEY), &usdc_contract);
+        env.storage().instance().set(&DataKey::Usdc, &usdc_contract);
 
         // Store Factory contract
-        env.storage()
-            .persistent()
-            .set(&Symbol::new(&env, FACTORY_KEY), &factory);
-
-        // Initialize fee pools
-        env.storage()
-            .persistent()
-            .set(&Symbol::new(&env, PLATFORM_FEES_KEY), &0i128);
+        env.storage().instance().set(&DataKey::Factory, &factory);
 
-        env.storage()
-            .persistent()
-            .set(&Symbol::new(&env, LEADERBOARD_FEES_KEY), &0i128);
+        // USDC is the first accepted fee token; pools start empty
+        let mut tokens = Vec::new(&env);
+        tokens.push_back(usdc_contract.clone());
+        env.storage().instance().set(&DataKey::Tokens, &tokens);
 
+        // Default distribution: 5% to the insurance fund, the rest 50%
+        // Platform, 30% Leaderboard, 20% Creator
+        let mut buckets = Vec::new(&env);
+        buckets.push_back(INSURANCE_BUCKET);
+        env.storage().instance().set(&DataKey::Buckets, &buckets);
         env.storage()
-            .persistent()
-            .set(&Symbol::new(&env, CREATOR_FEES_KEY), &0i128);
+            .instance()
+            .set(&DataKey::FeeSplits, &default_splits(&env));
 
-        env.storage()
-            .persistent()
-            .set(&Symbol::new(&env, TOTAL_FEES_KEY), &0i128);
+        // Admin alone governs withdrawals, which stay disabled (cap 0) until
+        // the policy is set
+        let mut signers = Vec::new(&env);
+        signers.push_back(admin.clone());
+        env.storage().instance().set(
+            &DataKey::WithdrawalPolicy,
+            &WithdrawalPolicy {
+                signers,
+                required: 1,
+                delay: 86400,
+                period: 86400,
+                period_cap: 0,
+            },
+        );
 
-        // Default distribution: 50% Platform, 30% Leaderboard, 20% Creator
-        let default_ratios = FeeRatios {
-            platform: 50,
-            leaderboard: 30,
-            creator: 20,
-        };
-        env.storage()
-            .persistent()
-            .set(&Symbol::new(&env, DISTRIBUTION_KEY), &default_ratios);
+        upgrade::init_schema_version(&env, SCHEMA_VERSION);
+        extend_instance(&env);
 
         // Emit initialization event
         TreasuryInitializedEvent {
@@ -127,26 +638,52 @@ impl Treasury {
             factory,
         }
         .publish(&env);
+
+        Ok(())
+    }
+
+    /// Admin: Accept fees in another token
+    pub fn add_token(env: Env, token: Address) -> Result<(), TreasuryError> {
+        require_admin(&env)?;
+
+        let mut tokens = Self::get_tokens(env.clone());
+        if tokens.contains(&token) {
+            return Err(TreasuryError::TokenAlreadySupported);
+        }
+        tokens.push_back(token.clone());
+        env.storage().instance().set(&DataKey::Tokens, &tokens);
+        extend_instance(&env);
+
+        TokenAddedEvent { token }.publish(&env);
+
+        Ok(())
+    }
+
+    /// Get the tokens accepted as fees
+    pub fn get_tokens(env: Env) -> Vec<Address> {
+        env.storage()
+            .instance()
+            .get(&DataKey::Tokens)
+            .unwrap_or(Vec::new(&env))
     }
 
     /// Update fee distribution percentages
+    ///
+    /// Shorthand for `set_fee_splits` over the platform, leaderboard and
+    /// creator buckets, with dust to the creator. Nothing goes to the
+    /// insurance fund; use `set_fee_splits` to keep funding it.
     pub fn set_fee_distribution(
         env: Env,
         platform_fee_pct: u32,
         leaderboard_fee_pct: u32,
         creator_fee_pct: u32,
-    ) {
+    ) -> Result<(), TreasuryError> {
         // Require admin authentication
-        let admin: Address = env
-            .storage()
-            .persistent()
-            .get(&Symbol::new(&env, ADMIN_KEY))
-            .expect("Not initialized");
-        admin.require_auth();
+        require_admin(&env)?;
 
         // Validate platform_fee + leaderboard_fee + creator_fee = 100%
         if platform_fee_pct + leaderboard_fee_pct + creator_fee_pct != 100 {
-            panic!("Ratios must sum to 100");
+            return Err(TreasuryError::InvalidRatios);
         }
 
         let new_ratios = FeeRatios {
@@ -156,8 +693,9 @@ impl Treasury {
         };
 
         env.storage()
-            .persistent()
-            .set(&Symbol::new(&env, DISTRIBUTION_KEY), &new_ratios);
+            .instance()
+            .set(&DataKey::FeeSplits, &percent_splits(&env, &new_ratios));
+        extend_instance(&env);
 
         // Emit FeeDistributionUpdated event
         FeeDistributionUpdatedEvent {
@@ -167,202 +705,1509 @@ impl Treasury {
             timestamp: env.ledger().timestamp(),
         }
         .publish(&env);
-    }
 
-    /// Deposit fees into treasury and split across pools
-    pub fn deposit_fees(env: Env, source: Address, amount: i128) {
-        source.require_auth();
-        // Validate amount > 0
-        if amount <= 0 {
-            panic!("Amount must be positive");
-        }
-
-        // Get USDC token contract
-        let usdc_token: Address = env
-            .storage()
-            .persistent()
-            .get(&Symbol::new(&env, USDC_KEY))
-            .expect("USDC not set");
-        let token_client = token::Client::new(&env, &usdc_token);
-        let treasury_address = env.current_contract_address();
-
-        // Transfer USDC from source to treasury
-        // The source must have authorized the treasury to pull funds
-        token_client.transfer(&source, &treasury_address, &amount);
+        Ok(())
+    }
 
-        // Get current ratios
-        let ratios: FeeRatios = env
-            .storage()
-            .persistent()
-            .get(&Symbol::new(&env, DISTRIBUTION_KEY))
-            .expect("Ratios not set");
+    /// Admin: Split fees in basis points across named buckets
+    ///
+    /// Splits must name distinct buckets and sum to 10_000 bps; what
+    /// rounding leaves over goes to `dust_bucket`, which must be one of them.
+    pub fn set_fee_splits(
+        env: Env,
+        splits: Vec<FeeSplit>,
+        dust_bucket: Symbol,
+    ) -> Result<(), TreasuryError> {
+        require_admin(&env)?;
 
-        // Calculate shares
-        let platform_share = (amount * ratios.platform as i128) / 100;
-        let leaderboard_share = (amount * ratios.leaderboard as i128) / 100;
-        let creator_share = amount - platform_share - leaderboard_share; // Remainder to creator to avoid rounding dust
+        let mut total_bps = 0u32;
+        let mut has_dust_bucket = false;
+        for (i, split) in splits.iter().enumerate() {
+            total_bps = total_bps.saturating_add(split.bps);
+            has_dust_bucket |= split.bucket == dust_bucket;
+            if splits
+                .iter()
+                .skip(i + 1)
+                .any(|other| other.bucket == split.bucket)
+            {
+                return Err(TreasuryError::InvalidRatios);
+            }
+        }
+        if total_bps != BPS_DENOMINATOR || !has_dust_bucket {
+            return Err(TreasuryError::InvalidRatios);
+        }
 
-        // Update pools
-        self::update_pool_balance(&env, PLATFORM_FEES_KEY, platform_share);
-        self::update_pool_balance(&env, LEADERBOARD_FEES_KEY, leaderboard_share);
-        self::update_pool_balance(&env, CREATOR_FEES_KEY, creator_share);
-        self::update_pool_balance(&env, TOTAL_FEES_KEY, amount);
+        let mut buckets = Self::get_buckets(env.clone());
+        for split in splits.iter() {
+            if FeePool::from_bucket(&env, &split.bucket).is_none()
+                && !buckets.contains(&split.bucket)
+            {
+                buckets.push_back(split.bucket);
+            }
+        }
+        env.storage().instance().set(&DataKey::Buckets, &buckets);
+        env.storage().instance().set(
+            &DataKey::FeeSplits,
+            &FeeSplitConfig {
+                splits: splits.clone(),
+                dust_bucket: dust_bucket.clone(),
+            },
+        );
+        extend_instance(&env);
 
-        // Emit FeeCollected(source, amount, timestamp)
-        FeeCollectedEvent {
-            source,
-            amount,
-            timestamp: env.ledger().timestamp(),
+        FeeSplitsUpdatedEvent {
+            splits,
+            dust_bucket,
         }
         .publish(&env);
+
+        Ok(())
     }
 
-    /// Get platform fees collected
-    pub fn get_platform_fees(env: Env) -> i128 {
+    /// Get the fee split applied by `deposit_fees`
+    pub fn get_fee_splits(env: Env) -> Result<FeeSplitConfig, TreasuryError> {
         env.storage()
-            .persistent()
-            .get(&Symbol::new(&env, PLATFORM_FEES_KEY))
-            .unwrap_or(0)
+            .instance()
+            .get(&DataKey::FeeSplits)
+            .ok_or(TreasuryError::NotInitialized)
     }
 
-    /// Get leaderboard fees collected
-    pub fn get_leaderboard_fees(env: Env) -> i128 {
+    /// Get every custom bucket that has been part of a fee split
+    pub fn get_buckets(env: Env) -> Vec<Symbol> {
         env.storage()
-            .persistent()
-            .get(&Symbol::new(&env, LEADERBOARD_FEES_KEY))
-            .unwrap_or(0)
+            .instance()
+            .get(&DataKey::Buckets)
+            .unwrap_or(Vec::new(&env))
     }
 
-    /// Get creator fees collected
-    pub fn get_creator_fees(env: Env) -> i128 {
+    /// Get a bucket's balance in a token
+    ///
+    /// For `creator` this is the unattributed pool; see `get_creator_owed`.
+    pub fn get_bucket_balance(env: Env, bucket: Symbol, token: Address) -> i128 {
         env.storage()
-            .persistent()
-            .get(&Symbol::new(&env, CREATOR_FEES_KEY))
+            .instance()
+            .get(&bucket_key(&env, &bucket, &token))
             .unwrap_or(0)
     }
 
-    /// Get total fees collected
-    pub fn get_total_fees(env: Env) -> i128 {
+    /// Admin: Set the fee kinds a market or AMM contract may deposit
+    ///
+    /// An empty list stops the source depositing. The factory set at
+    /// initialization may always deposit `Creation` fees, and only those.
+    pub fn set_fee_source(
+        env: Env,
+        source: Address,
+        kinds: Vec<FeeKind>,
+    ) -> Result<(), TreasuryError> {
+        require_admin(&env)?;
+
+        let key = DataKey::FeeSource(source.clone());
+        if kinds.is_empty() {
+            env.storage().persistent().remove(&key);
+        } else {
+            env.storage().persistent().set(&key, &kinds);
+            extend_persistent(&env, &key);
+        }
+        extend_instance(&env);
+
+        FeeSourceUpdatedEvent { source, kinds }.publish(&env);
+
+        Ok(())
+    }
+
+    /// Get the fee kinds an address may pass to `deposit_fees`
+    pub fn get_fee_source_kinds(env: Env, source: Address) -> Vec<FeeKind> {
+        let factory: Option<Address> = env.storage().instance().get(&DataKey::Factory);
+        if factory == Some(source.clone()) {
+            return vec![&env, FeeKind::Creation];
+        }
         env.storage()
             .persistent()
-            .get(&Symbol::new(&env, TOTAL_FEES_KEY))
-            .unwrap_or(0)
+            .get(&DataKey::FeeSource(source))
+            .unwrap_or(Vec::new(&env))
     }
 
-    /// Distribute rewards to leaderboard winners
-    pub fn distribute_leaderboard_rewards(_env: Env) {
-        todo!("Leaderboard distribution logic not yet implemented")
+    /// Check whether an address may call `deposit_fees` at all
+    pub fn is_fee_source(env: Env, source: Address) -> bool {
+        !Self::get_fee_source_kinds(env, source).is_empty()
     }
 
-    /// Distribute rewards to creators
-    pub fn distribute_creator_rewards(
+    /// Admin: Allow or stop a market or AMM contract drawing on the insurance fund
+    pub fn set_insured_contract(
         env: Env,
-        admin: Address,
-        distributions: soroban_sdk::Vec<(Address, i128)>,
-    ) {
-        admin.require_auth();
-
-        let stored_admin: Address = env
-            .storage()
-            .persistent()
-            .get(&Symbol::new(&env, ADMIN_KEY))
-            .expect("Admin not set");
+        contract: Address,
+        allowed: bool,
+    ) -> Result<(), TreasuryError> {
+        require_admin(&env)?;
 
-        if admin != stored_admin {
-            panic!("Unauthorized: only admin can distribute rewards");
+        let key = DataKey::InsuredContract(contract.clone());
+        if allowed {
+            env.storage().persistent().set(&key, &true);
+            extend_persistent(&env, &key);
+        } else {
+            env.storage().persistent().remove(&key);
         }
+        extend_instance(&env);
 
-        let creator_fees: i128 = env
-            .storage()
-            .persistent()
-            .get(&Symbol::new(&env, CREATOR_FEES_KEY))
-            .unwrap_or(0);
-
-        let mut total_amount = 0i128;
-        for dist in distributions.iter() {
-            total_amount += dist.1;
-        }
+        InsuredContractUpdatedEvent { contract, allowed }.publish(&env);
 
-        if total_amount > creator_fees {
-            panic!("Insufficient balance in creator pool");
-        }
+        Ok(())
+    }
 
-        let usdc_token: Address = env
-            .storage()
+    /// Check whether a contract may draw on the insurance fund
+    pub fn is_insured_contract(env: Env, contract: Address) -> bool {
+        env.storage()
             .persistent()
-            .get(&Symbol::new(&env, USDC_KEY))
-            .expect("USDC token not set");
+            .get(&DataKey::InsuredContract(contract))
+            .unwrap_or(false)
+    }
 
-        let token_client = token::Client::new(&env, &usdc_token);
-        let contract_address = env.current_contract_address();
+    /// Get the insurance fund balance in a token
+    pub fn get_insurance_fund(env: Env, token: Address) -> i128 {
+        Self::get_bucket_balance(env, INSURANCE_BUCKET, token)
+    }
 
-        for dist in distributions.iter() {
-            let (creator, amount) = dist;
-            token_client.transfer(&contract_address, &creator, &amount);
+    /// Registered market or AMM: Pay a shortfall from the insurance fund
+    ///
+    /// The draw is recorded with its reason. Returns the draw id.
+    pub fn cover_shortfall(
+        env: Env,
+        market: Address,
+        token: Address,
+        amount: i128,
+        reason: Symbol,
+    ) -> Result<u32, TreasuryError> {
+        market.require_auth();
+
+        if !Self::is_insured_contract(env.clone(), market.clone()) {
+            return Err(TreasuryError::NotInsured);
+        }
+        if amount <= 0 {
+            return Err(TreasuryError::InvalidAmount);
+        }
+        if amount > Self::get_insurance_fund(env.clone(), token.clone()) {
+            return Err(TreasuryError::InsufficientBalance);
+        }
+
+        let draw_id: u32 = env
+            .storage()
+            .instance()
+            .get(&DataKey::ShortfallDrawCount)
+            .unwrap_or(0);
+        env.storage()
+            .instance()
+            .set(&DataKey::ShortfallDrawCount, &(draw_id + 1));
+        self::update_pool_balance(
+            &env,
+            DataKey::TokenBucket(INSURANCE_BUCKET, token.clone()),
+            -amount,
+        );
+
+        let draw_key = DataKey::ShortfallDraw(draw_id);
+        env.storage().persistent().set(
+            &draw_key,
+            &ShortfallDraw {
+                market: market.clone(),
+                token: token.clone(),
+                amount,
+                reason: reason.clone(),
+                timestamp: env.ledger().timestamp(),
+            },
+        );
+        transfer_out(&env, &token, &market, amount);
+        extend_instance(&env);
+        extend_persistent(&env, &draw_key);
+
+        ShortfallCoveredEvent {
+            draw_id,
+            market,
+            token,
+            amount,
+            reason,
         }
+        .publish(&env);
+
+        Ok(draw_id)
+    }
 
-        let new_balance = creator_fees - total_amount;
+    /// Get a recorded insurance draw
+    pub fn get_shortfall_draw(env: Env, draw_id: u32) -> Option<ShortfallDraw> {
         env.storage()
             .persistent()
-            .set(&Symbol::new(&env, CREATOR_FEES_KEY), &new_balance);
+            .get(&DataKey::ShortfallDraw(draw_id))
+    }
 
-        CreatorRewardsEvent {
-            total_amount,
-            count: distributions.len(),
+    /// Get the number of insurance draws
+    pub fn get_shortfall_draw_count(env: Env) -> u32 {
+        env.storage()
+            .instance()
+            .get(&DataKey::ShortfallDrawCount)
+            .unwrap_or(0)
+    }
+
+    /// Deposit fees in an accepted token and split across that token's buckets
+    ///
+    /// Only the factory and registered fee sources may deposit, and only the
+    /// kinds they are registered for. Totals are kept per token and per `kind`.
+    ///
+    /// With a `creator`, the creator share is credited to that creator for
+    /// `claim_creator_rewards`; otherwise it joins the pool paid out by
+    /// `distribute_creator_rewards`.
+    pub fn deposit_fees(
+        env: Env,
+        source: Address,
+        token: Address,
+        kind: FeeKind,
+        creator: Option<Address>,
+        amount: i128,
+    ) -> Result<(), TreasuryError> {
+        source.require_auth();
+        let kinds = Self::get_fee_source_kinds(env.clone(), source.clone());
+        if kinds.is_empty() {
+            return Err(TreasuryError::UnregisteredSource);
+        }
+        if !kinds.contains(kind) {
+            return Err(TreasuryError::KindNotAllowed);
+        }
+        // Validate amount > 0
+        if amount <= 0 {
+            return Err(TreasuryError::InvalidAmount);
+        }
+        if !Self::get_tokens(env.clone()).contains(&token) {
+            return Err(TreasuryError::UnsupportedToken);
+        }
+
+        let token_client = token::Client::new(&env, &token);
+        let treasury_address = env.current_contract_address();
+
+        // Transfer the fee from source to treasury
+        // The source must have authorized the treasury to pull funds
+        token_client.transfer(&source, &treasury_address, &amount);
+
+        // Credit each bucket its share, and rounding dust to the dust bucket
+        let config = Self::get_fee_splits(env.clone())?;
+        let mut distributed = 0i128;
+        for split in config.splits.iter() {
+            let share = amount * split.bps as i128 / BPS_DENOMINATOR as i128;
+            distributed += share;
+            credit_bucket(&env, &split.bucket, &token, &creator, share);
+        }
+        credit_bucket(
+            &env,
+            &config.dust_bucket,
+            &token,
+            &creator,
+            amount - distributed,
+        );
+        self::update_pool_balance(&env, DataKey::TokenTotalFees(token.clone()), amount);
+        self::update_pool_balance(&env, DataKey::TokenKindFees(kind, token.clone()), amount);
+        extend_instance(&env);
+
+        // Emit FeeCollected(source, token, kind, creator, amount, timestamp)
+        FeeCollectedEvent {
+            source,
+            token,
+            kind,
+            creator,
+            amount,
+            timestamp: env.ledger().timestamp(),
         }
         .publish(&env);
+
+        Ok(())
+    }
+
+    /// Get platform fees collected in a token
+    pub fn get_platform_fees(env: Env, token: Address) -> i128 {
+        pool_balance(&env, FeePool::Platform, &token)
     }
 
-    /// Get treasury balance (total USDC held)
-    pub fn get_treasury_balance(env: Env) -> i128 {
-        let usdc_token: Address = env
+    /// Get leaderboard fees collected in a token
+    pub fn get_leaderboard_fees(env: Env, token: Address) -> i128 {
+        pool_balance(&env, FeePool::Leaderboard, &token)
+    }
+
+    /// Get creator fees collected in a token without a creator attribution
+    pub fn get_creator_fees(env: Env, token: Address) -> i128 {
+        pool_balance(&env, FeePool::Creator, &token)
+    }
+
+    /// Get creator fees in a token attributed to creators and not yet claimed
+    pub fn get_creator_owed(env: Env, token: Address) -> i128 {
+        env.storage()
+            .instance()
+            .get(&DataKey::TokenCreatorOwed(token))
+            .unwrap_or(0)
+    }
+
+    /// Get a creator's lifetime and unclaimed fee earnings in a token
+    pub fn get_creator_earnings(env: Env, creator: Address, token: Address) -> CreatorEarnings {
+        env.storage()
+            .persistent()
+            .get(&DataKey::TokenCreatorEarnings(creator, token))
+            .unwrap_or_default()
+    }
+
+    /// Withdraw all fees in a token attributed to the calling creator
+    pub fn claim_creator_rewards(
+        env: Env,
+        creator: Address,
+        token: Address,
+    ) -> Result<i128, TreasuryError> {
+        creator.require_auth();
+
+        let mut earnings = Self::get_creator_earnings(env.clone(), creator.clone(), token.clone());
+        let amount = earnings.unclaimed;
+        if amount <= 0 {
+            return Err(TreasuryError::NothingToClaim);
+        }
+
+        earnings.unclaimed = 0;
+        set_creator_earnings(&env, &creator, &token, &earnings);
+        self::update_pool_balance(&env, DataKey::TokenCreatorOwed(token.clone()), -amount);
+        transfer_out(&env, &token, &creator, amount);
+        extend_instance(&env);
+
+        CreatorRewardsClaimedEvent {
+            creator,
+            token,
+            amount,
+        }
+        .publish(&env);
+
+        Ok(amount)
+    }
+
+    /// Get total fees collected in a token
+    pub fn get_total_fees(env: Env, token: Address) -> i128 {
+        env.storage()
+            .instance()
+            .get(&DataKey::TokenTotalFees(token))
+            .unwrap_or(0)
+    }
+
+    /// Get total fees collected in a token from deposits of one kind
+    pub fn get_fees_by_kind(env: Env, kind: FeeKind, token: Address) -> i128 {
+        env.storage()
+            .instance()
+            .get(&DataKey::TokenKindFees(kind, token))
+            .unwrap_or(0)
+    }
+
+    /// Admin: Publish a leaderboard epoch, funded from a token's leaderboard pool
+    ///
+    /// Winners are committed to as a Merkle root over
+    /// `leaderboard_leaf(user, amount)` and claim with
+    /// `claim_leaderboard_reward`. Pairs are hashed in sorted order, so proofs
+    /// need no left/right flags. Returns the new epoch number.
+    pub fn distribute_leaderboard_rewards(
+        env: Env,
+        token: Address,
+        merkle_root: BytesN<32>,
+        total: i128,
+    ) -> Result<u32, TreasuryError> {
+        require_admin(&env)?;
+
+        if total <= 0 {
+            return Err(TreasuryError::InvalidAmount);
+        }
+        if total > Self::get_leaderboard_fees(env.clone(), token.clone()) {
+            return Err(TreasuryError::InsufficientBalance);
+        }
+
+        let epoch: u32 = env
             .storage()
+            .instance()
+            .get(&DataKey::LeaderboardEpochCount)
+            .unwrap_or(0);
+        env.storage()
+            .instance()
+            .set(&DataKey::LeaderboardEpochCount, &(epoch + 1));
+        self::update_pool_balance(&env, FeePool::Leaderboard.key(&token), -total);
+        self::update_pool_balance(&env, DataKey::TokenReserved(token.clone()), total);
+
+        let epoch_key = DataKey::LeaderboardEpoch(epoch);
+        env.storage().persistent().set(
+            &epoch_key,
+            &LeaderboardEpoch {
+                token: token.clone(),
+                merkle_root: merkle_root.clone(),
+                total,
+                claimed: 0,
+                published_at: env.ledger().timestamp(),
+            },
+        );
+        extend_instance(&env);
+        extend_persistent(&env, &epoch_key);
+
+        LeaderboardPublishedEvent {
+            epoch,
+            token,
+            merkle_root,
+            total,
+        }
+        .publish(&env);
+
+        Ok(epoch)
+    }
+
+    /// Claim a leaderboard reward with a Merkle proof of `(user, amount)`
+    pub fn claim_leaderboard_reward(
+        env: Env,
+        user: Address,
+        epoch: u32,
+        amount: i128,
+        proof: Vec<BytesN<32>>,
+    ) -> Result<(), TreasuryError> {
+        user.require_auth();
+
+        let epoch_key = DataKey::LeaderboardEpoch(epoch);
+        let mut record: LeaderboardEpoch = env
+            .storage()
+            .persistent()
+            .get(&epoch_key)
+            .ok_or(TreasuryError::EpochNotFound)?;
+        let claimed_key = DataKey::LeaderboardClaimed(epoch, user.clone());
+        if env.storage().persistent().has(&claimed_key) {
+            return Err(TreasuryError::AlreadyClaimed);
+        }
+        if amount <= 0 {
+            return Err(TreasuryError::InvalidAmount);
+        }
+
+        let mut node = Self::leaderboard_leaf(env.clone(), user.clone(), amount);
+        for sibling in proof.iter() {
+            node = hash_pair(&env, &node, &sibling);
+        }
+        if node != record.merkle_root {
+            return Err(TreasuryError::InvalidProof);
+        }
+
+        // A root promising more than was funded cannot drain other pools
+        if record.claimed + amount > record.total {
+            return Err(TreasuryError::InsufficientBalance);
+        }
+        record.claimed += amount;
+        self::update_pool_balance(&env, DataKey::TokenReserved(record.token.clone()), -amount);
+        env.storage().persistent().set(&epoch_key, &record);
+        env.storage().persistent().set(&claimed_key, &true);
+        transfer_out(&env, &record.token, &user, amount);
+
+        extend_instance(&env);
+        extend_persistent(&env, &epoch_key);
+        extend_persistent(&env, &claimed_key);
+
+        LeaderboardRewardClaimedEvent {
+            epoch,
+            user,
+            amount,
+        }
+        .publish(&env);
+
+        Ok(())
+    }
+
+    /// Merkle leaf for a leaderboard reward: sha256(user xdr || amount)
+    ///
+    /// `amount` is encoded as 16 big-endian bytes.
+    pub fn leaderboard_leaf(env: Env, user: Address, amount: i128) -> BytesN<32> {
+        let mut input = Bytes::new(&env);
+        input.append(&user.to_xdr(&env));
+        input.extend_from_array(&amount.to_be_bytes());
+        env.crypto().sha256(&input).into()
+    }
+
+    /// Get a published leaderboard epoch
+    pub fn get_leaderboard_epoch(env: Env, epoch: u32) -> Option<LeaderboardEpoch> {
+        env.storage()
             .persistent()
-            .get(&Symbol::new(&env, USDC_KEY))
-            .expect("USDC not set");
-        let token_client = token::Client::new(&env, &usdc_token);
-        token_client.balance(&env.current_contract_address())
+            .get(&DataKey::LeaderboardEpoch(epoch))
     }
 
-    /// Emergency withdrawal of funds
-    pub fn emergency_withdraw(env: Env, admin: Address, recipient: Address, amount: i128) {
+    /// Check whether a user has claimed their reward for an epoch
+    pub fn has_claimed_leaderboard(env: Env, epoch: u32, user: Address) -> bool {
+        env.storage()
+            .persistent()
+            .has(&DataKey::LeaderboardClaimed(epoch, user))
+    }
+
+    /// Distribute unattributed creator fees in a token to creators
+    pub fn distribute_creator_rewards(
+        env: Env,
+        admin: Address,
+        token: Address,
+        distributions: soroban_sdk::Vec<(Address, i128)>,
+    ) -> Result<(), TreasuryError> {
         admin.require_auth();
+
         let stored_admin: Address = env
             .storage()
-            .persistent()
-            .get(&Symbol::new(&env, ADMIN_KEY))
-            .expect("Not initialized");
+            .instance()
+            .get(&DataKey::Admin)
+            .ok_or(TreasuryError::NotInitialized)?;
+
         if admin != stored_admin {
-            panic!("Unauthorized");
+            return Err(TreasuryError::Unauthorized);
         }
 
-        let usdc_token: Address = env
-            .storage()
+        let creator_fees = pool_balance(&env, FeePool::Creator, &token);
+
+        let mut total_amount = 0i128;
+        for dist in distributions.iter() {
+            if dist.1 <= 0 {
+                return Err(TreasuryError::InvalidAmount);
+            }
+            total_amount += dist.1;
+        }
+
+        if total_amount > creator_fees {
+            return Err(TreasuryError::InsufficientBalance);
+        }
+
+        let token_client = token::Client::new(&env, &token);
+        let contract_address = env.current_contract_address();
+
+        for dist in distributions.iter() {
+            let (creator, amount) = dist;
+            token_client.transfer(&contract_address, &creator, &amount);
+        }
+
+        self::update_pool_balance(&env, FeePool::Creator.key(&token), -total_amount);
+        extend_instance(&env);
+
+        CreatorRewardsEvent {
+            token,
+            total_amount,
+            count: distributions.len(),
+        }
+        .publish(&env);
+
+        Ok(())
+    }
+
+    /// Get the treasury's holdings of every accepted token
+    pub fn get_treasury_balance(env: Env) -> Result<Vec<(Address, i128)>, TreasuryError> {
+        let tokens = Self::get_tokens(env.clone());
+        if tokens.is_empty() {
+            return Err(TreasuryError::NotInitialized);
+        }
+
+        let contract_address = env.current_contract_address();
+        let mut balances = Vec::new(&env);
+        for token in tokens.iter() {
+            let balance = token::Client::new(&env, &token).balance(&contract_address);
+            balances.push_back((token, balance));
+        }
+        Ok(balances)
+    }
+
+    /// Compare tracked obligations with actual holdings for every token
+    ///
+    /// Tokens sent to the treasury outside `deposit_fees` show up as surplus.
+    pub fn reconcile(env: Env) -> Vec<Reconciliation> {
+        let mut report = Vec::new(&env);
+        for token in Self::get_tokens(env.clone()).iter() {
+            report.push_back(reconcile_token(&env, &token));
+        }
+        report
+    }
+
+    /// Admin: Credit untracked tokens to a pool
+    pub fn credit_surplus(
+        env: Env,
+        token: Address,
+        pool: FeePool,
+        amount: i128,
+    ) -> Result<(), TreasuryError> {
+        require_admin(&env)?;
+
+        if amount <= 0 {
+            return Err(TreasuryError::InvalidAmount);
+        }
+        if !Self::get_tokens(env.clone()).contains(&token) {
+            return Err(TreasuryError::UnsupportedToken);
+        }
+        if amount > reconcile_token(&env, &token).surplus {
+            return Err(TreasuryError::InsufficientSurplus);
+        }
+
+        self::update_pool_balance(&env, pool.key(&token), amount);
+        extend_instance(&env);
+
+        SurplusCreditedEvent {
+            token,
+            pool,
+            amount,
+        }
+        .publish(&env);
+
+        Ok(())
+    }
+
+    /// Queue a withdrawal from a token's platform fee pool
+    ///
+    /// Shorthand for `emergency_withdraw` on the `platform` bucket, so the
+    /// same signers, delay and period cap apply. Returns the withdrawal id.
+    pub fn withdraw_platform_fees(
+        env: Env,
+        proposer: Address,
+        token: Address,
+        recipient: Address,
+        amount: i128,
+    ) -> Result<u32, TreasuryError> {
+        let bucket = FeePool::Platform.bucket(&env);
+        Self::emergency_withdraw(env, proposer, token, bucket, recipient, amount)
+    }
+
+    /// Admin: Create a budget in a token or change its per-epoch limit
+    ///
+    /// A changed epoch length takes effect when the current epoch ends.
+    pub fn set_budget(
+        env: Env,
+        name: Symbol,
+        token: Address,
+        epoch_limit: i128,
+        epoch_length: u64,
+    ) -> Result<(), TreasuryError> {
+        require_admin(&env)?;
+
+        if epoch_limit < 0 || epoch_length == 0 {
+            return Err(TreasuryError::InvalidBudget);
+        }
+
+        let budget = match Self::get_budget(env.clone(), name.clone(), token.clone()) {
+            Some(budget) => Budget {
+                epoch_limit,
+                epoch_length,
+                ..budget
+            },
+            None => Budget {
+                balance: 0,
+                epoch_limit,
+                epoch_length,
+                epoch_start: env.ledger().timestamp(),
+                spent_in_epoch: 0,
+            },
+        };
+        set_budget(&env, &name, &token, &budget);
+        extend_instance(&env);
+
+        BudgetUpdatedEvent {
+            budget: name,
+            token,
+            epoch_limit,
+            epoch_length,
+        }
+        .publish(&env);
+
+        Ok(())
+    }
+
+    /// Admin: Move funds from a token's platform pool into its budget
+    pub fn allocate_budget(
+        env: Env,
+        name: Symbol,
+        token: Address,
+        amount: i128,
+    ) -> Result<(), TreasuryError> {
+        require_admin(&env)?;
+
+        if amount <= 0 {
+            return Err(TreasuryError::InvalidAmount);
+        }
+        if amount > Self::get_platform_fees(env.clone(), token.clone()) {
+            return Err(TreasuryError::InsufficientBalance);
+        }
+        let mut budget = Self::get_budget(env.clone(), name.clone(), token.clone())
+            .ok_or(TreasuryError::BudgetNotFound)?;
+
+        self::update_pool_balance(&env, FeePool::Platform.key(&token), -amount);
+        budget.balance += amount;
+        self::update_pool_balance(&env, DataKey::TokenReserved(token.clone()), amount);
+        set_budget(&env, &name, &token, &budget);
+        extend_instance(&env);
+
+        BudgetAllocatedEvent {
+            budget: name,
+            token,
+            amount,
+        }
+        .publish(&env);
+
+        Ok(())
+    }
+
+    /// Admin: Grant or revoke a spender's right to draw from a budget
+    ///
+    /// Applies to the budget's balances in every token.
+    pub fn set_budget_spender(
+        env: Env,
+        name: Symbol,
+        spender: Address,
+        allowed: bool,
+    ) -> Result<(), TreasuryError> {
+        require_admin(&env)?;
+
+        let spender_key = DataKey::BudgetSpender(name.clone(), spender.clone());
+        if allowed {
+            env.storage().persistent().set(&spender_key, &true);
+            extend_persistent(&env, &spender_key);
+        } else {
+            env.storage().persistent().remove(&spender_key);
+        }
+        extend_instance(&env);
+
+        BudgetSpenderUpdatedEvent {
+            budget: name,
+            spender,
+            allowed,
+        }
+        .publish(&env);
+
+        Ok(())
+    }
+
+    /// Spender: Pay out of a budget, within its per-epoch limit
+    pub fn spend_budget(
+        env: Env,
+        spender: Address,
+        name: Symbol,
+        token: Address,
+        recipient: Address,
+        amount: i128,
+    ) -> Result<(), TreasuryError> {
+        spender.require_auth();
+
+        if !Self::is_budget_spender(env.clone(), name.clone(), spender.clone()) {
+            return Err(TreasuryError::NotBudgetSpender);
+        }
+        if amount <= 0 {
+            return Err(TreasuryError::InvalidAmount);
+        }
+        let mut budget = Self::get_budget(env.clone(), name.clone(), token.clone())
+            .ok_or(TreasuryError::BudgetNotFound)?;
+
+        let current_time = env.ledger().timestamp();
+        if current_time >= budget.epoch_start + budget.epoch_length {
+            budget.epoch_start = current_time;
+            budget.spent_in_epoch = 0;
+        }
+        if budget.spent_in_epoch + amount > budget.epoch_limit {
+            return Err(TreasuryError::BudgetLimitExceeded);
+        }
+        if amount > budget.balance {
+            return Err(TreasuryError::InsufficientBalance);
+        }
+
+        budget.balance -= amount;
+        self::update_pool_balance(&env, DataKey::TokenReserved(token.clone()), -amount);
+        budget.spent_in_epoch += amount;
+        set_budget(&env, &name, &token, &budget);
+        transfer_out(&env, &token, &recipient, amount);
+        extend_instance(&env);
+
+        BudgetDrawnEvent {
+            budget: name,
+            token,
+            spender,
+            recipient,
+            amount,
+        }
+        .publish(&env);
+
+        Ok(())
+    }
+
+    /// Get a budget's state in a token
+    pub fn get_budget(env: Env, name: Symbol, token: Address) -> Option<Budget> {
+        env.storage()
             .persistent()
-            .get(&Symbol::new(&env, USDC_KEY))
-            .expect("USDC not set");
-        let token_client = token::Client::new(&env, &usdc_token);
-        token_client.transfer(&env.current_contract_address(), &recipient, &amount);
+            .get(&DataKey::TokenBudget(name, token))
+    }
 
-        EmergencyWithdrawalEvent {
-            admin,
+    /// Check whether an address may draw from a budget
+    pub fn is_budget_spender(env: Env, name: Symbol, spender: Address) -> bool {
+        env.storage()
+            .persistent()
+            .get(&DataKey::BudgetSpender(name, spender))
+            .unwrap_or(false)
+    }
+
+    /// Admin: Fund a vesting stream from one token's pool
+    ///
+    /// The recipient collects vested amounts with `withdraw_from_stream`.
+    /// Streams cannot start in the past, so nothing is vested on creation.
+    /// Returns the stream id.
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_stream(
+        env: Env,
+        recipient: Address,
+        token: Address,
+        pool: FeePool,
+        total: i128,
+        start: u64,
+        cliff: u64,
+        end: u64,
+    ) -> Result<u32, TreasuryError> {
+        require_admin(&env)?;
+
+        if total <= 0
+            || start < env.ledger().timestamp()
+            || start >= end
+            || cliff < start
+            || cliff > end
+        {
+            return Err(TreasuryError::InvalidStream);
+        }
+        if total > pool_balance(&env, pool, &token) {
+            return Err(TreasuryError::InsufficientBalance);
+        }
+
+        let stream_id: u32 = env
+            .storage()
+            .instance()
+            .get(&DataKey::StreamCount)
+            .unwrap_or(0);
+        env.storage()
+            .instance()
+            .set(&DataKey::StreamCount, &(stream_id + 1));
+        self::update_pool_balance(&env, pool.key(&token), -total);
+        self::update_pool_balance(&env, DataKey::TokenReserved(token.clone()), total);
+
+        let stream_key = DataKey::Stream(stream_id);
+        env.storage().persistent().set(
+            &stream_key,
+            &Stream {
+                recipient: recipient.clone(),
+                token: token.clone(),
+                pool,
+                total,
+                withdrawn: 0,
+                start,
+                cliff,
+                end,
+                revoked_at: None,
+            },
+        );
+        extend_instance(&env);
+        extend_persistent(&env, &stream_key);
+
+        StreamCreatedEvent {
+            stream_id,
+            recipient,
+            token,
+            pool,
+            total,
+            start,
+            cliff,
+            end,
+        }
+        .publish(&env);
+
+        Ok(stream_id)
+    }
+
+    /// Collect everything vested and not yet withdrawn from a stream
+    pub fn withdraw_from_stream(
+        env: Env,
+        recipient: Address,
+        stream_id: u32,
+    ) -> Result<i128, TreasuryError> {
+        recipient.require_auth();
+
+        let mut stream =
+            Self::get_stream(env.clone(), stream_id).ok_or(TreasuryError::StreamNotFound)?;
+        if stream.recipient != recipient {
+            return Err(TreasuryError::Unauthorized);
+        }
+        let amount = stream.vested(env.ledger().timestamp()) - stream.withdrawn;
+        if amount <= 0 {
+            return Err(TreasuryError::NothingToClaim);
+        }
+
+        stream.withdrawn += amount;
+        self::update_pool_balance(&env, DataKey::TokenReserved(stream.token.clone()), -amount);
+        let stream_key = DataKey::Stream(stream_id);
+        env.storage().persistent().set(&stream_key, &stream);
+        transfer_out(&env, &stream.token, &recipient, amount);
+        extend_instance(&env);
+        extend_persistent(&env, &stream_key);
+
+        StreamWithdrawnEvent {
+            stream_id,
             recipient,
             amount,
-            timestamp: env.ledger().timestamp(),
         }
         .publish(&env);
+
+        Ok(amount)
+    }
+
+    /// Admin: Stop a stream and return its unvested remainder to its pool
+    ///
+    /// Amounts already vested stay withdrawable. Returns the amount returned.
+    pub fn revoke_stream(env: Env, stream_id: u32) -> Result<i128, TreasuryError> {
+        require_admin(&env)?;
+
+        let mut stream =
+            Self::get_stream(env.clone(), stream_id).ok_or(TreasuryError::StreamNotFound)?;
+        if stream.revoked_at.is_some() {
+            return Err(TreasuryError::StreamRevoked);
+        }
+
+        let now = env.ledger().timestamp();
+        let returned = stream.total - stream.vested(now);
+        stream.revoked_at = Some(now);
+        self::update_pool_balance(&env, stream.pool.key(&stream.token), returned);
+        self::update_pool_balance(
+            &env,
+            DataKey::TokenReserved(stream.token.clone()),
+            -returned,
+        );
+
+        let stream_key = DataKey::Stream(stream_id);
+        env.storage().persistent().set(&stream_key, &stream);
+        extend_instance(&env);
+        extend_persistent(&env, &stream_key);
+
+        StreamRevokedEvent {
+            stream_id,
+            returned,
+        }
+        .publish(&env);
+
+        Ok(returned)
+    }
+
+    /// Get a vesting stream
+    pub fn get_stream(env: Env, stream_id: u32) -> Option<Stream> {
+        env.storage().persistent().get(&DataKey::Stream(stream_id))
+    }
+
+    /// Get the amount a stream's recipient can withdraw now
+    pub fn get_stream_withdrawable(env: Env, stream_id: u32) -> Result<i128, TreasuryError> {
+        let stream =
+            Self::get_stream(env.clone(), stream_id).ok_or(TreasuryError::StreamNotFound)?;
+        Ok(stream.vested(env.ledger().timestamp()) - stream.withdrawn)
+    }
+
+    /// Multisig: Replace the emergency withdrawal policy
+    pub fn set_withdrawal_policy(
+        env: Env,
+        approvers: Vec<Address>,
+        policy: WithdrawalPolicy,
+    ) -> Result<(), TreasuryError> {
+        require_signer_approval(&env, &approvers)?;
+
+        if policy.required == 0
+            || policy.required > policy.signers.len()
+            || has_duplicates(&policy.signers)
+            || policy.period == 0
+            || policy.period_cap < 0
+        {
+            return Err(TreasuryError::InvalidPolicy);
+        }
+
+        env.storage()
+            .instance()
+            .set(&DataKey::WithdrawalPolicy, &policy);
+        extend_instance(&env);
+
+        WithdrawalPolicyUpdatedEvent { policy }.publish(&env);
+
+        Ok(())
+    }
+
+    /// Get the emergency withdrawal policy
+    pub fn get_withdrawal_policy(env: Env) -> Result<WithdrawalPolicy, TreasuryError> {
+        get_withdrawal_policy(&env)
+    }
+
+    /// Queue an emergency withdrawal from one token's fee pool or bucket
+    ///
+    /// Executable with `execute_withdrawal` once the policy's signers have
+    /// approved it and its delay has passed; any signer can cancel it before.
+    /// Returns the withdrawal id.
+    pub fn emergency_withdraw(
+        env: Env,
+        proposer: Address,
+        token: Address,
+        bucket: Symbol,
+        recipient: Address,
+        amount: i128,
+    ) -> Result<u32, TreasuryError> {
+        proposer.require_auth();
+        let policy = get_withdrawal_policy(&env)?;
+        if !policy.signers.contains(&proposer) {
+            return Err(TreasuryError::InvalidApprover);
+        }
+
+        if bucket == INSURANCE_BUCKET {
+            return Err(TreasuryError::InsuranceLocked);
+        }
+        if FeePool::from_bucket(&env, &bucket).is_none()
+            && !Self::get_buckets(env.clone()).contains(&bucket)
+        {
+            return Err(TreasuryError::BucketNotFound);
+        }
+        if amount <= 0 {
+            return Err(TreasuryError::InvalidAmount);
+        }
+        if amount > Self::get_bucket_balance(env.clone(), bucket.clone(), token.clone()) {
+            return Err(TreasuryError::InsufficientBalance);
+        }
+
+        let withdrawal_id: u32 = env
+            .storage()
+            .instance()
+            .get(&DataKey::WithdrawalCount)
+            .unwrap_or(0);
+        env.storage()
+            .instance()
+            .set(&DataKey::WithdrawalCount, &(withdrawal_id + 1));
+
+        let eta = env.ledger().timestamp() + policy.delay;
+        let mut approvals = Vec::new(&env);
+        approvals.push_back(proposer.clone());
+        let withdrawal_key = DataKey::Withdrawal(withdrawal_id);
+        env.storage().persistent().set(
+            &withdrawal_key,
+            &Withdrawal {
+                token: token.clone(),
+                bucket: bucket.clone(),
+                recipient: recipient.clone(),
+                amount,
+                approvals,
+                eta,
+                status: WithdrawalStatus::Pending,
+            },
+        );
+        extend_instance(&env);
+        extend_persistent(&env, &withdrawal_key);
+
+        WithdrawalQueuedEvent {
+            withdrawal_id,
+            proposer,
+            token,
+            bucket,
+            recipient,
+            amount,
+            eta,
+        }
+        .publish(&env);
+
+        Ok(withdrawal_id)
+    }
+
+    /// Approve a queued emergency withdrawal
+    pub fn approve_withdrawal(
+        env: Env,
+        signer: Address,
+        withdrawal_id: u32,
+    ) -> Result<(), TreasuryError> {
+        signer.require_auth();
+        let policy = get_withdrawal_policy(&env)?;
+        if !policy.signers.contains(&signer) {
+            return Err(TreasuryError::InvalidApprover);
+        }
+
+        let mut withdrawal = load_pending_withdrawal(&env, withdrawal_id)?;
+        if withdrawal.approvals.contains(&signer) {
+            return Err(TreasuryError::AlreadyApproved);
+        }
+        withdrawal.approvals.push_back(signer.clone());
+
+        let withdrawal_key = DataKey::Withdrawal(withdrawal_id);
+        env.storage().persistent().set(&withdrawal_key, &withdrawal);
+        extend_instance(&env);
+        extend_persistent(&env, &withdrawal_key);
+
+        WithdrawalApprovedEvent {
+            withdrawal_id,
+            signer,
+        }
+        .publish(&env);
+
+        Ok(())
+    }
+
+    /// Cancel a queued emergency withdrawal (any signer)
+    pub fn cancel_withdrawal(
+        env: Env,
+        signer: Address,
+        withdrawal_id: u32,
+    ) -> Result<(), TreasuryError> {
+        signer.require_auth();
+        let policy = get_withdrawal_policy(&env)?;
+        if !policy.signers.contains(&signer) {
+            return Err(TreasuryError::InvalidApprover);
+        }
+
+        let mut withdrawal = load_pending_withdrawal(&env, withdrawal_id)?;
+        withdrawal.status = WithdrawalStatus::Cancelled;
+
+        let withdrawal_key = DataKey::Withdrawal(withdrawal_id);
+        env.storage().persistent().set(&withdrawal_key, &withdrawal);
+        extend_instance(&env);
+        extend_persistent(&env, &withdrawal_key);
+
+        WithdrawalCancelledEvent {
+            withdrawal_id,
+            signer,
+        }
+        .publish(&env);
+
+        Ok(())
+    }
+
+    /// Execute an approved emergency withdrawal after its delay
+    ///
+    /// Debits the withdrawal's bucket and counts toward its token's period cap.
+    pub fn execute_withdrawal(env: Env, withdrawal_id: u32) -> Result<(), TreasuryError> {
+        let policy = get_withdrawal_policy(&env)?;
+        let mut withdrawal = load_pending_withdrawal(&env, withdrawal_id)?;
+
+        // Approvals from signers removed since approving no longer count
+        let mut approved = 0u32;
+        for signer in withdrawal.approvals.iter() {
+            if policy.signers.contains(&signer) {
+                approved += 1;
+            }
+        }
+        if approved < policy.required {
+            return Err(TreasuryError::InsufficientApprovers);
+        }
+
+        let current_time = env.ledger().timestamp();
+        if current_time < withdrawal.eta {
+            return Err(TreasuryError::WithdrawalDelayNotElapsed);
+        }
+        if withdrawal.amount
+            > Self::get_bucket_balance(
+                env.clone(),
+                withdrawal.bucket.clone(),
+                withdrawal.token.clone(),
+            )
+        {
+            return Err(TreasuryError::InsufficientBalance);
+        }
+
+        let mut window = Self::get_withdrawal_window(env.clone(), withdrawal.token.clone());
+        if current_time >= window.start + policy.period {
+            window = WithdrawalWindow {
+                start: current_time,
+                withdrawn: 0,
+            };
+        }
+        if window.withdrawn + withdrawal.amount > policy.period_cap {
+            return Err(TreasuryError::PeriodCapExceeded);
+        }
+        window.withdrawn += withdrawal.amount;
+        env.storage().instance().set(
+            &DataKey::TokenWithdrawalWindow(withdrawal.token.clone()),
+            &window,
+        );
+
+        self::update_pool_balance(
+            &env,
+            bucket_key(&env, &withdrawal.bucket, &withdrawal.token),
+            -withdrawal.amount,
+        );
+        withdrawal.status = WithdrawalStatus::Executed;
+        let withdrawal_key = DataKey::Withdrawal(withdrawal_id);
+        env.storage().persistent().set(&withdrawal_key, &withdrawal);
+
+        transfer_out(
+            &env,
+            &withdrawal.token,
+            &withdrawal.recipient,
+            withdrawal.amount,
+        );
+        extend_instance(&env);
+        extend_persistent(&env, &withdrawal_key);
+
+        EmergencyWithdrawalEvent {
+            withdrawal_id,
+            token: withdrawal.token,
+            bucket: withdrawal.bucket,
+            recipient: withdrawal.recipient,
+            amount: withdrawal.amount,
+            timestamp: current_time,
+        }
+        .publish(&env);
+
+        Ok(())
+    }
+
+    /// Get a queued emergency withdrawal
+    pub fn get_withdrawal(env: Env, withdrawal_id: u32) -> Option<Withdrawal> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Withdrawal(withdrawal_id))
+    }
+
+    /// Get the amount of a token withdrawn in the current cap period
+    pub fn get_withdrawal_window(env: Env, token: Address) -> WithdrawalWindow {
+        env.storage()
+            .instance()
+            .get(&DataKey::TokenWithdrawalWindow(token))
+            .unwrap_or_default()
+    }
+
+    /// Get the storage schema version of this contract
+    pub fn get_schema_version(env: Env) -> u32 {
+        upgrade::get_schema_version(&env)
+    }
+
+    /// Admin: Set the upgrade timelock in seconds (0 = upgrades apply immediately)
+    ///
+    /// A reduction only applies once the current delay has passed. Returns
+    /// when the new delay is in force.
+    pub fn set_upgrade_delay(env: Env, delay: u64) -> Result<u64, TreasuryError> {
+        require_admin(&env)?;
+        Ok(upgrade::set_upgrade_delay(&env, delay))
+    }
+
+    /// Admin: Queue a WASM upgrade behind the timelock, returns its eta
+    pub fn propose_upgrade(env: Env, new_wasm_hash: BytesN<32>) -> Result<u64, TreasuryError> {
+        require_admin(&env)?;
+        Ok(upgrade::propose_upgrade(&env, new_wasm_hash))
+    }
+
+    /// Admin: Cancel the queued upgrade during the timelock
+    pub fn cancel_upgrade(env: Env) -> Result<(), TreasuryError> {
+        require_admin(&env)?;
+        upgrade::cancel_upgrade(&env)?;
+        Ok(())
+    }
+
+    /// Get the queued upgrade, if any
+    pub fn get_pending_upgrade(env: Env) -> Option<PendingUpgrade> {
+        upgrade::get_pending_upgrade(&env)
+    }
+
+    /// Admin: Replace the Treasury WASM
+    ///
+    /// The admin may be a multisig account. When a timelock is set the hash
+    /// must match a proposal whose eta has passed.
+    pub fn upgrade(env: Env, new_wasm_hash: BytesN<32>) -> Result<(), TreasuryError> {
+        require_admin(&env)?;
+        upgrade::apply_upgrade(&env, new_wasm_hash)?;
+        Ok(())
+    }
+
+    /// Admin: Migrate storage to this code's schema version after an upgrade
+    pub fn migrate(env: Env) -> Result<(), TreasuryError> {
+        require_admin(&env)?;
+        upgrade::migrate(&env, SCHEMA_VERSION, |_env, _from_version| {})?;
+        Ok(())
     }
 }
 
-fn update_pool_balance(env: &Env, key: &str, delta: i128) {
-    let current: i128 = env
+/// Load the stored admin and require its authorization
+fn require_admin(env: &Env) -> Result<Address, TreasuryError> {
+    let admin: Address = env
         .storage()
-        .persistent()
-        .get(&Symbol::new(env, key))
-        .unwrap_or(0);
+        .instance()
+        .get(&DataKey::Admin)
+        .ok_or(TreasuryError::NotInitialized)?;
+    admin.require_auth();
+    Ok(admin)
+}
+
+fn get_withdrawal_policy(env: &Env) -> Result<WithdrawalPolicy, TreasuryError> {
     env.storage()
+        .instance()
+        .get(&DataKey::WithdrawalPolicy)
+        .ok_or(TreasuryError::NotInitialized)
+}
+
+/// Require authenticated approval from enough distinct withdrawal signers
+fn require_signer_approval(env: &Env, approvers: &Vec<Address>) -> Result<(), TreasuryError> {
+    let policy = get_withdrawal_policy(env)?;
+    if approvers.len() < policy.required {
+        return Err(TreasuryError::InsufficientApprovers);
+    }
+    if has_duplicates(approvers) {
+        return Err(TreasuryError::DuplicateApprovers);
+    }
+    for approver in approvers.iter() {
+        if !policy.signers.contains(&approver) {
+            return Err(TreasuryError::InvalidApprover);
+        }
+        approver.require_auth();
+    }
+    Ok(())
+}
+
+fn has_duplicates(addresses: &Vec<Address>) -> bool {
+    for (i, address) in addresses.iter().enumerate() {
+        for other in addresses.iter().skip(i + 1) {
+            if other == address {
+                return true;
+            }
+        }
+    }
+    false
+}
+
+/// Load a withdrawal that can still be approved, cancelled or executed
+fn load_pending_withdrawal(env: &Env, withdrawal_id: u32) -> Result<Withdrawal, TreasuryError> {
+    let withdrawal: Withdrawal = env
+        .storage()
         .persistent()
-        .set(&Symbol::new(env, key), &(current + delta));
+        .get(&DataKey::Withdrawal(withdrawal_id))
+        .ok_or(TreasuryError::WithdrawalNotFound)?;
+    if withdrawal.status != WithdrawalStatus::Pending {
+        return Err(TreasuryError::WithdrawalNotPending);
+    }
+    Ok(withdrawal)
+}
+
+/// Pay a token out of the treasury
+fn transfer_out(env: &Env, token: &Address, recipient: &Address, amount: i128) {
+    let contract_address = env.current_contract_address();
+    token::Client::new(env, token).transfer(&contract_address, recipient, &amount);
+}
+
+fn pool_balance(env: &Env, pool: FeePool, token: &Address) -> i128 {
+    env.storage().instance().get(&pool.key(token)).unwrap_or(0)
+}
+
+/// Balance key for a built-in pool or custom bucket
+fn bucket_key(env: &Env, bucket: &Symbol, token: &Address) -> DataKey {
+    match FeePool::from_bucket(env, bucket) {
+        Some(pool) => pool.key(token),
+        None => DataKey::TokenBucket(bucket.clone(), token.clone()),
+    }
+}
+
+fn reconcile_token(env: &Env, token: &Address) -> Reconciliation {
+    let instance = env.storage().instance();
+    let tracked = pool_balance(env, FeePool::Platform, token)
+        + pool_balance(env, FeePool::Leaderboard, token)
+        + pool_balance(env, FeePool::Creator, token)
+        + instance
+            .get::<_, i128>(&DataKey::TokenCreatorOwed(token.clone()))
+            .unwrap_or(0)
+        + instance
+            .get::<_, i128>(&DataKey::TokenReserved(token.clone()))
+            .unwrap_or(0)
+        + Treasury::get_buckets(env.clone())
+            .iter()
+            .map(|bucket| {
+                instance
+                    .get::<_, i128>(&DataKey::TokenBucket(bucket, token.clone()))
+                    .unwrap_or(0)
+            })
+            .sum::<i128>();
+    let actual = token::Client::new(env, token).balance(&env.current_contract_address());
+    Reconciliation {
+        token: token.clone(),
+        tracked,
+        actual,
+        surplus: (actual - tracked).max(0),
+        deficit: (tracked - actual).max(0),
+    }
+}
+
+/// Split over the three built-in pools, dust to the creator
+fn percent_splits(env: &Env, ratios: &FeeRatios) -> FeeSplitConfig {
+    let mut splits = Vec::new(env);
+    for (pool, pct) in [
+        (FeePool::Platform, ratios.platform),
+        (FeePool::Leaderboard, ratios.leaderboard),
+        (FeePool::Creator, ratios.creator),
+    ] {
+        splits.push_back(FeeSplit {
+            bucket: pool.bucket(env),
+            bps: pct * 100,
+        });
+    }
+    FeeSplitConfig {
+        splits,
+        dust_bucket: FeePool::Creator.bucket(env),
+    }
+}
+
+/// Default insurance share, the rest split 50/30/20 over the built-in pools,
+/// dust to the creator
+fn default_splits(env: &Env) -> FeeSplitConfig {
+    let remaining = BPS_DENOMINATOR - DEFAULT_INSURANCE_BPS;
+    let mut splits = Vec::new(env);
+    for (bucket, bps) in [
+        (FeePool::Platform.bucket(env), remaining * 50 / 100),
+        (FeePool::Leaderboard.bucket(env), remaining * 30 / 100),
+        (FeePool::Creator.bucket(env), remaining * 20 / 100),
+        (INSURANCE_BUCKET, DEFAULT_INSURANCE_BPS),
+    ] {
+        splits.push_back(FeeSplit { bucket, bps });
+    }
+    FeeSplitConfig {
+        splits,
+        dust_bucket: FeePool::Creator.bucket(env),
+    }
+}
+
+/// Credit a deposit share to its bucket
+///
+/// The creator share is attributed to `creator` when there is one.
+fn credit_bucket(
+    env: &Env,
+    bucket: &Symbol,
+    token: &Address,
+    creator: &Option<Address>,
+    amount: i128,
+) {
+    if amount == 0 {
+        return;
+    }
+    match (FeePool::from_bucket(env, bucket), creator) {
+        (Some(FeePool::Creator), Some(creator)) => {
+            let mut earnings =
+                Treasury::get_creator_earnings(env.clone(), creator.clone(), token.clone());
+            earnings.lifetime += amount;
+            earnings.unclaimed += amount;
+            set_creator_earnings(env, creator, token, &earnings);
+            update_pool_balance(env, DataKey::TokenCreatorOwed(token.clone()), amount);
+        }
+        (Some(pool), _) => update_pool_balance(env, pool.key(token), amount),
+        (None, _) => update_pool_balance(
+            env,
+            DataKey::TokenBucket(bucket.clone(), token.clone()),
+            amount,
+        ),
+    }
+}
+
+fn set_creator_earnings(env: &Env, creator: &Address, token: &Address, earnings: &CreatorEarnings) {
+    let key = DataKey::TokenCreatorEarnings(creator.clone(), token.clone());
+    env.storage().persistent().set(&key, earnings);
+    extend_persistent(env, &key);
+}
+
+fn set_budget(env: &Env, name: &Symbol, token: &Address, budget: &Budget) {
+    let key = DataKey::TokenBudget(name.clone(), token.clone());
+    env.storage().persistent().set(&key, budget);
+    extend_persistent(env, &key);
+}
+
+/// Hash two Merkle nodes in sorted order
+fn hash_pair(env: &Env, a: &BytesN<32>, b: &BytesN<32>) -> BytesN<32> {
+    let (first, second) = if a.to_array() <= b.to_array() {
+        (a, b)
+    } else {
+        (b, a)
+    };
+    let mut input = Bytes::new(env);
+    input.extend_from_array(&first.to_array());
+    input.extend_from_array(&second.to_array());
+    env.crypto().sha256(&input).into()
+}
+
+fn update_pool_balance(env: &Env, key: DataKey, delta: i128) {
+    let current: i128 = env.storage().instance().get(&key).unwrap_or(0);
+    env.storage().instance().set(&key, &(current + delta));
 }
 
 #[cfg(test)]
@@ -404,19 +2249,46 @@ mod tests {
     #[test]
     fn test_initialize() {
         let env = Env::default();
-        let (treasury, _usdc, _admin, _, _factory) = setup_treasury(&env);
+        let (treasury, usdc, _admin, _, _factory) = setup_treasury(&env);
+
+        assert_eq!(
+            treasury.get_tokens(),
+            soroban_sdk::vec![&env, usdc.address.clone()]
+        );
+        assert_eq!(treasury.get_platform_fees(&usdc.address), 0);
+        assert_eq!(treasury.get_leaderboard_fees(&usdc.address), 0);
+        assert_eq!(treasury.get_creator_fees(&usdc.address), 0);
+        assert_eq!(treasury.get_total_fees(&usdc.address), 0);
+    }
+
+    #[test]
+    fn test_set_fee_splits_validation() {
+        let env = Env::default();
+        let (treasury, _, _, _, _) = setup_treasury(&env);
+        let split = |name: &str, bps: u32| FeeSplit {
+            bucket: Symbol::new(&env, name),
+            bps,
+        };
+        let platform = Symbol::new(&env, "platform");
+
+        let splits = soroban_sdk::vec![&env, split("platform", 5_000), split("creator", 4_999)];
+        let result = treasury.try_set_fee_splits(&splits, &platform);
+        assert_eq!(result, Err(Ok(TreasuryError::InvalidRatios)));
+
+        let splits = soroban_sdk::vec![&env, split("platform", 5_000), split("platform", 5_000)];
+        let result = treasury.try_set_fee_splits(&splits, &platform);
+        assert_eq!(result, Err(Ok(TreasuryError::InvalidRatios)));
 
-        assert_eq!(treasury.get_platform_fees(), 0);
-        assert_eq!(treasury.get_leaderboard_fees(), 0);
-        assert_eq!(treasury.get_creator_fees(), 0);
-        assert_eq!(treasury.get_total_fees(), 0);
+        let splits = soroban_sdk::vec![&env, split("creator", 5_000), split("partner", 5_000)];
+        let result = treasury.try_set_fee_splits(&splits, &platform);
+        assert_eq!(result, Err(Ok(TreasuryError::InvalidRatios)));
     }
 
     #[test]
-    #[should_panic(expected = "Ratios must sum to 100")]
     fn test_set_fee_distribution_invalid_sum() {
         let env = Env::default();
         let (treasury, _, _, _, _) = setup_treasury(&env);
-        treasury.set_fee_distribution(&50, &50, &10); // 110%
+        let result = treasury.try_set_fee_distribution(&50, &50, &10);
+        assert_eq!(result, Err(Ok(TreasuryError::InvalidRatios)));
     }
 }
@@ -0,0 +1,265 @@
+// contract/src/upgrade.rs - Shared WASM upgrade and schema migration helpers
+// Each contract gates these behind its own admin before delegating here
+
+use soroban_sdk::{contractevent, contracttype, BytesN, Env};
+
+#[contractevent]
+pub struct UpgradeProposedEvent {
+    pub wasm_hash: BytesN<32>,
+    pub eta: u64,
+}
+
+#[contractevent]
+pub struct UpgradeCancelledEvent {
+    pub wasm_hash: BytesN<32>,
+}
+
+#[contractevent]
+pub struct ContractUpgradedEvent {
+    pub wasm_hash: BytesN<32>,
+    pub schema_version: u32,
+    pub timestamp: u64,
+}
+
+#[contractevent]
+pub struct UpgradeDelayUpdatedEvent {
+    pub old_delay: u64,
+    pub new_delay: u64,
+}
+
+#[contractevent]
+pub struct UpgradeDelayProposedEvent {
+    pub new_delay: u64,
+    pub eta: u64,
+}
+
+#[contractevent]
+pub struct SchemaMigratedEvent {
+    pub from_version: u32,
+    pub to_version: u32,
+}
+
+/// Instance storage keys shared by every upgradeable contract; variant names
+/// must not clash with any contract's own `DataKey`
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum UpgradeKey {
+    SchemaVersion,
+    UpgradeDelay,
+    PendingUpgrade,
+    PendingUpgradeDelay,
+}
+
+/// Upgrade failures shared by every contract
+///
+/// Each contract's error enum carries these at codes 100-103 and converts
+/// with `From`, so clients see the same code whichever contract they call.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum UpgradeError {
+    /// No upgrade has been proposed
+    NoPendingUpgrade,
+    /// Hash differs from the proposed upgrade
+    WasmHashMismatch,
+    /// Proposal eta has not been reached
+    TimelockNotElapsed,
+    /// Stored schema is already at the target version
+    SchemaUpToDate,
+}
+
+/// Upgrade queued behind the timelock
+///
+/// Raising the delay pushes `eta` back to `proposed_at` plus the new delay.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PendingUpgrade {
+    pub wasm_hash: BytesN<32>,
+    pub proposed_at: u64,
+    pub eta: u64,
+}
+
+/// Delay reduction queued behind the delay currently in force
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PendingUpgradeDelay {
+    pub delay: u64,
+    pub eta: u64,
+}
+
+/// Record the schema version written by `initialize`
+pub fn init_schema_version(env: &Env, version: u32) {
+    env.storage()
+        .instance()
+        .set(&UpgradeKey::SchemaVersion, &version);
+}
+
+/// Stored schema version (contracts deployed before versioning are v1)
+pub fn get_schema_version(env: &Env) -> u32 {
+    env.storage()
+        .instance()
+        .get(&UpgradeKey::SchemaVersion)
+        .unwrap_or(1)
+}
+
+/// Timelock in seconds between `propose_upgrade` and `upgrade` (0 = immediate)
+///
+/// A queued reduction is in force once its eta has passed.
+pub fn get_upgrade_delay(env: &Env) -> u64 {
+    match get_pending_upgrade_delay(env) {
+        Some(pending) if env.ledger().timestamp() >= pending.eta => pending.delay,
+        _ => env
+            .storage()
+            .instance()
+            .get(&UpgradeKey::UpgradeDelay)
+            .unwrap_or(0),
+    }
+}
+
+pub fn get_pending_upgrade_delay(env: &Env) -> Option<PendingUpgradeDelay> {
+    env.storage()
+        .instance()
+        .get(&UpgradeKey::PendingUpgradeDelay)
+}
+
+/// Change the timelock, returning when the new delay is in force
+///
+/// Increases apply at once, including to upgrades already queued. A
+/// reduction waits out the current delay, so it cannot be used to rush an
+/// upgrade through.
+pub fn set_upgrade_delay(env: &Env, new_delay: u64) -> u64 {
+    let old_delay = get_upgrade_delay(env);
+    let now = env.ledger().timestamp();
+    env.storage()
+        .instance()
+        .set(&UpgradeKey::UpgradeDelay, &old_delay.max(new_delay));
+
+    if new_delay >= old_delay {
+        env.storage()
+            .instance()
+            .remove(&UpgradeKey::PendingUpgradeDelay);
+        UpgradeDelayUpdatedEvent {
+            old_delay,
+            new_delay,
+        }
+        .publish(env);
+        return now;
+    }
+
+    let eta = now + old_delay;
+    env.storage().instance().set(
+        &UpgradeKey::PendingUpgradeDelay,
+        &PendingUpgradeDelay {
+            delay: new_delay,
+            eta,
+        },
+    );
+    UpgradeDelayProposedEvent { new_delay, eta }.publish(env);
+    eta
+}
+
+pub fn get_pending_upgrade(env: &Env) -> Option<PendingUpgrade> {
+    env.storage()
+        .instance()
+        .get(&UpgradeKey::PendingUpgrade)
+        .map(|pending| retime(env, pending))
+}
+
+/// Upgrade to `wasm_hash` that becomes executable once the timelock has elapsed
+pub fn queue_upgrade(env: &Env, wasm_hash: BytesN<32>) -> PendingUpgrade {
+    let now = env.ledger().timestamp();
+    PendingUpgrade {
+        wasm_hash,
+        proposed_at: now,
+        eta: now + get_upgrade_delay(env),
+    }
+}
+
+/// Push a queued upgrade's eta back if the delay was raised after it was queued
+pub fn retime(env: &Env, mut pending: PendingUpgrade) -> PendingUpgrade {
+    pending.eta = pending
+        .eta
+        .max(pending.proposed_at + get_upgrade_delay(env));
+    pending
+}
+
+/// Check `wasm_hash` against a queued upgrade, enforcing the timelock when
+/// one is configured
+pub fn require_ready(
+    env: &Env,
+    pending: Option<PendingUpgrade>,
+    wasm_hash: &BytesN<32>,
+) -> Result<(), UpgradeError> {
+    if get_upgrade_delay(env) > 0 {
+        let pending = retime(env, pending.ok_or(UpgradeError::NoPendingUpgrade)?);
+        if pending.wasm_hash != *wasm_hash {
+            return Err(UpgradeError::WasmHashMismatch);
+        }
+        if env.ledger().timestamp() < pending.eta {
+            return Err(UpgradeError::TimelockNotElapsed);
+        }
+    }
+    Ok(())
+}
+
+/// Queue an upgrade; it becomes executable once the timelock has elapsed
+pub fn propose_upgrade(env: &Env, wasm_hash: BytesN<32>) -> u64 {
+    let pending = queue_upgrade(env, wasm_hash.clone());
+    let eta = pending.eta;
+    env.storage()
+        .instance()
+        .set(&UpgradeKey::PendingUpgrade, &pending);
+
+    UpgradeProposedEvent { wasm_hash, eta }.publish(env);
+    eta
+}
+
+pub fn cancel_upgrade(env: &Env) -> Result<(), UpgradeError> {
+    let pending = get_pending_upgrade(env).ok_or(UpgradeError::NoPendingUpgrade)?;
+    env.storage().instance().remove(&UpgradeKey::PendingUpgrade);
+
+    UpgradeCancelledEvent {
+        wasm_hash: pending.wasm_hash,
+    }
+    .publish(env);
+    Ok(())
+}
+
+/// Swap the contract's WASM, enforcing the timelock when one is configured
+///
+/// With a non-zero delay the hash must match a proposal whose eta has passed.
+/// Storage is untouched; call `migrate` on the new code to transform it.
+pub fn apply_upgrade(env: &Env, wasm_hash: BytesN<32>) -> Result<(), UpgradeError> {
+    require_ready(env, get_pending_upgrade(env), &wasm_hash)?;
+    env.storage().instance().remove(&UpgradeKey::PendingUpgrade);
+
+    ContractUpgradedEvent {
+        wasm_hash: wasm_hash.clone(),
+        schema_version: get_schema_version(env),
+        timestamp: env.ledger().timestamp(),
+    }
+    .publish(env);
+
+    env.deployer().update_current_contract_wasm(wasm_hash);
+    Ok(())
+}
+
+/// Run `step(env, from)` for every version between the stored one and `target`
+///
+/// `step` transforms storage from `from` to `from + 1`.
+pub fn migrate(env: &Env, target: u32, step: impl Fn(&Env, u32)) -> Result<(), UpgradeError> {
+    let from_version = get_schema_version(env);
+    if from_version >= target {
+        return Err(UpgradeError::SchemaUpToDate);
+    }
+
+    for version in from_version..target {
+        step(env, version);
+    }
+    init_schema_version(env, target);
+
+    SchemaMigratedEvent {
+        from_version,
+        to_version: target,
+    }
+    .publish(env);
+    Ok(())
+}
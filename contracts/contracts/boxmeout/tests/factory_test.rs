@@ -253,10 +253,11 @@ fn test_update_treasury_address() {
 }
 */
 
-use soroban_sdk::{testutils::Address as _, Address, Env, Symbol};
+use soroban_sdk::{testutils::Address as _, token, Address, Env, Symbol};
 
 // Import the Factory contract
-use boxmeout::factory::{MarketFactory, MarketFactoryClient};
+use boxmeout::factory::{FactoryError, MarketFactory, MarketFactoryClient};
+use boxmeout::treasury::{FeeKind, Treasury, TreasuryClient};
 // Helper function to create test environment
 fn create_test_env() -> Env {
     Env::default()
@@ -294,7 +295,6 @@ fn test_factory_initialize() {
 }
 
 #[test]
-#[should_panic(expected = "already initialized")]
 fn test_factory_initialize_twice_fails() {
     let env = create_test_env();
     let factory_id = register_factory(&env);
@@ -308,8 +308,9 @@ fn test_factory_initialize_twice_fails() {
     env.mock_all_auths();
     client.initialize(&admin, &usdc, &treasury);
 
-    // Second initialization should panic
-    client.initialize(&admin, &usdc, &treasury);
+    // Second initialization returns AlreadyInitialized
+    let result = client.try_initialize(&admin, &usdc, &treasury);
+    assert_eq!(result, Err(Ok(FactoryError::AlreadyInitialized)));
 }
 
 #[test]
@@ -336,6 +337,7 @@ fn test_create_market_invalid_timestamps() {
 
     client.create_market(
         &creator,
+        &0u64,
         &title,
         &description,
         &category,
@@ -368,12 +370,197 @@ fn test_create_market_closing_time_in_past() {
 
     client.create_market(
         &creator,
+        &0u64,
+        &title,
+        &description,
+        &category,
+        &closing_time,
+        &resolution_time,
+    );
+}
+
+/// Helper: factory wired to a real treasury and USDC token, creator funded for fees
+fn setup_factory_with_treasury(env: &Env) -> (MarketFactoryClient<'_>, Address) {
+    env.mock_all_auths();
+
+    let factory_id = register_factory(env);
+    let client = MarketFactoryClient::new(env, &factory_id);
+
+    let admin = Address::generate(env);
+    let usdc = create_mock_token(env, &admin);
+    let treasury_id = env.register(Treasury, ());
+    TreasuryClient::new(env, &treasury_id).initialize(&admin, &usdc, &factory_id);
+    client.initialize(&admin, &usdc, &treasury_id);
+
+    let creator = Address::generate(env);
+    token::StellarAssetClient::new(env, &usdc).mint(&creator, &100_000_000);
+
+    (client, creator)
+}
+
+#[test]
+fn test_create_market_returns_computed_id() {
+    let env = create_test_env();
+    let (client, creator) = setup_factory_with_treasury(&env);
+
+    let title = Symbol::new(&env, "Mayweather");
+    let description = Symbol::new(&env, "MayweatherWins");
+    let category = Symbol::new(&env, "Boxing");
+    let closing_time = env.ledger().timestamp() + 86400;
+    let resolution_time = closing_time + 3600;
+
+    let expected = client.compute_market_id(
+        &creator,
+        &7u64,
+        &title,
+        &description,
+        &category,
+        &closing_time,
+        &resolution_time,
+    );
+    assert!(!client.market_exists(&expected));
+
+    let market_id = client.create_market(
+        &creator,
+        &7u64,
+        &title,
+        &description,
+        &category,
+        &closing_time,
+        &resolution_time,
+    );
+
+    assert_eq!(market_id, expected);
+    assert!(client.market_exists(&market_id));
+    assert_eq!(client.get_market_count(), 1);
+}
+
+#[test]
+fn test_create_market_deposits_creation_fee() {
+    let env = create_test_env();
+    let (client, creator) = setup_factory_with_treasury(&env);
+    let treasury = TreasuryClient::new(&env, &client.get_treasury());
+    let usdc = treasury.get_tokens().get(0).unwrap();
+
+    let closing_time = env.ledger().timestamp() + 86400;
+    client.create_market(
+        &creator,
+        &1u64,
+        &Symbol::new(&env, "Mayweather"),
+        &Symbol::new(&env, "MayweatherWins"),
+        &Symbol::new(&env, "Boxing"),
+        &closing_time,
+        &(closing_time + 3600),
+    );
+
+    assert_eq!(
+        token::Client::new(&env, &usdc).balance(&creator),
+        90_000_000
+    );
+    assert_eq!(
+        treasury.get_fees_by_kind(&FeeKind::Creation, &usdc),
+        10_000_000
+    );
+    assert_eq!(treasury.get_total_fees(&usdc), 10_000_000);
+}
+
+#[test]
+fn test_market_id_depends_on_creator_nonce_and_params() {
+    let env = create_test_env();
+    let (client, creator) = setup_factory_with_treasury(&env);
+    let other_creator = Address::generate(&env);
+
+    let title = Symbol::new(&env, "Mayweather");
+    let description = Symbol::new(&env, "MayweatherWins");
+    let category = Symbol::new(&env, "Boxing");
+    let closing_time = env.ledger().timestamp() + 86400;
+    let resolution_time = closing_time + 3600;
+
+    let base = client.compute_market_id(
+        &creator,
+        &1u64,
+        &title,
+        &description,
+        &category,
+        &closing_time,
+        &resolution_time,
+    );
+    let other_nonce = client.compute_market_id(
+        &creator,
+        &2u64,
+        &title,
+        &description,
+        &category,
+        &closing_time,
+        &resolution_time,
+    );
+    let other_creator_id = client.compute_market_id(
+        &other_creator,
+        &1u64,
+        &title,
+        &description,
+        &category,
+        &closing_time,
+        &resolution_time,
+    );
+    let other_params = client.compute_market_id(
+        &creator,
+        &1u64,
+        &title,
+        &description,
+        &category,
+        &closing_time,
+        &(resolution_time + 1),
+    );
+
+    assert_ne!(base, other_nonce);
+    assert_ne!(base, other_creator_id);
+    assert_ne!(base, other_params);
+
+    // Deterministic for identical inputs
+    let again = client.compute_market_id(
+        &creator,
+        &1u64,
+        &title,
+        &description,
+        &category,
+        &closing_time,
+        &resolution_time,
+    );
+    assert_eq!(base, again);
+}
+
+#[test]
+fn test_create_market_duplicate_nonce_rejected() {
+    let env = create_test_env();
+    let (client, creator) = setup_factory_with_treasury(&env);
+
+    let title = Symbol::new(&env, "Mayweather");
+    let description = Symbol::new(&env, "MayweatherWins");
+    let category = Symbol::new(&env, "Boxing");
+    let closing_time = env.ledger().timestamp() + 86400;
+    let resolution_time = closing_time + 3600;
+
+    client.create_market(
+        &creator,
+        &1u64,
+        &title,
+        &description,
+        &category,
+        &closing_time,
+        &resolution_time,
+    );
+    // Same creator, nonce and parameters derive the same ID
+    let result = client.try_create_market(
+        &creator,
+        &1u64,
         &title,
         &description,
         &category,
         &closing_time,
         &resolution_time,
     );
+    assert_eq!(result, Err(Ok(FactoryError::MarketAlreadyExists)));
 }
 
 #[test]
@@ -0,0 +1,15 @@
+#![no_std]
+// Upgrade target for tests: reports a bumped version so callers can tell the
+// WASM was swapped while the contract address and storage stayed in place.
+
+use soroban_sdk::{contract, contractimpl, Env};
+
+#[contract]
+pub struct UpgradeV2;
+
+#[contractimpl]
+impl UpgradeV2 {
+    pub fn version(_env: Env) -> u32 {
+        2
+    }
+}
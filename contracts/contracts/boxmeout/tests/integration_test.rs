@@ -117,7 +117,7 @@ fn test_complete_prediction_flow() {
     // market_client.claim_winnings(&user1, &market_id);
 
     // Step 14: Verify treasury fees collected
-    // let platform_fees = treasury_client.get_platform_fees();
+    // let platform_fees = treasury_client.get_platform_fees(&usdc_token);
     // assert!(platform_fees > 0);
 
     // Verify complete flow succeeded
@@ -362,7 +362,6 @@ fn test_claim_winnings_happy_path() {
 }
 
 #[test]
-#[should_panic(expected = "User did not predict winning outcome")]
 fn test_losing_users_cannot_claim() {
     let env = create_test_env();
     let (client, market_id, token_client, market_contract) = setup_market_for_claims(&env);
@@ -377,12 +376,12 @@ fn test_losing_users_cannot_claim() {
     // User predicted NO (0) - they are a loser
     client.test_set_prediction(&user, &0u32, &500);
 
-    // Should panic: "User did not predict winning outcome"
-    client.claim_winnings(&user, &market_id);
+    // Losing prediction returns NotWinner
+    let result = client.try_claim_winnings(&user, &market_id);
+    assert_eq!(result, Err(Ok(MarketError::NotWinner)));
 }
 
 #[test]
-#[should_panic(expected = "Market not resolved")]
 fn test_cannot_claim_before_resolution() {
     let env = create_test_env();
     let (client, market_id, _token_client, _market_contract) = setup_market_for_claims(&env);
@@ -393,11 +392,11 @@ fn test_cannot_claim_before_resolution() {
     client.test_set_prediction(&user, &1u32, &500);
 
     // Market is still OPEN - should fail
-    client.claim_winnings(&user, &market_id);
+    let result = client.try_claim_winnings(&user, &market_id);
+    assert_eq!(result, Err(Ok(MarketError::MarketNotResolved)));
 }
 
 #[test]
-#[should_panic(expected = "Winnings already claimed")]
 fn test_cannot_double_claim() {
     let env = create_test_env();
     let (client, market_id, token_client, market_contract) = setup_market_for_claims(&env);
@@ -414,8 +413,9 @@ fn test_cannot_double_claim() {
     let payout = client.claim_winnings(&user, &market_id);
     assert_eq!(payout, 900);
 
-    // Second claim should panic with "Winnings already claimed"
-    client.claim_winnings(&user, &market_id);
+    // Second claim returns AlreadyClaimed
+    let result = client.try_claim_winnings(&user, &market_id);
+    assert_eq!(result, Err(Ok(MarketError::AlreadyClaimed)));
 }
 
 #[test]
@@ -486,7 +486,6 @@ fn test_winner_no_outcome_also_works() {
 }
 
 #[test]
-#[should_panic(expected = "No prediction found for user")]
 fn test_user_without_prediction_cannot_claim() {
     let env = create_test_env();
     let (client, market_id, token_client, market_contract) = setup_market_for_claims(&env);
@@ -498,7 +497,8 @@ fn test_user_without_prediction_cannot_claim() {
     client.test_setup_resolution(&market_id, &1u32, &1000, &0);
 
     // User has NO prediction - should fail
-    client.claim_winnings(&user, &market_id);
+    let result = client.try_claim_winnings(&user, &market_id);
+    assert_eq!(result, Err(Ok(MarketError::NoPrediction)));
 }
 
 #[test]
@@ -692,7 +692,6 @@ fn test_dispute_market_happy_path() {
 }
 
 #[test]
-#[should_panic(expected = "Market not resolved")]
 fn test_dispute_market_not_resolved() {
     let env = create_test_env();
     let (client, market_id, _token_client, _market_contract) = setup_market_for_claims(&env);
@@ -701,11 +700,11 @@ fn test_dispute_market_not_resolved() {
     let dispute_reason = Symbol::new(&env, "wrong");
 
     // Market is OPEN, not RESOLVED
-    client.dispute_market(&user, &market_id, &dispute_reason, &None);
+    let result = client.try_dispute_market(&user, &market_id, &dispute_reason, &None);
+    assert_eq!(result, Err(Ok(MarketError::MarketNotResolved)));
 }
 
 #[test]
-#[should_panic(expected = "Dispute window has closed")]
 fn test_dispute_market_window_closed() {
     let env = create_test_env();
     let (client, market_id, token_client, market_contract) = setup_market_for_claims(&env);
@@ -730,7 +729,8 @@ fn test_dispute_market_window_closed() {
         li.timestamp = 102345 + 604801;
     });
 
-    client.dispute_market(&user, &market_id, &dispute_reason, &None);
+    let result = client.try_dispute_market(&user, &market_id, &dispute_reason, &None);
+    assert_eq!(result, Err(Ok(MarketError::DisputeWindowClosed)));
 }
 
 // ============================================================================
@@ -14,11 +14,14 @@ use boxmeout::{OracleManager, OracleManagerClient};
 
 use soroban_sdk::{
     testutils::{Address as _, Ledger},
-    Address, BytesN, Env, Symbol,
+    token, vec, Address, BytesN, Env, Symbol,
 };
 
 use boxmeout::market::PredictionMarket;
-use boxmeout::oracle::{OracleManager, OracleManagerClient};
+use boxmeout::oracle::{
+    ConsensusMode, OracleError, OracleManager, OracleManagerClient, OverrideApproval,
+    OverrideError, OverrideStatus, VoteWeighting, WeightedConsensus,
+};
 
 fn create_test_env() -> Env {
     Env::default()
@@ -90,7 +93,6 @@ fn test_register_multiple_oracles() {
 }
 
 #[test]
-#[should_panic(expected = "Maximum oracle limit reached")]
 fn test_register_oracle_exceeds_limit() {
     let env = create_test_env();
     env.mock_all_auths();
@@ -101,16 +103,17 @@ fn test_register_oracle_exceeds_limit() {
     let admin = Address::generate(&env);
     client.initialize(&admin, &2u32);
 
-    // Register 11 oracles (limit is 10)
-    for _ in 0..11 {
+    // Register 10 oracles (the limit), the 11th is rejected
+    for _ in 0..10 {
         let oracle = Address::generate(&env);
         let name = Symbol::new(&env, "Oracle");
         client.register_oracle(&oracle, &name);
     }
+    let result = client.try_register_oracle(&Address::generate(&env), &Symbol::new(&env, "Oracle"));
+    assert_eq!(result, Err(Ok(OracleError::MaxOraclesReached)));
 }
 
 #[test]
-#[should_panic(expected = "Oracle already registered")]
 fn test_register_duplicate_oracle() {
     let env = create_test_env();
     env.mock_all_auths();
@@ -128,7 +131,8 @@ fn test_register_duplicate_oracle() {
     client.register_oracle(&oracle1, &name);
 
     // Try to register same oracle again
-    client.register_oracle(&oracle1, &name);
+    let result = client.try_register_oracle(&oracle1, &name);
+    assert_eq!(result, Err(Ok(OracleError::OracleAlreadyRegistered)));
 }
 
 #[test]
@@ -149,7 +153,7 @@ fn test_submit_attestation() {
     let resolution_time = 1000u64;
 
     // Register market with resolution time
-    client.register_market(&market_id, &resolution_time);
+    client.register_market(&market_id, &resolution_time, &ConsensusMode::Count);
 
     // Set ledger time past resolution time
     env.ledger().set_timestamp(1001);
@@ -188,7 +192,7 @@ fn test_check_consensus_reached() {
     let resolution_time = 1000u64;
 
     // Register market and set timestamp past resolution time
-    client.register_market(&market_id, &resolution_time);
+    client.register_market(&market_id, &resolution_time, &ConsensusMode::Count);
     env.ledger().set_timestamp(1001);
 
     let data_hash = BytesN::from_array(&env, &[0u8; 32]);
@@ -223,7 +227,7 @@ fn test_check_consensus_not_reached() {
     let resolution_time = 1000u64;
 
     // Register market and set timestamp past resolution time
-    client.register_market(&market_id, &resolution_time);
+    client.register_market(&market_id, &resolution_time, &ConsensusMode::Count);
     env.ledger().set_timestamp(1001);
 
     let data_hash = BytesN::from_array(&env, &[0u8; 32]);
@@ -271,7 +275,7 @@ fn test_check_consensus_tie_handling() {
     let resolution_time = 1000u64;
 
     // Register market and set timestamp past resolution time
-    client.register_market(&market_id, &resolution_time);
+    client.register_market(&market_id, &resolution_time, &ConsensusMode::Count);
     env.ledger().set_timestamp(1001);
 
     let data_hash = BytesN::from_array(&env, &[0u8; 32]);
@@ -289,9 +293,119 @@ fn test_check_consensus_tie_handling() {
 
 #[test]
 fn test_remove_oracle() {
-    // TODO: Implement when remove_oracle is ready
-    // Admin removes misbehaving oracle
-    // Only admin can remove
+    let env = create_test_env();
+    env.mock_all_auths();
+
+    let oracle_id = register_oracle(&env);
+    let client = OracleManagerClient::new(&env, &oracle_id);
+    client.initialize(&Address::generate(&env), &1u32);
+
+    let oracle1 = Address::generate(&env);
+    let oracle2 = Address::generate(&env);
+    client.register_oracle(&oracle1, &Symbol::new(&env, "Oracle1"));
+    client.register_oracle(&oracle2, &Symbol::new(&env, "Oracle2"));
+
+    let market_id = BytesN::from_array(&env, &[1u8; 32]);
+    let other_market = BytesN::from_array(&env, &[2u8; 32]);
+    let resolution_time = env.ledger().timestamp() + 10;
+    client.register_market(&market_id, &resolution_time, &ConsensusMode::Count);
+    client.register_market(&other_market, &resolution_time, &ConsensusMode::Count);
+    env.ledger().with_mut(|li| li.timestamp = resolution_time);
+
+    let data_hash = BytesN::from_array(&env, &[3u8; 32]);
+    client.submit_attestation(&oracle1, &market_id, &1u32, &data_hash);
+
+    client.deregister_oracle(&oracle1);
+    assert!(!client.is_oracle_active(&oracle1));
+    assert_eq!(client.get_oracle_count(), 1);
+
+    // History is kept and the existing vote still counts
+    assert_eq!(client.get_oracle_accuracy(&oracle1), 100);
+    assert!(client.get_attestation(&market_id, &oracle1).is_some());
+    assert_eq!(client.check_consensus(&market_id), (true, 1));
+
+    // No new attestations while inactive
+    let result = client.try_submit_attestation(&oracle1, &other_market, &1u32, &data_hash);
+    assert_eq!(result, Err(Ok(OracleError::OracleNotRegistered)));
+
+    let result = client.try_deregister_oracle(&oracle1);
+    assert_eq!(result, Err(Ok(OracleError::OracleAlreadyInactive)));
+    let result = client.try_deregister_oracle(&Address::generate(&env));
+    assert_eq!(result, Err(Ok(OracleError::OracleNotRegistered)));
+    // Re-registering is not a way back in
+    let result = client.try_register_oracle(&oracle1, &Symbol::new(&env, "Oracle1"));
+    assert_eq!(result, Err(Ok(OracleError::OracleAlreadyRegistered)));
+}
+
+#[test]
+fn test_reactivate_oracle() {
+    let env = create_test_env();
+    env.mock_all_auths();
+
+    let oracle_id = register_oracle(&env);
+    let client = OracleManagerClient::new(&env, &oracle_id);
+    client.initialize(&Address::generate(&env), &1u32);
+
+    let oracle1 = Address::generate(&env);
+    client.register_oracle(&oracle1, &Symbol::new(&env, "Oracle1"));
+
+    let result = client.try_reactivate_oracle(&oracle1);
+    assert_eq!(result, Err(Ok(OracleError::OracleAlreadyActive)));
+
+    client.deregister_oracle(&oracle1);
+    client.reactivate_oracle(&oracle1);
+    assert!(client.is_oracle_active(&oracle1));
+    assert_eq!(client.get_oracle_count(), 1);
+
+    let market_id = BytesN::from_array(&env, &[1u8; 32]);
+    let resolution_time = env.ledger().timestamp() + 10;
+    client.register_market(&market_id, &resolution_time, &ConsensusMode::Count);
+    env.ledger().with_mut(|li| li.timestamp = resolution_time);
+    client.submit_attestation(
+        &oracle1,
+        &market_id,
+        &0u32,
+        &BytesN::from_array(&env, &[3u8; 32]),
+    );
+    assert_eq!(client.get_attestation_counts(&market_id), (0, 1));
+}
+
+#[test]
+fn test_reactivate_oracle_respects_limit() {
+    let env = create_test_env();
+    env.mock_all_auths();
+
+    let oracle_id = register_oracle(&env);
+    let client = OracleManagerClient::new(&env, &oracle_id);
+    client.initialize(&Address::generate(&env), &1u32);
+
+    let retired = Address::generate(&env);
+    client.register_oracle(&retired, &Symbol::new(&env, "Retired"));
+    client.deregister_oracle(&retired);
+
+    // The freed slot can be taken by a new oracle
+    for _ in 0..10 {
+        client.register_oracle(&Address::generate(&env), &Symbol::new(&env, "Oracle"));
+    }
+
+    let result = client.try_reactivate_oracle(&retired);
+    assert_eq!(result, Err(Ok(OracleError::MaxOraclesReached)));
+}
+
+#[test]
+#[should_panic]
+fn test_deregister_oracle_requires_admin() {
+    let env = create_test_env();
+    let oracle_id = register_oracle(&env);
+    let client = OracleManagerClient::new(&env, &oracle_id);
+
+    env.mock_all_auths();
+    client.initialize(&Address::generate(&env), &1u32);
+    let oracle1 = Address::generate(&env);
+    client.register_oracle(&oracle1, &Symbol::new(&env, "Oracle1"));
+
+    env.set_auths(&[]);
+    client.deregister_oracle(&oracle1);
 }
 
 #[test]
@@ -322,7 +436,7 @@ fn test_submit_attestation_stores_attestation() {
     let resolution_time = 1000u64;
 
     // Register market with resolution time
-    client.register_market(&market_id, &resolution_time);
+    client.register_market(&market_id, &resolution_time, &ConsensusMode::Count);
 
     // Set ledger time past resolution time
     env.ledger().set_timestamp(1500);
@@ -348,7 +462,6 @@ fn test_submit_attestation_stores_attestation() {
 
 /// Non-attestor (unregistered oracle) is rejected
 #[test]
-#[should_panic(expected = "Oracle not registered")]
 fn test_submit_attestation_non_attestor_rejected() {
     let env = create_test_env();
     env.mock_all_auths();
@@ -366,20 +479,20 @@ fn test_submit_attestation_non_attestor_rejected() {
     let resolution_time = 1000u64;
 
     // Register market
-    client.register_market(&market_id, &resolution_time);
+    client.register_market(&market_id, &resolution_time, &ConsensusMode::Count);
 
     // Set ledger time past resolution time
     env.ledger().set_timestamp(1500);
 
     let data_hash = BytesN::from_array(&env, &[0u8; 32]);
 
-    // This should panic because oracle is not registered
-    client.submit_attestation(&unregistered_oracle, &market_id, &1u32, &data_hash);
+    // Unregistered oracle returns OracleNotRegistered
+    let result = client.try_submit_attestation(&unregistered_oracle, &market_id, &1u32, &data_hash);
+    assert_eq!(result, Err(Ok(OracleError::OracleNotRegistered)));
 }
 
 /// Cannot attest before resolution_time
 #[test]
-#[should_panic(expected = "Cannot attest before resolution time")]
 fn test_submit_attestation_before_resolution_time() {
     let env = create_test_env();
     env.mock_all_auths();
@@ -397,20 +510,20 @@ fn test_submit_attestation_before_resolution_time() {
     let resolution_time = 2000u64;
 
     // Register market with resolution time of 2000
-    client.register_market(&market_id, &resolution_time);
+    client.register_market(&market_id, &resolution_time, &ConsensusMode::Count);
 
     // Set ledger time BEFORE resolution time
     env.ledger().set_timestamp(1500);
 
     let data_hash = BytesN::from_array(&env, &[0u8; 32]);
 
-    // This should panic because we're before resolution time
-    client.submit_attestation(&oracle1, &market_id, &1u32, &data_hash);
+    // Before resolution time returns ResolutionTimeNotReached
+    let result = client.try_submit_attestation(&oracle1, &market_id, &1u32, &data_hash);
+    assert_eq!(result, Err(Ok(OracleError::ResolutionTimeNotReached)));
 }
 
 /// Invalid outcome (not 0 or 1) is rejected
 #[test]
-#[should_panic(expected = "Invalid attestation result")]
 fn test_submit_attestation_invalid_outcome_rejected() {
     let env = create_test_env();
     env.mock_all_auths();
@@ -428,15 +541,16 @@ fn test_submit_attestation_invalid_outcome_rejected() {
     let resolution_time = 1000u64;
 
     // Register market
-    client.register_market(&market_id, &resolution_time);
+    client.register_market(&market_id, &resolution_time, &ConsensusMode::Count);
 
     // Set ledger time past resolution time
     env.ledger().set_timestamp(1500);
 
     let data_hash = BytesN::from_array(&env, &[0u8; 32]);
 
-    // This should panic because outcome 2 is invalid (only 0 or 1 allowed)
-    client.submit_attestation(&oracle1, &market_id, &2u32, &data_hash);
+    // Outcome 2 returns InvalidOutcome (only 0 or 1 allowed)
+    let result = client.try_submit_attestation(&oracle1, &market_id, &2u32, &data_hash);
+    assert_eq!(result, Err(Ok(OracleError::InvalidOutcome)));
 }
 
 /// Verify AttestationSubmitted event is emitted correctly
@@ -458,7 +572,7 @@ fn test_submit_attestation_event_emitted() {
     let resolution_time = 1000u64;
 
     // Register market
-    client.register_market(&market_id, &resolution_time);
+    client.register_market(&market_id, &resolution_time, &ConsensusMode::Count);
 
     // Set ledger time past resolution time
     env.ledger().set_timestamp(1500);
@@ -496,7 +610,7 @@ fn test_register_market() {
     let resolution_time = 3000u64;
 
     // Register market
-    client.register_market(&market_id, &resolution_time);
+    client.register_market(&market_id, &resolution_time, &ConsensusMode::Count);
 
     // Verify resolution time is stored
     let stored_time = client.get_market_resolution_time(&market_id);
@@ -532,7 +646,7 @@ fn test_attestation_count_tracking() {
     let resolution_time = 1000u64;
 
     // Register market
-    client.register_market(&market_id, &resolution_time);
+    client.register_market(&market_id, &resolution_time, &ConsensusMode::Count);
     env.ledger().set_timestamp(1500);
 
     let data_hash = BytesN::from_array(&env, &[0u8; 32]);
@@ -602,7 +716,7 @@ fn test_finalize_resolution_integration() {
     );
 
     // Register market in oracle
-    oracle_client.register_market(&market_id_bytes, &resolution_time);
+    oracle_client.register_market(&market_id_bytes, &resolution_time, &ConsensusMode::Count);
 
     // Advance time past resolution
     env.ledger().set_timestamp(resolution_time + 10);
@@ -642,7 +756,6 @@ fn test_finalize_resolution_integration() {
 
 /// Test finalize_resolution fails if consensus not reached
 #[test]
-#[should_panic(expected = "Consensus not reached")]
 fn test_finalize_resolution_no_consensus() {
     use boxmeout::market::PredictionMarket;
 
@@ -662,7 +775,7 @@ fn test_finalize_resolution_no_consensus() {
     oracle_client.register_oracle(&oracle1, &Symbol::new(&env, "O1"));
 
     let resolution_time = 1000u64;
-    oracle_client.register_market(&market_id_bytes, &resolution_time);
+    oracle_client.register_market(&market_id_bytes, &resolution_time, &ConsensusMode::Count);
 
     // Only 1 attestation (not enough for consensus)
     env.ledger().set_timestamp(resolution_time + 10);
@@ -672,13 +785,13 @@ fn test_finalize_resolution_no_consensus() {
     // Advance past dispute period
     env.ledger().set_timestamp(resolution_time + 604800 + 10);
 
-    // Should panic: consensus not reached
-    oracle_client.finalize_resolution(&market_id_bytes, &market_contract_id);
+    // Returns ConsensusNotReached
+    let result = oracle_client.try_finalize_resolution(&market_id_bytes, &market_contract_id);
+    assert_eq!(result, Err(Ok(OracleError::ConsensusNotReached)));
 }
 
 /// Test finalize_resolution fails if dispute period not elapsed
 #[test]
-#[should_panic(expected = "Dispute period not elapsed")]
 fn test_finalize_resolution_dispute_period_not_elapsed() {
     use boxmeout::market::PredictionMarket;
 
@@ -700,7 +813,7 @@ fn test_finalize_resolution_dispute_period_not_elapsed() {
     oracle_client.register_oracle(&oracle2, &Symbol::new(&env, "O2"));
 
     let resolution_time = 1000u64;
-    oracle_client.register_market(&market_id_bytes, &resolution_time);
+    oracle_client.register_market(&market_id_bytes, &resolution_time, &ConsensusMode::Count);
 
     // Submit attestations to reach consensus
     env.ledger().set_timestamp(resolution_time + 10);
@@ -711,13 +824,13 @@ fn test_finalize_resolution_dispute_period_not_elapsed() {
     // Try to finalize before dispute period (only 100 seconds after resolution)
     env.ledger().set_timestamp(resolution_time + 100);
 
-    // Should panic: dispute period not elapsed
-    oracle_client.finalize_resolution(&market_id_bytes, &market_contract_id);
+    // Returns DisputePeriodNotElapsed
+    let result = oracle_client.try_finalize_resolution(&market_id_bytes, &market_contract_id);
+    assert_eq!(result, Err(Ok(OracleError::DisputePeriodNotElapsed)));
 }
 
 /// Test finalize_resolution fails if market not registered
 #[test]
-#[should_panic(expected = "Market not registered")]
 fn test_finalize_resolution_market_not_registered() {
     let env = create_test_env();
     env.mock_all_auths();
@@ -731,6 +844,1211 @@ fn test_finalize_resolution_market_not_registered() {
     let admin = Address::generate(&env);
     oracle_client.initialize(&admin, &2u32);
 
-    // Market not registered - should panic
-    oracle_client.finalize_resolution(&market_id_bytes, &market_contract_id);
+    // Market not registered returns MarketNotRegistered
+    let result = oracle_client.try_finalize_resolution(&market_id_bytes, &market_contract_id);
+    assert_eq!(result, Err(Ok(OracleError::MarketNotRegistered)));
+}
+
+// ===== STAKING TESTS =====
+
+fn setup_staking<'a>(
+    env: &Env,
+    client: &OracleManagerClient,
+    min_stake: i128,
+    unbonding_period: u64,
+) -> (token::TokenClient<'a>, token::StellarAssetClient<'a>) {
+    let stake_token = env
+        .register_stellar_asset_contract_v2(Address::generate(env))
+        .address();
+    client.configure_staking(&stake_token, &min_stake, &unbonding_period);
+    (
+        token::TokenClient::new(env, &stake_token),
+        token::StellarAssetClient::new(env, &stake_token),
+    )
+}
+
+#[test]
+fn test_stake_unstake_and_withdraw() {
+    let env = create_test_env();
+    env.mock_all_auths();
+
+    let oracle_id = register_oracle(&env);
+    let client = OracleManagerClient::new(&env, &oracle_id);
+    client.initialize(&Address::generate(&env), &1u32);
+    let (token, token_admin) = setup_staking(&env, &client, 1_000, 3600);
+
+    let oracle1 = Address::generate(&env);
+    client.register_oracle(&oracle1, &Symbol::new(&env, "Oracle1"));
+    token_admin.mint(&oracle1, &5_000);
+
+    client.stake(&oracle1, &5_000);
+    assert_eq!(client.get_oracle_stake(&oracle1), 5_000);
+    assert_eq!(token.balance(&oracle1), 0);
+    assert_eq!(token.balance(&oracle_id), 5_000);
+
+    let release_time = client.unstake(&oracle1, &2_000);
+    assert_eq!(release_time, env.ledger().timestamp() + 3600);
+    assert_eq!(client.get_oracle_stake(&oracle1), 3_000);
+
+    let result = client.try_withdraw_stake(&oracle1);
+    assert_eq!(result, Err(Ok(OracleError::UnbondingNotElapsed)));
+
+    env.ledger().with_mut(|li| li.timestamp = release_time);
+    assert_eq!(client.withdraw_stake(&oracle1), 2_000);
+    assert_eq!(token.balance(&oracle1), 2_000);
+    assert_eq!(token.balance(&oracle_id), 3_000);
+    assert!(client.get_unbonding_stake(&oracle1).is_none());
+
+    let result = client.try_withdraw_stake(&oracle1);
+    assert_eq!(result, Err(Ok(OracleError::NoUnbondingStake)));
+}
+
+#[test]
+fn test_stake_validation() {
+    let env = create_test_env();
+    env.mock_all_auths();
+
+    let oracle_id = register_oracle(&env);
+    let client = OracleManagerClient::new(&env, &oracle_id);
+    client.initialize(&Address::generate(&env), &1u32);
+
+    let oracle1 = Address::generate(&env);
+    client.register_oracle(&oracle1, &Symbol::new(&env, "Oracle1"));
+
+    let result = client.try_stake(&oracle1, &100);
+    assert_eq!(result, Err(Ok(OracleError::StakingNotConfigured)));
+
+    let (_, token_admin) = setup_staking(&env, &client, 0, 3600);
+    token_admin.mint(&oracle1, &100);
+
+    let result = client.try_stake(&oracle1, &0);
+    assert_eq!(result, Err(Ok(OracleError::InvalidStakeAmount)));
+    let result = client.try_stake(&Address::generate(&env), &100);
+    assert_eq!(result, Err(Ok(OracleError::OracleNotRegistered)));
+
+    client.stake(&oracle1, &100);
+    let result = client.try_unstake(&oracle1, &101);
+    assert_eq!(result, Err(Ok(OracleError::InvalidStakeAmount)));
+
+    // Unstaked tokens must stay slashable for a while
+    let stake_token = client.get_stake_token().unwrap();
+    let result = client.try_configure_staking(&stake_token, &0, &0);
+    assert_eq!(result, Err(Ok(OracleError::InvalidUnbondingPeriod)));
+}
+
+#[test]
+fn test_attestation_requires_min_stake() {
+    let env = create_test_env();
+    env.mock_all_auths();
+
+    let oracle_id = register_oracle(&env);
+    let client = OracleManagerClient::new(&env, &oracle_id);
+    client.initialize(&Address::generate(&env), &1u32);
+    let (_, token_admin) = setup_staking(&env, &client, 1_000, 3600);
+
+    let oracle1 = Address::generate(&env);
+    client.register_oracle(&oracle1, &Symbol::new(&env, "Oracle1"));
+    token_admin.mint(&oracle1, &1_000);
+
+    let market_id = BytesN::from_array(&env, &[1u8; 32]);
+    let other_market = BytesN::from_array(&env, &[2u8; 32]);
+    let resolution_time = env.ledger().timestamp() + 10;
+    client.register_market(&market_id, &resolution_time, &ConsensusMode::Count);
+    client.register_market(&other_market, &resolution_time, &ConsensusMode::Count);
+    env.ledger().with_mut(|li| li.timestamp = resolution_time);
+    let data_hash = BytesN::from_array(&env, &[3u8; 32]);
+
+    let result = client.try_submit_attestation(&oracle1, &market_id, &1u32, &data_hash);
+    assert_eq!(result, Err(Ok(OracleError::InsufficientStake)));
+
+    client.stake(&oracle1, &1_000);
+    client.submit_attestation(&oracle1, &market_id, &1u32, &data_hash);
+
+    // Unbonding stake no longer counts toward the minimum
+    client.unstake(&oracle1, &1);
+    let result = client.try_submit_attestation(&oracle1, &other_market, &1u32, &data_hash);
+    assert_eq!(result, Err(Ok(OracleError::InsufficientStake)));
+}
+
+// ===== WEIGHTED CONSENSUS TESTS =====
+
+fn weighted(weighting: VoteWeighting, threshold_bps: u32, quorum: u32) -> ConsensusMode {
+    ConsensusMode::Weighted(WeightedConsensus {
+        weighting,
+        threshold_bps,
+        quorum,
+    })
+}
+
+/// Register `stakes.len()` oracles, each staking its amount, and a market
+/// using `mode` that is open for attestation
+fn setup_weighted_market(
+    env: &Env,
+    stakes: &[i128],
+    mode: ConsensusMode,
+) -> (OracleManagerClient<'static>, BytesN<32>, Vec<Address>) {
+    env.mock_all_auths();
+    let oracle_id = register_oracle(env);
+    let client = OracleManagerClient::new(env, &oracle_id);
+    client.initialize(&Address::generate(env), &1u32);
+    let (_, token_admin) = setup_staking(env, &client, 0, 3600);
+
+    let mut oracles = Vec::new();
+    for stake in stakes {
+        let oracle = Address::generate(env);
+        client.register_oracle(&oracle, &Symbol::new(env, "Oracle"));
+        token_admin.mint(&oracle, stake);
+        client.stake(&oracle, stake);
+        oracles.push(oracle);
+    }
+
+    let market_id = BytesN::from_array(env, &[1u8; 32]);
+    let resolution_time = env.ledger().timestamp() + 10;
+    client.register_market(&market_id, &resolution_time, &mode);
+    env.ledger().with_mut(|li| li.timestamp = resolution_time);
+    (client, market_id, oracles)
+}
+
+fn attest(
+    env: &Env,
+    client: &OracleManagerClient,
+    oracle: &Address,
+    market_id: &BytesN<32>,
+    outcome: u32,
+) {
+    client.submit_attestation(
+        oracle,
+        market_id,
+        &outcome,
+        &BytesN::from_array(env, &[3u8; 32]),
+    );
+}
+
+#[test]
+fn test_stake_weighted_consensus_reached() {
+    let env = create_test_env();
+    let mode = weighted(VoteWeighting::Stake, 6_670, 2);
+    let (client, market_id, oracles) =
+        setup_weighted_market(&env, &[7_000, 2_000, 1_000], mode.clone());
+    assert_eq!(client.get_consensus_mode(&market_id), mode);
+
+    // One heavy oracle outweighs two light ones: 70% YES
+    attest(&env, &client, &oracles[0], &market_id, 1);
+    attest(&env, &client, &oracles[1], &market_id, 0);
+    assert_eq!(client.check_consensus(&market_id), (true, 1));
+
+    attest(&env, &client, &oracles[2], &market_id, 0);
+    // 7000 / 10000 still passes 66.7%
+    assert_eq!(client.check_consensus(&market_id), (true, 1));
+}
+
+#[test]
+fn test_weighted_consensus_below_threshold() {
+    let env = create_test_env();
+    let (client, market_id, oracles) = setup_weighted_market(
+        &env,
+        &[6_000, 4_000],
+        weighted(VoteWeighting::Stake, 6_670, 2),
+    );
+
+    // 60% leads but is short of the 66.7% threshold
+    attest(&env, &client, &oracles[0], &market_id, 1);
+    attest(&env, &client, &oracles[1], &market_id, 0);
+    assert_eq!(client.check_consensus(&market_id), (false, 0));
+}
+
+#[test]
+fn test_weighted_consensus_tie() {
+    let env = create_test_env();
+    let (client, market_id, oracles) = setup_weighted_market(
+        &env,
+        &[5_000, 2_500, 2_500],
+        weighted(VoteWeighting::Stake, 5_001, 1),
+    );
+
+    attest(&env, &client, &oracles[0], &market_id, 1);
+    attest(&env, &client, &oracles[1], &market_id, 0);
+    attest(&env, &client, &oracles[2], &market_id, 0);
+    assert_eq!(client.check_consensus(&market_id), (false, 0));
+}
+
+#[test]
+fn test_weighted_consensus_quorum_not_met() {
+    let env = create_test_env();
+    let (client, market_id, oracles) = setup_weighted_market(
+        &env,
+        &[9_000, 500, 500],
+        weighted(VoteWeighting::Stake, 6_670, 3),
+    );
+
+    // Unanimous by weight, but only two of the three required oracles voted
+    attest(&env, &client, &oracles[0], &market_id, 1);
+    attest(&env, &client, &oracles[1], &market_id, 1);
+    assert_eq!(client.check_consensus(&market_id), (false, 0));
+
+    attest(&env, &client, &oracles[2], &market_id, 0);
+    assert_eq!(client.check_consensus(&market_id), (true, 1));
+}
+
+#[test]
+fn test_accuracy_weighted_consensus() {
+    let env = create_test_env();
+    // Stakes are ignored when weighting by accuracy: all oracles start at 100
+    let (client, market_id, oracles) = setup_weighted_market(
+        &env,
+        &[9_000, 500, 500],
+        weighted(VoteWeighting::Accuracy, 6_000, 3),
+    );
+
+    attest(&env, &client, &oracles[0], &market_id, 1);
+    attest(&env, &client, &oracles[1], &market_id, 0);
+    attest(&env, &client, &oracles[2], &market_id, 0);
+    assert_eq!(client.check_consensus(&market_id), (true, 0));
+}
+
+#[test]
+fn test_vote_weight_fixed_at_attestation() {
+    let env = create_test_env();
+    let (client, market_id, oracles) = setup_weighted_market(
+        &env,
+        &[7_000, 3_000],
+        weighted(VoteWeighting::StakeAndAccuracy, 6_670, 2),
+    );
+
+    attest(&env, &client, &oracles[0], &market_id, 1);
+    attest(&env, &client, &oracles[1], &market_id, 0);
+
+    // Unstaking after voting does not change the outcome
+    client.unstake(&oracles[0], &7_000);
+    assert_eq!(client.check_consensus(&market_id), (true, 1));
+}
+
+#[test]
+fn test_register_market_rejects_invalid_weighted_config() {
+    let env = create_test_env();
+    env.mock_all_auths();
+    let oracle_id = register_oracle(&env);
+    let client = OracleManagerClient::new(&env, &oracle_id);
+    client.initialize(&Address::generate(&env), &1u32);
+    let market_id = BytesN::from_array(&env, &[1u8; 32]);
+
+    for mode in [
+        weighted(VoteWeighting::Stake, 5_000, 1),
+        weighted(VoteWeighting::Stake, 10_001, 1),
+        weighted(VoteWeighting::Stake, 6_670, 0),
+    ] {
+        let result = client.try_register_market(&market_id, &100, &mode);
+        assert_eq!(result, Err(Ok(OracleError::InvalidConsensusConfig)));
+    }
+    assert_eq!(client.get_consensus_mode(&market_id), ConsensusMode::Count);
+}
+
+// ===== COMMIT-REVEAL TESTS =====
+
+/// Market with commits open at t=110 until 200 and reveals until 300, plus three oracles
+fn setup_commit_reveal_market(
+    env: &Env,
+) -> (OracleManagerClient<'static>, BytesN<32>, Vec<Address>) {
+    env.mock_all_auths();
+    env.ledger().with_mut(|li| li.timestamp = 100);
+    let oracle_id = register_oracle(env);
+    let client = OracleManagerClient::new(env, &oracle_id);
+    client.initialize(&Address::generate(env), &2u32);
+
+    let oracles: Vec<Address> = (0..3).map(|_| Address::generate(env)).collect();
+    for oracle in &oracles {
+        client.register_oracle(oracle, &Symbol::new(env, "Oracle"));
+    }
+
+    let market_id = BytesN::from_array(env, &[1u8; 32]);
+    client.register_market(&market_id, &110, &ConsensusMode::Count);
+    client.enable_commit_reveal(&market_id, &200, &300);
+    env.ledger().with_mut(|li| li.timestamp = 110);
+    (client, market_id, oracles)
+}
+
+fn commit(
+    env: &Env,
+    client: &OracleManagerClient,
+    oracle: &Address,
+    market_id: &BytesN<32>,
+    outcome: u32,
+) -> (BytesN<32>, BytesN<32>) {
+    let data_hash = BytesN::from_array(env, &[3u8; 32]);
+    let salt = BytesN::from_array(env, &[9u8; 32]);
+    let commitment = client.attestation_commitment(&outcome, &data_hash, &salt);
+    client.commit_attestation(oracle, market_id, &commitment);
+    (data_hash, salt)
+}
+
+#[test]
+fn test_commit_reveal_counts_only_revealed_votes() {
+    let env = create_test_env();
+    let (client, market_id, oracles) = setup_commit_reveal_market(&env);
+
+    let (hash0, salt0) = commit(&env, &client, &oracles[0], &market_id, 1);
+    let (hash1, salt1) = commit(&env, &client, &oracles[1], &market_id, 1);
+    commit(&env, &client, &oracles[2], &market_id, 0);
+
+    // Commits are sealed: nothing counts yet
+    assert_eq!(client.get_attestation_counts(&market_id), (0, 0));
+    assert_eq!(client.check_consensus(&market_id), (false, 0));
+
+    // Reveals only open at the commit deadline
+    let result = client.try_reveal_attestation(&oracles[0], &market_id, &1, &hash0, &salt0);
+    assert_eq!(result, Err(Ok(OracleError::RevealPhaseNotOpen)));
+
+    env.ledger().with_mut(|li| li.timestamp = 200);
+    client.reveal_attestation(&oracles[0], &market_id, &1, &hash0, &salt0);
+    assert_eq!(client.check_consensus(&market_id), (false, 0));
+    client.reveal_attestation(&oracles[1], &market_id, &1, &hash1, &salt1);
+
+    assert_eq!(client.get_attestation_counts(&market_id), (2, 0));
+    assert_eq!(client.check_consensus(&market_id), (true, 1));
+}
+
+#[test]
+fn test_commit_reveal_rejects_mismatched_reveal() {
+    let env = create_test_env();
+    let (client, market_id, oracles) = setup_commit_reveal_market(&env);
+
+    let (hash0, salt0) = commit(&env, &client, &oracles[0], &market_id, 1);
+    env.ledger().with_mut(|li| li.timestamp = 200);
+
+    // Changing the vote after seeing others does not match the commitment
+    let result = client.try_reveal_attestation(&oracles[0], &market_id, &0, &hash0, &salt0);
+    assert_eq!(result, Err(Ok(OracleError::CommitmentMismatch)));
+
+    let result = client.try_reveal_attestation(&oracles[1], &market_id, &1, &hash0, &salt0);
+    assert_eq!(result, Err(Ok(OracleError::CommitmentNotFound)));
+
+    client.reveal_attestation(&oracles[0], &market_id, &1, &hash0, &salt0);
+    let result = client.try_reveal_attestation(&oracles[0], &market_id, &1, &hash0, &salt0);
+    assert_eq!(result, Err(Ok(OracleError::AlreadyAttested)));
+
+    env.ledger().with_mut(|li| li.timestamp = 300);
+    let result = client.try_reveal_attestation(&oracles[0], &market_id, &1, &hash0, &salt0);
+    assert_eq!(result, Err(Ok(OracleError::RevealPhaseClosed)));
+}
+
+#[test]
+fn test_reveal_requires_attestor() {
+    let env = create_test_env();
+    let (client, market_id, oracles) = setup_commit_reveal_market(&env);
+
+    let (hash0, salt0) = commit(&env, &client, &oracles[0], &market_id, 1);
+    let (hash1, salt1) = commit(&env, &client, &oracles[1], &market_id, 1);
+
+    // Deregistered after committing
+    client.deregister_oracle(&oracles[0]);
+    // Below the minimum stake after committing
+    let stake_token = env
+        .register_stellar_asset_contract_v2(Address::generate(&env))
+        .address();
+    client.configure_staking(&stake_token, &1, &3600);
+
+    env.ledger().with_mut(|li| li.timestamp = 200);
+    let result = client.try_reveal_attestation(&oracles[0], &market_id, &1, &hash0, &salt0);
+    assert_eq!(result, Err(Ok(OracleError::OracleNotRegistered)));
+    let result = client.try_reveal_attestation(&oracles[1], &market_id, &1, &hash1, &salt1);
+    assert_eq!(result, Err(Ok(OracleError::InsufficientStake)));
+    assert_eq!(client.get_attestation_counts(&market_id), (0, 0));
+}
+
+#[test]
+fn test_commit_reveal_phase_rules() {
+    let env = create_test_env();
+    let (client, market_id, oracles) = setup_commit_reveal_market(&env);
+    let data_hash = BytesN::from_array(&env, &[3u8; 32]);
+
+    // Plain attestations are not accepted on a commit-reveal market
+    let result = client.try_submit_attestation(&oracles[0], &market_id, &1, &data_hash);
+    assert_eq!(result, Err(Ok(OracleError::CommitRevealRequired)));
+
+    commit(&env, &client, &oracles[0], &market_id, 1);
+    let result = client.try_commit_attestation(&oracles[0], &market_id, &data_hash);
+    assert_eq!(result, Err(Ok(OracleError::AlreadyAttested)));
+
+    env.ledger().with_mut(|li| li.timestamp = 200);
+    let result = client.try_commit_attestation(&oracles[1], &market_id, &data_hash);
+    assert_eq!(result, Err(Ok(OracleError::CommitPhaseClosed)));
+
+    // Deadlines can't be changed once attestations have started
+    let result = client.try_enable_commit_reveal(&market_id, &400, &500);
+    assert_eq!(result, Err(Ok(OracleError::InvalidRevealWindow)));
+}
+
+#[test]
+fn test_enable_commit_reveal_validates_window() {
+    let env = create_test_env();
+    env.mock_all_auths();
+    let oracle_id = register_oracle(&env);
+    let client = OracleManagerClient::new(&env, &oracle_id);
+    client.initialize(&Address::generate(&env), &1u32);
+
+    let market_id = BytesN::from_array(&env, &[1u8; 32]);
+    let result = client.try_enable_commit_reveal(&market_id, &200, &300);
+    assert_eq!(result, Err(Ok(OracleError::MarketNotRegistered)));
+
+    client.register_market(&market_id, &100, &ConsensusMode::Count);
+    for (commit_deadline, reveal_deadline) in [(100, 300), (200, 200)] {
+        let result =
+            client.try_enable_commit_reveal(&market_id, &commit_deadline, &reveal_deadline);
+        assert_eq!(result, Err(Ok(OracleError::InvalidRevealWindow)));
+    }
+    assert!(client.get_reveal_window(&market_id).is_none());
+
+    let result = client.try_commit_attestation(&Address::generate(&env), &market_id, &market_id);
+    assert_eq!(result, Err(Ok(OracleError::OracleNotRegistered)));
+}
+
+#[test]
+fn test_missed_reveal_costs_reputation() {
+    let env = create_test_env();
+    let (client, market_id, oracles) = setup_commit_reveal_market(&env);
+
+    let (hash0, salt0) = commit(&env, &client, &oracles[0], &market_id, 1);
+    let (hash1, salt1) = commit(&env, &client, &oracles[1], &market_id, 1);
+    commit(&env, &client, &oracles[2], &market_id, 0);
+
+    env.ledger().with_mut(|li| li.timestamp = 200);
+    client.reveal_attestation(&oracles[0], &market_id, &1, &hash0, &salt0);
+    client.reveal_attestation(&oracles[1], &market_id, &1, &hash1, &salt1);
+
+    let result = client.try_settle_reveals(&market_id);
+    assert_eq!(result, Err(Ok(OracleError::RevealPhaseNotOver)));
+
+    env.ledger().with_mut(|li| li.timestamp = 300);
+    assert_eq!(client.settle_reveals(&market_id), 1);
+    assert_eq!(client.get_oracle_accuracy(&oracles[2]), 90);
+    assert_eq!(client.get_oracle_accuracy(&oracles[0]), 100);
+
+    // Settling is one-off
+    assert_eq!(client.settle_reveals(&market_id), 0);
+    assert_eq!(client.get_oracle_accuracy(&oracles[2]), 90);
+}
+
+#[test]
+fn test_finalize_waits_for_reveal_deadline() {
+    let env = create_test_env();
+    let (client, market_id, oracles) = setup_commit_reveal_market(&env);
+    let market_address = Address::generate(&env);
+
+    let (hash0, salt0) = commit(&env, &client, &oracles[0], &market_id, 0);
+    let (hash1, salt1) = commit(&env, &client, &oracles[1], &market_id, 0);
+    commit(&env, &client, &oracles[2], &market_id, 1);
+
+    env.ledger().with_mut(|li| li.timestamp = 200);
+    client.reveal_attestation(&oracles[0], &market_id, &0, &hash0, &salt0);
+    client.reveal_attestation(&oracles[1], &market_id, &0, &hash1, &salt1);
+
+    let result = client.try_finalize_resolution(&market_id, &market_address);
+    assert_eq!(result, Err(Ok(OracleError::RevealPhaseNotOver)));
+
+    // Past the reveal deadline the usual dispute period applies
+    env.ledger().with_mut(|li| li.timestamp = 300);
+    let result = client.try_finalize_resolution(&market_id, &market_address);
+    assert_eq!(result, Err(Ok(OracleError::DisputePeriodNotElapsed)));
+}
+
+// ===== OPTIMISTIC RESOLUTION TESTS =====
+
+const BOND: i128 = 500;
+
+/// Optimistic market past its resolution time (t=110) with a 3600s liveness,
+/// two registered oracles and a funded proposer and disputer
+fn setup_optimistic_market(
+    env: &Env,
+) -> (
+    OracleManagerClient<'static>,
+    token::TokenClient<'static>,
+    BytesN<32>,
+    Address,
+    Address,
+    Vec<Address>,
+) {
+    env.mock_all_auths();
+    env.ledger().with_mut(|li| li.timestamp = 100);
+    let oracle_id = register_oracle(env);
+    let client = OracleManagerClient::new(env, &oracle_id);
+    client.initialize(&Address::generate(env), &2u32);
+    let (token, token_admin) = setup_staking(env, &client, 0, 3600);
+
+    let oracles: Vec<Address> = (0..2).map(|_| Address::generate(env)).collect();
+    for oracle in &oracles {
+        client.register_oracle(oracle, &Symbol::new(env, "Oracle"));
+    }
+
+    let market_id = BytesN::from_array(env, &[1u8; 32]);
+    client.register_market(&market_id, &110, &ConsensusMode::Count);
+    client.enable_optimistic(&market_id, &BOND, &3600);
+
+    let proposer = Address::generate(env);
+    let disputer = Address::generate(env);
+    token_admin.mint(&proposer, &BOND);
+    token_admin.mint(&disputer, &BOND);
+    env.ledger().with_mut(|li| li.timestamp = 110);
+    (client, token, market_id, proposer, disputer, oracles)
+}
+
+#[test]
+fn test_undisputed_proposal_becomes_consensus() {
+    let env = create_test_env();
+    let (client, token, market_id, proposer, _, _) = setup_optimistic_market(&env);
+
+    let expires_at = client.propose_outcome(&proposer, &market_id, &1);
+    assert_eq!(expires_at, 110 + 3600);
+    assert_eq!(token.balance(&proposer), 0);
+    assert_eq!(client.get_proposal(&market_id).unwrap().outcome, 1);
+
+    let result = client.try_settle_proposal(&market_id);
+    assert_eq!(result, Err(Ok(OracleError::LivenessNotElapsed)));
+
+    env.ledger().with_mut(|li| li.timestamp = expires_at);
+    assert_eq!(client.settle_proposal(&market_id), 1);
+    assert_eq!(client.get_consensus_result(&market_id), 1);
+    assert_eq!(token.balance(&proposer), BOND);
+
+    let result = client.try_settle_proposal(&market_id);
+    assert_eq!(result, Err(Ok(OracleError::ProposalAlreadySettled)));
+
+    // Later votes cannot replace the settled result
+    env.ledger().with_mut(|li| li.timestamp = 110 + 604800);
+    let result = client.try_finalize_resolution(&market_id, &Address::generate(&env));
+    assert_eq!(result, Err(Ok(OracleError::ProposalAlreadySettled)));
+}
+
+/// Close and finalize an optimistic market from `setup_optimistic_market`
+/// through a market contract, storing the oracles' consensus result
+fn finalize_optimistic_market(env: &Env, client: &OracleManagerClient, market_id: &BytesN<32>) {
+    use boxmeout::market::{PredictionMarket, PredictionMarketClient};
+
+    let market_address = env.register(PredictionMarket, ());
+    let market = PredictionMarketClient::new(env, &market_address);
+    market.initialize(
+        market_id,
+        &Address::generate(env),
+        &Address::generate(env),
+        &env.register_stellar_asset_contract_v2(Address::generate(env))
+            .address(),
+        &client.address,
+        &105,
+        &110,
+    );
+    market.close_market(market_id);
+    env.ledger().with_mut(|li| li.timestamp = 110 + 604800);
+    client.finalize_resolution(market_id, &market_address);
+}
+
+#[test]
+fn test_undisputed_proposal_against_resolved_market_forfeits_bond() {
+    let env = create_test_env();
+    let (client, token, market_id, proposer, _, oracles) = setup_optimistic_market(&env);
+
+    client.propose_outcome(&proposer, &market_id, &1);
+    let data_hash = BytesN::from_array(&env, &[3u8; 32]);
+    for oracle in &oracles {
+        client.submit_attestation(oracle, &market_id, &0, &data_hash);
+    }
+    finalize_optimistic_market(&env, &client, &market_id);
+
+    // The market resolved NO before the YES proposal was settled
+    let admin = client.get_admin_signers().get(0).unwrap();
+    assert_eq!(client.settle_proposal(&market_id), 0);
+    assert_eq!(client.get_consensus_result(&market_id), 0);
+    assert_eq!(token.balance(&proposer), 0);
+    assert_eq!(token.balance(&admin), BOND);
+}
+
+#[test]
+fn test_disputed_proposal_escalates_to_oracle_vote() {
+    let env = create_test_env();
+    let (client, token, market_id, proposer, disputer, oracles) = setup_optimistic_market(&env);
+
+    client.propose_outcome(&proposer, &market_id, &1);
+    client.dispute_proposal(&disputer, &market_id);
+    assert_eq!(token.balance(&client.address), BOND * 2);
+
+    // Liveness passing no longer settles a disputed proposal on its own
+    env.ledger().with_mut(|li| li.timestamp = 110 + 3600);
+    let result = client.try_settle_proposal(&market_id);
+    assert_eq!(result, Err(Ok(OracleError::ConsensusResultNotFound)));
+
+    let data_hash = BytesN::from_array(&env, &[3u8; 32]);
+    for oracle in &oracles {
+        client.submit_attestation(oracle, &market_id, &0, &data_hash);
+    }
+
+    // A live count is not final: bonds wait for the stored result
+    let result = client.try_settle_proposal(&market_id);
+    assert_eq!(result, Err(Ok(OracleError::ConsensusResultNotFound)));
+    finalize_optimistic_market(&env, &client, &market_id);
+
+    // Oracles side with the disputer, who takes both bonds
+    assert_eq!(client.settle_proposal(&market_id), 0);
+    assert_eq!(token.balance(&disputer), BOND * 2);
+    assert_eq!(token.balance(&proposer), 0);
+    assert_eq!(token.balance(&client.address), 0);
+}
+
+#[test]
+fn test_disputed_proposal_upheld_pays_proposer() {
+    let env = create_test_env();
+    let (client, token, market_id, proposer, disputer, oracles) = setup_optimistic_market(&env);
+
+    client.propose_outcome(&proposer, &market_id, &1);
+    client.dispute_proposal(&disputer, &market_id);
+
+    let data_hash = BytesN::from_array(&env, &[3u8; 32]);
+    for oracle in &oracles {
+        client.submit_attestation(oracle, &market_id, &1, &data_hash);
+    }
+    finalize_optimistic_market(&env, &client, &market_id);
+
+    assert_eq!(client.settle_proposal(&market_id), 1);
+    assert_eq!(token.balance(&proposer), BOND * 2);
+    assert_eq!(token.balance(&disputer), 0);
+}
+
+#[test]
+fn test_optimistic_proposal_rules() {
+    let env = create_test_env();
+    let (client, _, market_id, proposer, disputer, _) = setup_optimistic_market(&env);
+
+    let result = client.try_dispute_proposal(&disputer, &market_id);
+    assert_eq!(result, Err(Ok(OracleError::ProposalNotFound)));
+    let result = client.try_propose_outcome(&proposer, &market_id, &2);
+    assert_eq!(result, Err(Ok(OracleError::InvalidOutcome)));
+
+    client.propose_outcome(&proposer, &market_id, &1);
+    let result = client.try_propose_outcome(&disputer, &market_id, &0);
+    assert_eq!(result, Err(Ok(OracleError::ProposalAlreadyExists)));
+
+    env.ledger().with_mut(|li| li.timestamp = 110 + 3600);
+    let result = client.try_dispute_proposal(&disputer, &market_id);
+    assert_eq!(result, Err(Ok(OracleError::LivenessElapsed)));
+
+    let other_market = BytesN::from_array(&env, &[2u8; 32]);
+    client.register_market(&other_market, &110, &ConsensusMode::Count);
+    let result = client.try_propose_outcome(&proposer, &other_market, &1);
+    assert_eq!(result, Err(Ok(OracleError::OptimisticNotEnabled)));
+    let result = client.try_enable_optimistic(&other_market, &0, &3600);
+    assert_eq!(result, Err(Ok(OracleError::InvalidOptimisticConfig)));
+}
+
+// ===== CONSENSUS THRESHOLD TESTS =====
+
+#[test]
+fn test_set_consensus_threshold() {
+    let env = create_test_env();
+    env.mock_all_auths();
+    let oracle_id = register_oracle(&env);
+    let client = OracleManagerClient::new(&env, &oracle_id);
+    client.initialize(&Address::generate(&env), &2u32);
+
+    for _ in 0..3 {
+        client.register_oracle(&Address::generate(&env), &Symbol::new(&env, "Oracle"));
+    }
+
+    client.set_consensus_threshold(&3);
+    assert_eq!(client.get_consensus_threshold(), 3);
+
+    let result = client.try_set_consensus_threshold(&0);
+    assert_eq!(result, Err(Ok(OracleError::InvalidConsensusThreshold)));
+    let result = client.try_set_consensus_threshold(&4);
+    assert_eq!(result, Err(Ok(OracleError::InvalidConsensusThreshold)));
+    assert_eq!(client.get_consensus_threshold(), 3);
+}
+
+#[test]
+fn test_consensus_threshold_change_spares_existing_markets() {
+    let env = create_test_env();
+    env.mock_all_auths();
+    env.ledger().with_mut(|li| li.timestamp = 100);
+    let oracle_id = register_oracle(&env);
+    let client = OracleManagerClient::new(&env, &oracle_id);
+    client.initialize(&Address::generate(&env), &2u32);
+
+    let oracles: Vec<Address> = (0..3).map(|_| Address::generate(&env)).collect();
+    for oracle in &oracles {
+        client.register_oracle(oracle, &Symbol::new(&env, "Oracle"));
+    }
+
+    let old_market = BytesN::from_array(&env, &[1u8; 32]);
+    client.register_market(&old_market, &110, &ConsensusMode::Count);
+    client.set_consensus_threshold(&3);
+    let new_market = BytesN::from_array(&env, &[2u8; 32]);
+    client.register_market(&new_market, &110, &ConsensusMode::Count);
+
+    assert_eq!(client.get_market_threshold(&old_market), 2);
+    assert_eq!(client.get_market_threshold(&new_market), 3);
+
+    env.ledger().with_mut(|li| li.timestamp = 110);
+    let data_hash = BytesN::from_array(&env, &[3u8; 32]);
+    for oracle in &oracles[..2] {
+        client.submit_attestation(oracle, &old_market, &1, &data_hash);
+        client.submit_attestation(oracle, &new_market, &1, &data_hash);
+    }
+
+    assert_eq!(client.check_consensus(&old_market), (true, 1));
+    assert_eq!(client.check_consensus(&new_market), (false, 0));
+}
+
+#[test]
+fn test_register_market_twice_rejected() {
+    let env = create_test_env();
+    env.mock_all_auths();
+    let oracle_id = register_oracle(&env);
+    let client = OracleManagerClient::new(&env, &oracle_id);
+    client.initialize(&Address::generate(&env), &2u32);
+    for _ in 0..3 {
+        client.register_oracle(&Address::generate(&env), &Symbol::new(&env, "Oracle"));
+    }
+
+    let market_id = BytesN::from_array(&env, &[1u8; 32]);
+    client.register_market(&market_id, &110, &ConsensusMode::Count);
+    client.set_consensus_threshold(&3);
+
+    let result = client.try_register_market(&market_id, &220, &ConsensusMode::Count);
+    assert_eq!(result, Err(Ok(OracleError::MarketAlreadyRegistered)));
+    assert_eq!(client.get_market_threshold(&market_id), 2);
+}
+
+#[test]
+#[should_panic]
+fn test_set_consensus_threshold_requires_admin() {
+    let env = create_test_env();
+    let oracle_id = register_oracle(&env);
+    let client = OracleManagerClient::new(&env, &oracle_id);
+    env.mock_all_auths();
+    client.initialize(&Address::generate(&env), &1u32);
+    client.register_oracle(&Address::generate(&env), &Symbol::new(&env, "Oracle"));
+
+    env.set_auths(&[]);
+    client.set_consensus_threshold(&1);
+}
+
+// ===== OVERRIDE SIGNER TESTS =====
+
+/// Oracle initialized by `admin`, the sole override signer, with one market
+fn setup_override(env: &Env) -> (OracleManagerClient<'static>, Address, BytesN<32>) {
+    env.mock_all_auths();
+    let oracle_id = register_oracle(env);
+    let client = OracleManagerClient::new(env, &oracle_id);
+    let admin = Address::generate(env);
+    client.initialize(&admin, &1u32);
+
+    let market_id = BytesN::from_array(env, &[1u8; 32]);
+    client.register_market(&market_id, &110, &ConsensusMode::Count);
+    (client, admin, market_id)
+}
+
+#[test]
+fn test_signer_management() {
+    let env = create_test_env();
+    let (client, admin, _) = setup_override(&env);
+    assert_eq!(client.get_required_signatures(), 2);
+
+    // The lone initial signer approves alone until a second signer exists
+    let signer2 = Address::generate(&env);
+    let signer3 = Address::generate(&env);
+    client.add_signer(&vec![&env, admin.clone()], &signer2);
+    let result = client.try_add_signer(&vec![&env, admin.clone()], &signer3);
+    assert_eq!(result, Err(Ok(OverrideError::InsufficientApprovers)));
+    client.add_signer(&vec![&env, admin.clone(), signer2.clone()], &signer3);
+    assert_eq!(client.get_admin_signers().len(), 3);
+
+    let result = client.try_add_signer(&vec![&env, admin.clone(), signer2.clone()], &signer2);
+    assert_eq!(result, Err(Ok(OverrideError::SignerAlreadyExists)));
+
+    client.set_required_signatures(&vec![&env, admin.clone(), signer2.clone()], &3);
+    assert_eq!(client.get_required_signatures(), 3);
+    client.set_required_signatures(
+        &vec![&env, admin.clone(), signer2.clone(), signer3.clone()],
+        &2,
+    );
+    assert_eq!(client.get_required_signatures(), 2);
+
+    // Now two distinct signers must approve every change
+    let result = client.try_set_override_cooldown(&vec![&env, admin.clone()], &60);
+    assert_eq!(result, Err(Ok(OverrideError::InsufficientApprovers)));
+    client.set_override_cooldown(&vec![&env, admin.clone(), signer3.clone()], &60);
+    assert_eq!(client.get_override_cooldown(), 60);
+
+    let result =
+        client.try_set_required_signatures(&vec![&env, admin.clone(), signer2.clone()], &4);
+    assert_eq!(result, Err(Ok(OverrideError::InvalidSignerConfig)));
+
+    client.remove_signer(&vec![&env, admin.clone(), signer2.clone()], &signer3);
+    assert_eq!(
+        client.get_admin_signers(),
+        vec![&env, admin.clone(), signer2.clone()]
+    );
+
+    // Removing another would leave fewer signers than required
+    let result = client.try_remove_signer(&vec![&env, admin.clone(), signer2.clone()], &signer2);
+    assert_eq!(result, Err(Ok(OverrideError::InvalidSignerConfig)));
+    let result = client.try_remove_signer(&vec![&env, admin.clone(), signer2.clone()], &signer3);
+    assert_eq!(result, Err(Ok(OverrideError::SignerNotFound)));
+}
+
+#[test]
+fn test_override_rejects_duplicate_and_unknown_approvers() {
+    let env = create_test_env();
+    let (client, admin, market_id) = setup_override(&env);
+    let signer2 = Address::generate(&env);
+    client.add_signer(&vec![&env, admin.clone()], &signer2);
+
+    let justification = BytesN::from_array(&env, &[7u8; 32]);
+    let result = client.try_emergency_override(
+        &vec![&env, admin.clone(), admin.clone()],
+        &market_id,
+        &1,
+        &justification,
+    );
+    assert_eq!(result, Err(Ok(OverrideError::DuplicateApprovers)));
+
+    let result = client.try_emergency_override(
+        &vec![&env, admin.clone(), Address::generate(&env)],
+        &market_id,
+        &1,
+        &justification,
+    );
+    assert_eq!(result, Err(Ok(OverrideError::InvalidApprover)));
+
+    client.emergency_override(
+        &vec![&env, admin.clone(), signer2.clone()],
+        &market_id,
+        &1,
+        &justification,
+    );
+    assert_eq!(client.get_consensus_result(&market_id), 1);
+    assert!(client.is_manual_override(&market_id));
+}
+
+#[test]
+fn test_override_cooldown_is_configurable() {
+    let env = create_test_env();
+    let (client, admin, market_id) = setup_override(&env);
+    let approvers = vec![&env, admin.clone()];
+    client.set_override_cooldown(&approvers, &60);
+
+    env.ledger().with_mut(|li| li.timestamp = 1000);
+    let justification = BytesN::from_array(&env, &[7u8; 32]);
+    client.emergency_override(&approvers, &market_id, &1, &justification);
+
+    env.ledger().with_mut(|li| li.timestamp = 1059);
+    let result = client.try_emergency_override(&approvers, &market_id, &0, &justification);
+    assert_eq!(result, Err(Ok(OverrideError::CooldownNotElapsed)));
+
+    env.ledger().with_mut(|li| li.timestamp = 1060);
+    client.emergency_override(&approvers, &market_id, &0, &justification);
+    assert_eq!(client.get_consensus_result(&market_id), 0);
+}
+
+#[test]
+#[should_panic]
+fn test_add_signer_requires_signer_auth() {
+    let env = create_test_env();
+    let (client, admin, _) = setup_override(&env);
+
+    env.set_auths(&[]);
+    client.add_signer(&vec![&env, admin], &Address::generate(&env));
+}
+
+// ===== OVERRIDE PROPOSAL TESTS =====
+
+/// Override setup with three signers, two of them required
+fn setup_override_proposals(env: &Env) -> (OracleManagerClient<'static>, [Address; 3], BytesN<32>) {
+    let (client, admin, market_id) = setup_override(env);
+    let signer2 = Address::generate(env);
+    let signer3 = Address::generate(env);
+    client.add_signer(&vec![env, admin.clone()], &signer2);
+    client.add_signer(&vec![env, admin.clone(), signer2.clone()], &signer3);
+    env.ledger().with_mut(|li| li.timestamp = 1000);
+    (client, [admin, signer2, signer3], market_id)
+}
+
+#[test]
+fn test_override_proposal_executes_at_threshold() {
+    let env = create_test_env();
+    let (client, signers, market_id) = setup_override_proposals(&env);
+    let justification = BytesN::from_array(&env, &[7u8; 32]);
+
+    let proposal_id = client.propose_override(&signers[0], &market_id, &1, &justification);
+    assert!(!client.is_manual_override(&market_id));
+
+    env.ledger().with_mut(|li| li.timestamp = 1500);
+    assert!(client.approve_override(&signers[2], &proposal_id));
+    assert_eq!(client.get_consensus_result(&market_id), 1);
+
+    let proposal = client.get_override_proposal(&proposal_id).unwrap();
+    assert_eq!(proposal.status, OverrideStatus::Executed);
+
+    let record = client.get_override_record(&market_id).unwrap();
+    assert_eq!(
+        record.approvers,
+        vec![&env, signers[0].clone(), signers[2].clone()]
+    );
+    assert_eq!(
+        record.approvals,
+        vec![
+            &env,
+            OverrideApproval {
+                admin: signers[0].clone(),
+                timestamp: 1000
+            },
+            OverrideApproval {
+                admin: signers[2].clone(),
+                timestamp: 1500
+            },
+        ]
+    );
+
+    let result = client.try_approve_override(&signers[1], &proposal_id);
+    assert_eq!(result, Err(Ok(OverrideError::ProposalNotPending)));
+}
+
+#[test]
+fn test_override_proposal_approval_rules() {
+    let env = create_test_env();
+    let (client, signers, market_id) = setup_override_proposals(&env);
+    let justification = BytesN::from_array(&env, &[7u8; 32]);
+
+    let result =
+        client.try_propose_override(&Address::generate(&env), &market_id, &1, &justification);
+    assert_eq!(result, Err(Ok(OverrideError::InvalidApprover)));
+    let result = client.try_propose_override(&signers[0], &market_id, &2, &justification);
+    assert_eq!(result, Err(Ok(OverrideError::InvalidOutcome)));
+
+    let proposal_id = client.propose_override(&signers[0], &market_id, &1, &justification);
+    let result = client.try_approve_override(&signers[0], &proposal_id);
+    assert_eq!(result, Err(Ok(OverrideError::AlreadyApproved)));
+    let result = client.try_approve_override(&Address::generate(&env), &proposal_id);
+    assert_eq!(result, Err(Ok(OverrideError::InvalidApprover)));
+    let result = client.try_approve_override(&signers[1], &(proposal_id + 1));
+    assert_eq!(result, Err(Ok(OverrideError::ProposalNotFound)));
+    let result = client.try_execute_override(&proposal_id);
+    assert_eq!(result, Err(Ok(OverrideError::InsufficientApprovers)));
+}
+
+#[test]
+fn test_override_proposal_expires() {
+    let env = create_test_env();
+    let (client, signers, market_id) = setup_override_proposals(&env);
+    let justification = BytesN::from_array(&env, &[7u8; 32]);
+
+    let proposal_id = client.propose_override(&signers[0], &market_id, &1, &justification);
+    let expires_at = client
+        .get_override_proposal(&proposal_id)
+        .unwrap()
+        .expires_at;
+
+    env.ledger().with_mut(|li| li.timestamp = expires_at);
+    let result = client.try_approve_override(&signers[1], &proposal_id);
+    assert_eq!(result, Err(Ok(OverrideError::ProposalExpired)));
+    assert!(!client.is_manual_override(&market_id));
+}
+
+#[test]
+fn test_cancel_override_proposal() {
+    let env = create_test_env();
+    let (client, signers, market_id) = setup_override_proposals(&env);
+    let justification = BytesN::from_array(&env, &[7u8; 32]);
+
+    let proposal_id = client.propose_override(&signers[0], &market_id, &1, &justification);
+    let result = client.try_cancel_override(&signers[1], &proposal_id);
+    assert_eq!(result, Err(Ok(OverrideError::NotProposer)));
+
+    client.cancel_override(&signers[0], &proposal_id);
+    assert_eq!(
+        client.get_override_proposal(&proposal_id).unwrap().status,
+        OverrideStatus::Cancelled
+    );
+
+    let result = client.try_approve_override(&signers[1], &proposal_id);
+    assert_eq!(result, Err(Ok(OverrideError::ProposalNotPending)));
+    let result = client.try_cancel_override(&signers[0], &proposal_id);
+    assert_eq!(result, Err(Ok(OverrideError::ProposalNotPending)));
+}
+
+#[test]
+fn test_approved_override_waits_for_cooldown() {
+    let env = create_test_env();
+    let (client, signers, market_id) = setup_override_proposals(&env);
+    let justification = BytesN::from_array(&env, &[7u8; 32]);
+
+    client.emergency_override(
+        &vec![&env, signers[0].clone(), signers[1].clone()],
+        &market_id,
+        &0,
+        &justification,
+    );
+
+    let proposal_id = client.propose_override(&signers[0], &market_id, &1, &justification);
+    assert!(!client.approve_override(&signers[1], &proposal_id));
+    assert_eq!(client.get_consensus_result(&market_id), 0);
+
+    let result = client.try_execute_override(&proposal_id);
+    assert_eq!(result, Err(Ok(OverrideError::CooldownNotElapsed)));
+
+    env.ledger().with_mut(|li| li.timestamp = 1000 + 86400);
+    client.execute_override(&proposal_id);
+    assert_eq!(client.get_consensus_result(&market_id), 1);
+}
+
+// ===== QUERY TESTS =====
+
+#[test]
+fn test_get_attestations_includes_data_hash() {
+    let env = create_test_env();
+    env.mock_all_auths();
+    env.ledger().with_mut(|li| li.timestamp = 100);
+    let oracle_id = register_oracle(&env);
+    let client = OracleManagerClient::new(&env, &oracle_id);
+    client.initialize(&Address::generate(&env), &2u32);
+
+    let oracle1 = Address::generate(&env);
+    let oracle2 = Address::generate(&env);
+    client.register_oracle(&oracle1, &Symbol::new(&env, "Oracle1"));
+    client.register_oracle(&oracle2, &Symbol::new(&env, "Oracle2"));
+
+    let market_id = BytesN::from_array(&env, &[1u8; 32]);
+    client.register_market(&market_id, &110, &ConsensusMode::Count);
+    assert_eq!(client.get_attestations(&market_id).len(), 0);
+
+    env.ledger().with_mut(|li| li.timestamp = 120);
+    let hash1 = BytesN::from_array(&env, &[3u8; 32]);
+    let hash2 = BytesN::from_array(&env, &[4u8; 32]);
+    client.submit_attestation(&oracle1, &market_id, &1, &hash1);
+    client.submit_attestation(&oracle2, &market_id, &0, &hash2);
+
+    let attestations = client.get_attestations(&market_id);
+    assert_eq!(attestations.len(), 2);
+    let first = attestations.get(0).unwrap();
+    assert_eq!(first.attestor, oracle1);
+    assert_eq!(first.outcome, 1);
+    assert_eq!(first.data_hash, hash1);
+    assert_eq!(first.timestamp, 120);
+    assert_eq!(attestations.get(1).unwrap().data_hash, hash2);
+}
+
+#[test]
+fn test_get_oracle_info() {
+    let env = create_test_env();
+    env.mock_all_auths();
+    env.ledger().with_mut(|li| li.timestamp = 100);
+    let oracle_id = register_oracle(&env);
+    let client = OracleManagerClient::new(&env, &oracle_id);
+    client.initialize(&Address::generate(&env), &1u32);
+    let (_, token_admin) = setup_staking(&env, &client, 0, 3600);
+
+    let oracle = Address::generate(&env);
+    client.register_oracle(&oracle, &Symbol::new(&env, "Oracle1"));
+    token_admin.mint(&oracle, &500);
+    client.stake(&oracle, &500);
+
+    let market_id = BytesN::from_array(&env, &[1u8; 32]);
+    client.register_market(&market_id, &110, &ConsensusMode::Count);
+    env.ledger().with_mut(|li| li.timestamp = 110);
+    client.submit_attestation(
+        &oracle,
+        &market_id,
+        &1,
+        &BytesN::from_array(&env, &[3u8; 32]),
+    );
+
+    let challenger = Address::generate(&env);
+    token_admin.mint(&challenger, &1000);
+    client.challenge_attestation(
+        &challenger,
+        &oracle,
+        &market_id,
+        &Symbol::new(&env, "wrong"),
+    );
+    client.resolve_challenge(&oracle, &market_id, &false);
+
+    let info = client.get_oracle_info(&oracle);
+    assert_eq!(info.oracle, oracle);
+    assert_eq!(info.name, Symbol::new(&env, "Oracle1"));
+    assert!(info.active);
+    assert_eq!(info.joined_at, 100);
+    assert_eq!(info.accuracy, 100);
+    assert_eq!(info.stake, 500);
+    assert_eq!(info.stats.attestations, 1);
+    assert_eq!(info.stats.challenges_received, 1);
+    assert_eq!(info.stats.challenges_won, 1);
+    assert_eq!(info.stats.challenges_lost, 0);
+
+    let result = client.try_get_oracle_info(&Address::generate(&env));
+    assert_eq!(result, Err(Ok(OracleError::OracleNotRegistered)));
+}
+
+#[test]
+fn test_get_active_oracles_sorted_by_reputation() {
+    let env = create_test_env();
+    env.mock_all_auths();
+    env.ledger().with_mut(|li| li.timestamp = 100);
+    let oracle_id = register_oracle(&env);
+    let client = OracleManagerClient::new(&env, &oracle_id);
+    client.initialize(&Address::generate(&env), &1u32);
+    let (_, token_admin) = setup_staking(&env, &client, 0, 3600);
+
+    let oracles: Vec<Address> = (0..4).map(|_| Address::generate(&env)).collect();
+    for oracle in &oracles {
+        client.register_oracle(oracle, &Symbol::new(&env, "Oracle"));
+    }
+    client.deregister_oracle(&oracles[3]);
+
+    // A valid challenge costs oracles[1] reputation
+    let market_id = BytesN::from_array(&env, &[1u8; 32]);
+    client.register_market(&market_id, &110, &ConsensusMode::Count);
+    env.ledger().with_mut(|li| li.timestamp = 110);
+    client.submit_attestation(
+        &oracles[1],
+        &market_id,
+        &1,
+        &BytesN::from_array(&env, &[3u8; 32]),
+    );
+    let challenger = Address::generate(&env);
+    token_admin.mint(&challenger, &1000);
+    client.challenge_attestation(
+        &challenger,
+        &oracles[1],
+        &market_id,
+        &Symbol::new(&env, "wrong"),
+    );
+    client.resolve_challenge(&oracles[1], &market_id, &true);
+
+    assert_eq!(
+        client.get_active_oracles(),
+        vec![
+            &env,
+            oracles[0].clone(),
+            oracles[2].clone(),
+            oracles[1].clone()
+        ]
+    );
+    assert_eq!(client.get_oracles().len(), 4);
+
+    let report = client.get_consensus_report();
+    assert_eq!(report.markets_resolved, 0);
+    assert_eq!(report.total_attestations, 1);
+    assert_eq!(report.total_challenges, 1);
+    assert_eq!(report.successful_challenges, 1);
+    assert_eq!(report.oracles.len(), 4);
+    assert_eq!(report.oracles.get(1).unwrap().stats.challenges_lost, 1);
+}
+
+#[test]
+fn test_consensus_report_counts_resolved_markets_once() {
+    let env = create_test_env();
+    let (client, admin, market_id) = setup_override(&env);
+    let justification = BytesN::from_array(&env, &[7u8; 32]);
+    client.set_override_cooldown(&vec![&env, admin.clone()], &0);
+
+    client.emergency_override(&vec![&env, admin.clone()], &market_id, &1, &justification);
+    client.emergency_override(&vec![&env, admin.clone()], &market_id, &0, &justification);
+
+    assert_eq!(client.get_consensus_report().markets_resolved, 1);
 }
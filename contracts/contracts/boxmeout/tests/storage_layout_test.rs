@@ -0,0 +1,531 @@
+//! Pins the on-chain encoding of every storage key.
+//!
+//! Renaming a variant or reordering its fields moves the data it points to;
+//! if one of these tests fails, the change needs a schema migration.
+
+use soroban_sdk::{
+    symbol_short, testutils::Address as _, vec, xdr::ToXdr, Address, Bytes, BytesN, Env, IntoVal,
+    Symbol, Val, Vec,
+};
+
+use boxmeout::{
+    amm::DataKey as AmmKey, factory::DataKey as FactoryKey, market::DataKey as MarketKey,
+    oracle::DataKey as OracleKey, treasury::DataKey as TreasuryKey, treasury::FeeKind,
+    treasury::FeePool, upgrade::UpgradeKey,
+};
+
+/// Encoding of an enum variant: `[Symbol(name), fields...]`
+fn expected(env: &Env, name: &str, fields: Vec<Val>) -> Bytes {
+    let mut encoded: Vec<Val> = vec![env, Symbol::new(env, name).into_val(env)];
+    encoded.append(&fields);
+    encoded.to_xdr(env)
+}
+
+/// Check each key's encoding and that no two keys (including the shared
+/// upgrade keys) collide
+fn assert_layout(env: &Env, keys: Vec<(Bytes, Bytes)>) {
+    let mut all: Vec<Bytes> = Vec::new(env);
+    for (actual, want) in keys.iter() {
+        assert_eq!(actual, want);
+        all.push_back(actual);
+    }
+    for key in [
+        UpgradeKey::SchemaVersion,
+        UpgradeKey::UpgradeDelay,
+        UpgradeKey::PendingUpgrade,
+        UpgradeKey::PendingUpgradeDelay,
+    ] {
+        all.push_back(key.to_xdr(env));
+    }
+    for i in 0..all.len() {
+        for j in (i + 1)..all.len() {
+            assert_ne!(
+                all.get(i),
+                all.get(j),
+                "storage keys {} and {} collide",
+                i,
+                j
+            );
+        }
+    }
+}
+
+#[test]
+fn test_upgrade_key_layout() {
+    let env = Env::default();
+    let none: Vec<Val> = Vec::new(&env);
+    assert_eq!(
+        UpgradeKey::SchemaVersion.to_xdr(&env),
+        expected(&env, "SchemaVersion", none.clone())
+    );
+    assert_eq!(
+        UpgradeKey::UpgradeDelay.to_xdr(&env),
+        expected(&env, "UpgradeDelay", none.clone())
+    );
+    assert_eq!(
+        UpgradeKey::PendingUpgrade.to_xdr(&env),
+        expected(&env, "PendingUpgrade", none.clone())
+    );
+    assert_eq!(
+        UpgradeKey::PendingUpgradeDelay.to_xdr(&env),
+        expected(&env, "PendingUpgradeDelay", none)
+    );
+    assert_layout(&env, Vec::new(&env));
+}
+
+#[test]
+fn test_amm_key_layout() {
+    let env = Env::default();
+    let m = BytesN::from_array(&env, &[1u8; 32]);
+    let user = Address::generate(&env);
+    let none: Vec<Val> = Vec::new(&env);
+    let market: Vec<Val> = vec![&env, m.into_val(&env)];
+
+    assert_layout(
+        &env,
+        vec![
+            &env,
+            (
+                AmmKey::Admin.to_xdr(&env),
+                expected(&env, "Admin", none.clone()),
+            ),
+            (
+                AmmKey::Factory.to_xdr(&env),
+                expected(&env, "Factory", none.clone()),
+            ),
+            (
+                AmmKey::Usdc.to_xdr(&env),
+                expected(&env, "Usdc", none.clone()),
+            ),
+            (
+                AmmKey::MaxLiquidityCap.to_xdr(&env),
+                expected(&env, "MaxLiquidityCap", none.clone()),
+            ),
+            (
+                AmmKey::SlippageProtection.to_xdr(&env),
+                expected(&env, "SlippageProtection", none.clone()),
+            ),
+            (
+                AmmKey::TradingFee.to_xdr(&env),
+                expected(&env, "TradingFee", none.clone()),
+            ),
+            (
+                AmmKey::PricingModel.to_xdr(&env),
+                expected(&env, "PricingModel", none),
+            ),
+            (
+                AmmKey::PoolExists(m.clone()).to_xdr(&env),
+                expected(&env, "PoolExists", market.clone()),
+            ),
+            (
+                AmmKey::PoolYesReserve(m.clone()).to_xdr(&env),
+                expected(&env, "PoolYesReserve", market.clone()),
+            ),
+            (
+                AmmKey::PoolNoReserve(m.clone()).to_xdr(&env),
+                expected(&env, "PoolNoReserve", market.clone()),
+            ),
+            (
+                AmmKey::PoolK(m.clone()).to_xdr(&env),
+                expected(&env, "PoolK", market.clone()),
+            ),
+            (
+                AmmKey::PoolLpSupply(m.clone()).to_xdr(&env),
+                expected(&env, "PoolLpSupply", market.clone()),
+            ),
+            (
+                AmmKey::PoolLpTokens(m.clone(), user.clone()).to_xdr(&env),
+                expected(
+                    &env,
+                    "PoolLpTokens",
+                    vec![&env, m.into_val(&env), user.into_val(&env)],
+                ),
+            ),
+            (
+                AmmKey::UserShares(m.clone(), user.clone(), 1).to_xdr(&env),
+                expected(
+                    &env,
+                    "UserShares",
+                    vec![
+                        &env,
+                        m.into_val(&env),
+                        user.into_val(&env),
+                        1u32.into_val(&env),
+                    ],
+                ),
+            ),
+            (
+                AmmKey::TradeCount(m.clone()).to_xdr(&env),
+                expected(&env, "TradeCount", market),
+            ),
+        ],
+    );
+}
+
+#[test]
+fn test_amm_share_keys_distinct_per_outcome() {
+    let env = Env::default();
+    let m = BytesN::from_array(&env, &[1u8; 32]);
+    let user = Address::generate(&env);
+    assert_ne!(
+        AmmKey::UserShares(m.clone(), user.clone(), 0).to_xdr(&env),
+        AmmKey::UserShares(m, user, 1).to_xdr(&env)
+    );
+}
+
+#[test]
+fn test_factory_key_layout() {
+    let env = Env::default();
+    let m = BytesN::from_array(&env, &[1u8; 32]);
+    let market_contract = Address::generate(&env);
+    let none: Vec<Val> = Vec::new(&env);
+    let market: Vec<Val> = vec![&env, m.into_val(&env)];
+
+    assert_layout(
+        &env,
+        vec![
+            &env,
+            (
+                FactoryKey::Admin.to_xdr(&env),
+                expected(&env, "Admin", none.clone()),
+            ),
+            (
+                FactoryKey::Usdc.to_xdr(&env),
+                expected(&env, "Usdc", none.clone()),
+            ),
+            (
+                FactoryKey::Treasury.to_xdr(&env),
+                expected(&env, "Treasury", none.clone()),
+            ),
+            (
+                FactoryKey::MarketCount.to_xdr(&env),
+                expected(&env, "MarketCount", none),
+            ),
+            (
+                FactoryKey::Market(m.clone()).to_xdr(&env),
+                expected(&env, "Market", market.clone()),
+            ),
+            (
+                FactoryKey::MarketMeta(m.clone()).to_xdr(&env),
+                expected(&env, "MarketMeta", market),
+            ),
+            (
+                FactoryKey::MarketUpgrade(market_contract.clone()).to_xdr(&env),
+                expected(
+                    &env,
+                    "MarketUpgrade",
+                    vec![&env, market_contract.into_val(&env)],
+                ),
+            ),
+        ],
+    );
+}
+
+#[test]
+fn test_treasury_key_layout() {
+    let env = Env::default();
+    let user = Address::generate(&env);
+    let token = Address::generate(&env);
+    let none: Vec<Val> = Vec::new(&env);
+
+    assert_layout(
+        &env,
+        vec![
+            &env,
+            (
+                TreasuryKey::Admin.to_xdr(&env),
+                expected(&env, "Admin", none.clone()),
+            ),
+            (
+                TreasuryKey::Usdc.to_xdr(&env),
+                expected(&env, "Usdc", none.clone()),
+            ),
+            (
+                TreasuryKey::Factory.to_xdr(&env),
+                expected(&env, "Factory", none.clone()),
+            ),
+            (
+                TreasuryKey::LeaderboardEpochCount.to_xdr(&env),
+                expected(&env, "LeaderboardEpochCount", none.clone()),
+            ),
+            (
+                TreasuryKey::WithdrawalPolicy.to_xdr(&env),
+                expected(&env, "WithdrawalPolicy", none.clone()),
+            ),
+            (
+                TreasuryKey::WithdrawalCount.to_xdr(&env),
+                expected(&env, "WithdrawalCount", none),
+            ),
+            (
+                TreasuryKey::Withdrawal(3).to_xdr(&env),
+                expected(&env, "Withdrawal", vec![&env, 3u32.into_val(&env)]),
+            ),
+            (
+                TreasuryKey::BudgetSpender(symbol_short!("ops"), user.clone()).to_xdr(&env),
+                expected(
+                    &env,
+                    "BudgetSpender",
+                    vec![
+                        &env,
+                        symbol_short!("ops").into_val(&env),
+                        user.into_val(&env),
+                    ],
+                ),
+            ),
+            (
+                TreasuryKey::StreamCount.to_xdr(&env),
+                expected(&env, "StreamCount", Vec::new(&env)),
+            ),
+            (
+                TreasuryKey::Stream(3).to_xdr(&env),
+                expected(&env, "Stream", vec![&env, 3u32.into_val(&env)]),
+            ),
+            (
+                TreasuryKey::TokenReserved(token.clone()).to_xdr(&env),
+                expected(&env, "TokenReserved", vec![&env, token.into_val(&env)]),
+            ),
+            (
+                TreasuryKey::FeeSplits.to_xdr(&env),
+                expected(&env, "FeeSplits", Vec::new(&env)),
+            ),
+            (
+                TreasuryKey::Buckets.to_xdr(&env),
+                expected(&env, "Buckets", Vec::new(&env)),
+            ),
+            (
+                TreasuryKey::TokenBucket(symbol_short!("partner"), token.clone()).to_xdr(&env),
+                expected(
+                    &env,
+                    "TokenBucket",
+                    vec![
+                        &env,
+                        symbol_short!("partner").into_val(&env),
+                        token.into_val(&env),
+                    ],
+                ),
+            ),
+            (
+                TreasuryKey::FeeSource(user.clone()).to_xdr(&env),
+                expected(&env, "FeeSource", vec![&env, user.into_val(&env)]),
+            ),
+            (
+                TreasuryKey::TokenKindFees(FeeKind::Winnings, token.clone()).to_xdr(&env),
+                expected(
+                    &env,
+                    "TokenKindFees",
+                    vec![&env, FeeKind::Winnings.into_val(&env), token.into_val(&env)],
+                ),
+            ),
+            (
+                TreasuryKey::InsuredContract(user.clone()).to_xdr(&env),
+                expected(&env, "InsuredContract", vec![&env, user.into_val(&env)]),
+            ),
+            (
+                TreasuryKey::ShortfallDrawCount.to_xdr(&env),
+                expected(&env, "ShortfallDrawCount", Vec::new(&env)),
+            ),
+            (
+                TreasuryKey::ShortfallDraw(3).to_xdr(&env),
+                expected(&env, "ShortfallDraw", vec![&env, 3u32.into_val(&env)]),
+            ),
+            (
+                TreasuryKey::Tokens.to_xdr(&env),
+                expected(&env, "Tokens", Vec::new(&env)),
+            ),
+            (
+                TreasuryKey::TokenPool(FeePool::Platform, token.clone()).to_xdr(&env),
+                expected(
+                    &env,
+                    "TokenPool",
+                    vec![&env, FeePool::Platform.into_val(&env), token.into_val(&env)],
+                ),
+            ),
+            (
+                TreasuryKey::TokenTotalFees(token.clone()).to_xdr(&env),
+                expected(&env, "TokenTotalFees", vec![&env, token.into_val(&env)]),
+            ),
+            (
+                TreasuryKey::TokenCreatorOwed(token.clone()).to_xdr(&env),
+                expected(&env, "TokenCreatorOwed", vec![&env, token.into_val(&env)]),
+            ),
+            (
+                TreasuryKey::TokenCreatorEarnings(user.clone(), token.clone()).to_xdr(&env),
+                expected(
+                    &env,
+                    "TokenCreatorEarnings",
+                    vec![&env, user.into_val(&env), token.into_val(&env)],
+                ),
+            ),
+            (
+                TreasuryKey::TokenWithdrawalWindow(token.clone()).to_xdr(&env),
+                expected(
+                    &env,
+                    "TokenWithdrawalWindow",
+                    vec![&env, token.into_val(&env)],
+                ),
+            ),
+            (
+                TreasuryKey::TokenBudget(symbol_short!("ops"), token.clone()).to_xdr(&env),
+                expected(
+                    &env,
+                    "TokenBudget",
+                    vec![
+                        &env,
+                        symbol_short!("ops").into_val(&env),
+                        token.into_val(&env),
+                    ],
+                ),
+            ),
+            (
+                TreasuryKey::LeaderboardEpoch(3).to_xdr(&env),
+                expected(&env, "LeaderboardEpoch", vec![&env, 3u32.into_val(&env)]),
+            ),
+            (
+                TreasuryKey::LeaderboardClaimed(3, user.clone()).to_xdr(&env),
+                expected(
+                    &env,
+                    "LeaderboardClaimed",
+                    vec![&env, 3u32.into_val(&env), user.into_val(&env)],
+                ),
+            ),
+        ],
+    );
+}
+
+#[test]
+fn test_market_key_layout() {
+    let env = Env::default();
+    let m = BytesN::from_array(&env, &[1u8; 32]);
+    let user = Address::generate(&env);
+    let none: Vec<Val> = Vec::new(&env);
+    let by_user: Vec<Val> = vec![&env, user.into_val(&env)];
+
+    let mut keys = Vec::new(&env);
+    for (key, name) in [
+        (MarketKey::MarketId, "MarketId"),
+        (MarketKey::Creator, "Creator"),
+        (MarketKey::Factory, "Factory"),
+        (MarketKey::Usdc, "Usdc"),
+        (MarketKey::Oracle, "Oracle"),
+        (MarketKey::ClosingTime, "ClosingTime"),
+        (MarketKey::ResolutionTime, "ResolutionTime"),
+        (MarketKey::MarketState, "MarketState"),
+        (MarketKey::YesPool, "YesPool"),
+        (MarketKey::NoPool, "NoPool"),
+        (MarketKey::TotalVolume, "TotalVolume"),
+        (MarketKey::PendingCount, "PendingCount"),
+        (MarketKey::WinningOutcome, "WinningOutcome"),
+        (MarketKey::WinnerShares, "WinnerShares"),
+        (MarketKey::LoserShares, "LoserShares"),
+        (MarketKey::Participants, "Participants"),
+    ] {
+        keys.push_back((key.to_xdr(&env), expected(&env, name, none.clone())));
+    }
+    keys.push_back((
+        MarketKey::Commit(user.clone()).to_xdr(&env),
+        expected(&env, "Commit", by_user.clone()),
+    ));
+    keys.push_back((
+        MarketKey::Prediction(user.clone()).to_xdr(&env),
+        expected(&env, "Prediction", by_user),
+    ));
+    keys.push_back((
+        MarketKey::Dispute(m.clone()).to_xdr(&env),
+        expected(&env, "Dispute", vec![&env, m.into_val(&env)]),
+    ));
+
+    assert_layout(&env, keys);
+}
+
+#[test]
+fn test_oracle_key_layout() {
+    let env = Env::default();
+    let m = BytesN::from_array(&env, &[1u8; 32]);
+    let oracle = Address::generate(&env);
+    let none: Vec<Val> = Vec::new(&env);
+    let by_oracle: Vec<Val> = vec![&env, oracle.into_val(&env)];
+    let by_market: Vec<Val> = vec![&env, m.into_val(&env)];
+    let by_vote: Vec<Val> = vec![&env, m.into_val(&env), oracle.into_val(&env)];
+
+    let mut keys = Vec::new(&env);
+    for (key, name) in [
+        (OracleKey::Admin, "Admin"),
+        (OracleKey::RequiredConsensus, "RequiredConsensus"),
+        (OracleKey::OracleCount, "OracleCount"),
+        (OracleKey::AdminSigners, "AdminSigners"),
+        (OracleKey::RequiredSignatures, "RequiredSignatures"),
+        (OracleKey::LastOverrideTime, "LastOverrideTime"),
+        (OracleKey::OverrideCooldown, "OverrideCooldown"),
+        (OracleKey::StakeToken, "StakeToken"),
+        (OracleKey::MinStake, "MinStake"),
+        (OracleKey::UnbondingPeriod, "UnbondingPeriod"),
+        (OracleKey::OverrideProposalCount, "OverrideProposalCount"),
+        (OracleKey::OracleList, "OracleList"),
+        (OracleKey::MarketsResolved, "MarketsResolved"),
+    ] {
+        keys.push_back((key.to_xdr(&env), expected(&env, name, none.clone())));
+    }
+    for (key, name) in [
+        (OracleKey::Oracle(oracle.clone()), "Oracle"),
+        (OracleKey::OracleName(oracle.clone()), "OracleName"),
+        (OracleKey::OracleAccuracy(oracle.clone()), "OracleAccuracy"),
+        (OracleKey::OracleStake(oracle.clone()), "OracleStake"),
+        (
+            OracleKey::OracleTimestamp(oracle.clone()),
+            "OracleTimestamp",
+        ),
+        (
+            OracleKey::ChallengerReward(oracle.clone()),
+            "ChallengerReward",
+        ),
+        (OracleKey::OracleReward(oracle.clone()), "OracleReward"),
+        (OracleKey::Unbonding(oracle.clone()), "Unbonding"),
+        (OracleKey::OpenChallenges(oracle.clone()), "OpenChallenges"),
+        (OracleKey::OracleStats(oracle.clone()), "OracleStats"),
+    ] {
+        keys.push_back((key.to_xdr(&env), expected(&env, name, by_oracle.clone())));
+    }
+    for (key, name) in [
+        (OracleKey::MarketResTime(m.clone()), "MarketResTime"),
+        (OracleKey::AttestCountYes(m.clone()), "AttestCountYes"),
+        (OracleKey::AttestCountNo(m.clone()), "AttestCountNo"),
+        (OracleKey::MarketConsensus(m.clone()), "MarketConsensus"),
+        (OracleKey::RevealWindow(m.clone()), "RevealWindow"),
+        (OracleKey::Committers(m.clone()), "Committers"),
+        (OracleKey::RevealsSettled(m.clone()), "RevealsSettled"),
+        (OracleKey::MarketThreshold(m.clone()), "MarketThreshold"),
+        (OracleKey::Optimistic(m.clone()), "Optimistic"),
+        (OracleKey::Proposal(m.clone()), "Proposal"),
+        (OracleKey::Voters(m.clone()), "Voters"),
+        (OracleKey::MarketChallenged(m.clone()), "MarketChallenged"),
+        (OracleKey::ConsensusResult(m.clone()), "ConsensusResult"),
+        (OracleKey::ManualOverride(m.clone()), "ManualOverride"),
+        (OracleKey::OverrideRecord(m.clone()), "OverrideRecord"),
+    ] {
+        keys.push_back((key.to_xdr(&env), expected(&env, name, by_market.clone())));
+    }
+    for (key, name) in [
+        (OracleKey::Vote(m.clone(), oracle.clone()), "Vote"),
+        (
+            OracleKey::VoteWeight(m.clone(), oracle.clone()),
+            "VoteWeight",
+        ),
+        (
+            OracleKey::Attestation(m.clone(), oracle.clone()),
+            "Attestation",
+        ),
+        (OracleKey::Challenge(m.clone(), oracle.clone()), "Challenge"),
+        (
+            OracleKey::AttestCommit(m.clone(), oracle.clone()),
+            "AttestCommit",
+        ),
+    ] {
+        keys.push_back((key.to_xdr(&env), expected(&env, name, by_vote.clone())));
+    }
+    keys.push_back((
+        OracleKey::OverrideProposal(7).to_xdr(&env),
+        expected(&env, "OverrideProposal", vec![&env, 7u32.into_val(&env)]),
+    ));
+
+    assert_layout(&env, keys);
+}
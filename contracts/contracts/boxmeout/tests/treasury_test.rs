@@ -1,6 +1,13 @@
-use soroban_sdk::{testutils::Address as _, Address, Env};
+use soroban_sdk::{
+    symbol_short,
+    testutils::{Address as _, Ledger},
+    token, vec, Address, Bytes, BytesN, Env, Symbol,
+};
 
-use boxmeout::treasury::{Treasury, TreasuryClient};
+use boxmeout::treasury::{
+    FeeKind, FeePool, FeeSplit, Treasury, TreasuryClient, TreasuryError, WithdrawalPolicy,
+    WithdrawalStatus,
+};
 
 fn create_test_env() -> Env {
     Env::default()
@@ -24,9 +31,9 @@ fn test_treasury_initialize() {
     client.initialize(&admin, &usdc_contract, &factory);
 
     // Verify fee pools initialized to 0
-    let platform_fees = client.get_platform_fees();
-    let leaderboard_fees = client.get_leaderboard_fees();
-    let creator_fees = client.get_creator_fees();
+    let platform_fees = client.get_platform_fees(&usdc_contract);
+    let leaderboard_fees = client.get_leaderboard_fees(&usdc_contract);
+    let creator_fees = client.get_creator_fees(&usdc_contract);
 
     assert_eq!(platform_fees, 0);
     assert_eq!(leaderboard_fees, 0);
@@ -50,11 +57,162 @@ fn test_distribute_rewards_non_admin_fails() {
     // Should panic
 }
 
+/// Treasury with a real USDC token and 10_000 in deposited fees split
+/// 50/30/20 with no insurance share (3_000 in the leaderboard pool)
+fn setup_funded_treasury(
+    env: &Env,
+) -> (
+    TreasuryClient<'static>,
+    token::TokenClient<'static>,
+    Address,
+) {
+    env.mock_all_auths();
+    let client = TreasuryClient::new(env, &register_treasury(env));
+    let usdc = env
+        .register_stellar_asset_contract_v2(Address::generate(env))
+        .address();
+    let admin = Address::generate(env);
+    client.initialize(&admin, &usdc, &Address::generate(env));
+    let split = |name: &str, bps: u32| FeeSplit {
+        bucket: Symbol::new(env, name),
+        bps,
+    };
+    client.set_fee_splits(
+        &vec![
+            env,
+            split("platform", 5_000),
+            split("leaderboard", 3_000),
+            split("creator", 2_000),
+        ],
+        &Symbol::new(env, "creator"),
+    );
+
+    let source = fee_source(env, &client);
+    token::StellarAssetClient::new(env, &usdc).mint(&source, &10_000);
+    client.deposit_fees(&source, &usdc, &FeeKind::Trading, &None, &10_000);
+    (client, token::TokenClient::new(env, &usdc), admin)
+}
+
+/// Address registered to call `deposit_fees` for every kind but `Creation`
+fn fee_source(env: &Env, client: &TreasuryClient) -> Address {
+    let source = Address::generate(env);
+    client.set_fee_source(
+        &source,
+        &vec![env, FeeKind::Trading, FeeKind::Winnings, FeeKind::Slashing],
+    );
+    source
+}
+
+/// Parent of two Merkle nodes, hashed in sorted order
+fn hash_pair(env: &Env, a: &BytesN<32>, b: &BytesN<32>) -> BytesN<32> {
+    let (first, second) = if a.to_array() <= b.to_array() {
+        (a, b)
+    } else {
+        (b, a)
+    };
+    let mut input = Bytes::new(env);
+    input.extend_from_array(&first.to_array());
+    input.extend_from_array(&second.to_array());
+    env.crypto().sha256(&input).into()
+}
+
 #[test]
 fn test_distribute_leaderboard_rewards() {
-    // TODO: Implement when distribute_leaderboard_rewards is ready
-    // Test distributing to top 10 users
-    // Test proportional distribution
+    let env = create_test_env();
+    let (client, usdc, _) = setup_funded_treasury(&env);
+    assert_eq!(client.get_leaderboard_fees(&usdc.address), 3_000);
+
+    // Three winners: root = H(H(a, b), c)
+    let winners = [
+        (Address::generate(&env), 1_000i128),
+        (Address::generate(&env), 700i128),
+        (Address::generate(&env), 300i128),
+    ];
+    let leaves: std::vec::Vec<BytesN<32>> = winners
+        .iter()
+        .map(|(user, amount)| client.leaderboard_leaf(user, amount))
+        .collect();
+    let ab = hash_pair(&env, &leaves[0], &leaves[1]);
+    let root = hash_pair(&env, &ab, &leaves[2]);
+
+    let epoch = client.distribute_leaderboard_rewards(&usdc.address, &root, &2_000);
+    assert_eq!(epoch, 0);
+    assert_eq!(client.get_leaderboard_fees(&usdc.address), 1_000);
+
+    let proofs = [
+        vec![&env, leaves[1].clone(), leaves[2].clone()],
+        vec![&env, leaves[0].clone(), leaves[2].clone()],
+        vec![&env, ab.clone()],
+    ];
+    for ((user, amount), proof) in winners.iter().zip(proofs.iter()) {
+        client.claim_leaderboard_reward(user, &epoch, amount, proof);
+        assert_eq!(usdc.balance(user), *amount);
+        assert!(client.has_claimed_leaderboard(&epoch, user));
+    }
+    assert_eq!(client.get_leaderboard_epoch(&epoch).unwrap().claimed, 2_000);
+
+    let result = client.try_claim_leaderboard_reward(&winners[0].0, &epoch, &1_000, &proofs[0]);
+    assert_eq!(result, Err(Ok(TreasuryError::AlreadyClaimed)));
+}
+
+#[test]
+fn test_claim_leaderboard_reward_rejects_bad_proofs() {
+    let env = create_test_env();
+    let (client, usdc, _) = setup_funded_treasury(&env);
+
+    let user = Address::generate(&env);
+    let other = Address::generate(&env);
+    let user_leaf = client.leaderboard_leaf(&user, &500);
+    let other_leaf = client.leaderboard_leaf(&other, &500);
+    let root = hash_pair(&env, &user_leaf, &other_leaf);
+    let epoch = client.distribute_leaderboard_rewards(&usdc.address, &root, &1_000);
+
+    // Claiming more than the leaf commits to
+    let result =
+        client.try_claim_leaderboard_reward(&user, &epoch, &900, &vec![&env, other_leaf.clone()]);
+    assert_eq!(result, Err(Ok(TreasuryError::InvalidProof)));
+    // Someone else's proof
+    let stranger = Address::generate(&env);
+    let result = client.try_claim_leaderboard_reward(
+        &stranger,
+        &epoch,
+        &500,
+        &vec![&env, other_leaf.clone()],
+    );
+    assert_eq!(result, Err(Ok(TreasuryError::InvalidProof)));
+    let result =
+        client.try_claim_leaderboard_reward(&user, &(epoch + 1), &500, &vec![&env, other_leaf]);
+    assert_eq!(result, Err(Ok(TreasuryError::EpochNotFound)));
+    assert_eq!(usdc.balance(&user), 0);
+}
+
+#[test]
+fn test_distribute_leaderboard_rewards_limited_to_pool() {
+    let env = create_test_env();
+    let (client, usdc, _) = setup_funded_treasury(&env);
+    let root = BytesN::from_array(&env, &[1u8; 32]);
+
+    let result = client.try_distribute_leaderboard_rewards(&usdc.address, &root, &3_001);
+    assert_eq!(result, Err(Ok(TreasuryError::InsufficientBalance)));
+    let result = client.try_distribute_leaderboard_rewards(&usdc.address, &root, &0);
+    assert_eq!(result, Err(Ok(TreasuryError::InvalidAmount)));
+
+    client.distribute_leaderboard_rewards(&usdc.address, &root, &3_000);
+    assert_eq!(client.get_leaderboard_fees(&usdc.address), 0);
+}
+
+#[test]
+#[should_panic]
+fn test_distribute_leaderboard_rewards_requires_admin() {
+    let env = create_test_env();
+    let (client, usdc, _) = setup_funded_treasury(&env);
+
+    env.set_auths(&[]);
+    client.distribute_leaderboard_rewards(
+        &usdc.address,
+        &BytesN::from_array(&env, &[1u8; 32]),
+        &100,
+    );
 }
 
 #[test]
@@ -63,3 +221,1180 @@ fn test_update_fee_structure() {
     // Admin updates fee percentages
     // Non-admin cannot update
 }
+
+#[test]
+fn test_creator_fee_attribution_and_claim() {
+    let env = create_test_env();
+    let (client, usdc, _) = setup_funded_treasury(&env);
+    let usdc_admin = token::StellarAssetClient::new(&env, &usdc.address);
+
+    let source = fee_source(&env, &client);
+    let creator = Address::generate(&env);
+    usdc_admin.mint(&source, &2_000);
+    client.deposit_fees(
+        &source,
+        &usdc.address,
+        &FeeKind::Trading,
+        &Some(creator.clone()),
+        &1_000,
+    );
+    client.deposit_fees(
+        &source,
+        &usdc.address,
+        &FeeKind::Trading,
+        &Some(creator.clone()),
+        &1_000,
+    );
+
+    // 20% creator share of each deposit; unattributed pool untouched
+    let earnings = client.get_creator_earnings(&creator, &usdc.address);
+    assert_eq!(earnings.lifetime, 400);
+    assert_eq!(earnings.unclaimed, 400);
+    assert_eq!(client.get_creator_owed(&usdc.address), 400);
+    assert_eq!(client.get_creator_fees(&usdc.address), 2_000);
+
+    assert_eq!(client.claim_creator_rewards(&creator, &usdc.address), 400);
+    assert_eq!(usdc.balance(&creator), 400);
+    let earnings = client.get_creator_earnings(&creator, &usdc.address);
+    assert_eq!(earnings.lifetime, 400);
+    assert_eq!(earnings.unclaimed, 0);
+    assert_eq!(client.get_creator_owed(&usdc.address), 0);
+
+    let result = client.try_claim_creator_rewards(&creator, &usdc.address);
+    assert_eq!(result, Err(Ok(TreasuryError::NothingToClaim)));
+}
+
+#[test]
+fn test_distribute_creator_rewards_cannot_spend_attributed_fees() {
+    let env = create_test_env();
+    let (client, usdc, admin) = setup_funded_treasury(&env);
+    let usdc_admin = token::StellarAssetClient::new(&env, &usdc.address);
+
+    let source = fee_source(&env, &client);
+    let creator = Address::generate(&env);
+    usdc_admin.mint(&source, &10_000);
+    client.deposit_fees(
+        &source,
+        &usdc.address,
+        &FeeKind::Trading,
+        &Some(creator.clone()),
+        &10_000,
+    );
+
+    // Only the 2_000 unattributed share from setup can be distributed
+    let result = client.try_distribute_creator_rewards(
+        &admin,
+        &usdc.address,
+        &vec![&env, (Address::generate(&env), 2_001i128)],
+    );
+    assert_eq!(result, Err(Ok(TreasuryError::InsufficientBalance)));
+
+    // A negative entry cannot offset an overdraw
+    let result = client.try_distribute_creator_rewards(
+        &admin,
+        &usdc.address,
+        &vec![
+            &env,
+            (Address::generate(&env), 2_500i128),
+            (Address::generate(&env), -500i128),
+        ],
+    );
+    assert_eq!(result, Err(Ok(TreasuryError::InvalidAmount)));
+    let result = client.try_distribute_creator_rewards(
+        &admin,
+        &usdc.address,
+        &vec![&env, (Address::generate(&env), 0i128)],
+    );
+    assert_eq!(result, Err(Ok(TreasuryError::InvalidAmount)));
+    assert_eq!(
+        client
+            .get_creator_earnings(&creator, &usdc.address)
+            .unclaimed,
+        2_000
+    );
+}
+
+/// Funded treasury with two of three signers required, a one hour delay and
+/// a 4_000 cap per day
+fn setup_withdrawals(
+    env: &Env,
+) -> (
+    TreasuryClient<'static>,
+    token::TokenClient<'static>,
+    [Address; 3],
+) {
+    let (client, usdc, admin) = setup_funded_treasury(env);
+    let signers = [
+        Address::generate(env),
+        Address::generate(env),
+        Address::generate(env),
+    ];
+    client.set_withdrawal_policy(
+        &vec![env, admin],
+        &WithdrawalPolicy {
+            signers: vec![
+                env,
+                signers[0].clone(),
+                signers[1].clone(),
+                signers[2].clone(),
+            ],
+            required: 2,
+            delay: 3600,
+            period: 86400,
+            period_cap: 4_000,
+        },
+    );
+    env.ledger().with_mut(|li| li.timestamp = 1000);
+    (client, usdc, signers)
+}
+
+#[test]
+fn test_emergency_withdraw_timelock_and_approvals() {
+    let env = create_test_env();
+    let (client, usdc, signers) = setup_withdrawals(&env);
+    let recipient = Address::generate(&env);
+
+    let id = client.emergency_withdraw(
+        &signers[0],
+        &usdc.address,
+        &Symbol::new(&env, "platform"),
+        &recipient,
+        &3_000,
+    );
+    assert_eq!(client.get_withdrawal(&id).unwrap().eta, 4600);
+
+    // One approval is not enough
+    env.ledger().with_mut(|li| li.timestamp = 4600);
+    let result = client.try_execute_withdrawal(&id);
+    assert_eq!(result, Err(Ok(TreasuryError::InsufficientApprovers)));
+
+    env.ledger().with_mut(|li| li.timestamp = 1000);
+    client.approve_withdrawal(&signers[1], &id);
+    let result = client.try_approve_withdrawal(&signers[1], &id);
+    assert_eq!(result, Err(Ok(TreasuryError::AlreadyApproved)));
+    let result = client.try_execute_withdrawal(&id);
+    assert_eq!(result, Err(Ok(TreasuryError::WithdrawalDelayNotElapsed)));
+
+    env.ledger().with_mut(|li| li.timestamp = 4600);
+    client.execute_withdrawal(&id);
+    assert_eq!(usdc.balance(&recipient), 3_000);
+    assert_eq!(client.get_platform_fees(&usdc.address), 2_000);
+    assert_eq!(
+        client.get_treasury_balance(),
+        vec![&env, (usdc.address.clone(), 7_000)]
+    );
+    assert_eq!(
+        client.get_withdrawal(&id).unwrap().status,
+        WithdrawalStatus::Executed
+    );
+
+    let result = client.try_execute_withdrawal(&id);
+    assert_eq!(result, Err(Ok(TreasuryError::WithdrawalNotPending)));
+}
+
+#[test]
+fn test_emergency_withdraw_checks_pool_and_signer() {
+    let env = create_test_env();
+    let (client, usdc, signers) = setup_withdrawals(&env);
+    let recipient = Address::generate(&env);
+
+    let result = client.try_emergency_withdraw(
+        &signers[0],
+        &usdc.address,
+        &Symbol::new(&env, "creator"),
+        &recipient,
+        &2_001,
+    );
+    assert_eq!(result, Err(Ok(TreasuryError::InsufficientBalance)));
+
+    let outsider = Address::generate(&env);
+    let result = client.try_emergency_withdraw(
+        &outsider,
+        &usdc.address,
+        &Symbol::new(&env, "platform"),
+        &recipient,
+        &100,
+    );
+    assert_eq!(result, Err(Ok(TreasuryError::InvalidApprover)));
+}
+
+#[test]
+fn test_emergency_withdraw_period_cap() {
+    let env = create_test_env();
+    let (client, usdc, signers) = setup_withdrawals(&env);
+    let recipient = Address::generate(&env);
+
+    let first = client.emergency_withdraw(
+        &signers[0],
+        &usdc.address,
+        &Symbol::new(&env, "platform"),
+        &recipient,
+        &3_000,
+    );
+    client.approve_withdrawal(&signers[1], &first);
+    let second = client.emergency_withdraw(
+        &signers[0],
+        &usdc.address,
+        &Symbol::new(&env, "leaderboard"),
+        &recipient,
+        &2_000,
+    );
+    client.approve_withdrawal(&signers[2], &second);
+
+    env.ledger().with_mut(|li| li.timestamp = 4600);
+    client.execute_withdrawal(&first);
+    let result = client.try_execute_withdrawal(&second);
+    assert_eq!(result, Err(Ok(TreasuryError::PeriodCapExceeded)));
+
+    // Cap resets once the period has passed
+    env.ledger().with_mut(|li| li.timestamp = 4600 + 86400);
+    client.execute_withdrawal(&second);
+    assert_eq!(usdc.balance(&recipient), 5_000);
+    assert_eq!(client.get_leaderboard_fees(&usdc.address), 1_000);
+    assert_eq!(client.get_withdrawal_window(&usdc.address).withdrawn, 2_000);
+}
+
+#[test]
+fn test_cancel_withdrawal() {
+    let env = create_test_env();
+    let (client, usdc, signers) = setup_withdrawals(&env);
+    let recipient = Address::generate(&env);
+
+    let id = client.emergency_withdraw(
+        &signers[0],
+        &usdc.address,
+        &Symbol::new(&env, "platform"),
+        &recipient,
+        &1_000,
+    );
+    client.approve_withdrawal(&signers[1], &id);
+    client.cancel_withdrawal(&signers[2], &id);
+
+    env.ledger().with_mut(|li| li.timestamp = 4600);
+    let result = client.try_execute_withdrawal(&id);
+    assert_eq!(result, Err(Ok(TreasuryError::WithdrawalNotPending)));
+    assert_eq!(usdc.balance(&recipient), 0);
+    assert_eq!(client.get_platform_fees(&usdc.address), 5_000);
+}
+
+#[test]
+fn test_set_withdrawal_policy_requires_signers() {
+    let env = create_test_env();
+    let (client, _, signers) = setup_withdrawals(&env);
+    let policy = client.get_withdrawal_policy();
+
+    let result = client.try_set_withdrawal_policy(&vec![&env, signers[0].clone()], &policy);
+    assert_eq!(result, Err(Ok(TreasuryError::InsufficientApprovers)));
+
+    let result = client
+        .try_set_withdrawal_policy(&vec![&env, signers[0].clone(), signers[0].clone()], &policy);
+    assert_eq!(result, Err(Ok(TreasuryError::DuplicateApprovers)));
+
+    let mut invalid = policy.clone();
+    invalid.required = 4;
+    let result = client.try_set_withdrawal_policy(
+        &vec![&env, signers[0].clone(), signers[1].clone()],
+        &invalid,
+    );
+    assert_eq!(result, Err(Ok(TreasuryError::InvalidPolicy)));
+}
+
+#[test]
+fn test_withdraw_platform_fees() {
+    let env = create_test_env();
+    let (client, usdc, signers) = setup_withdrawals(&env);
+    let recipient = Address::generate(&env);
+
+    // Queued like any other withdrawal: approvals and delay first
+    let id = client.withdraw_platform_fees(&signers[0], &usdc.address, &recipient, &1_500);
+    let result = client.try_execute_withdrawal(&id);
+    assert_eq!(result, Err(Ok(TreasuryError::InsufficientApprovers)));
+    client.approve_withdrawal(&signers[1], &id);
+    let result = client.try_execute_withdrawal(&id);
+    assert_eq!(result, Err(Ok(TreasuryError::WithdrawalDelayNotElapsed)));
+
+    env.ledger().with_mut(|li| li.timestamp = 4600);
+    client.execute_withdrawal(&id);
+    assert_eq!(usdc.balance(&recipient), 1_500);
+    assert_eq!(client.get_platform_fees(&usdc.address), 3_500);
+    assert_eq!(client.get_withdrawal_window(&usdc.address).withdrawn, 1_500);
+
+    let result = client.try_withdraw_platform_fees(&signers[0], &usdc.address, &recipient, &3_501);
+    assert_eq!(result, Err(Ok(TreasuryError::InsufficientBalance)));
+    let result = client.try_withdraw_platform_fees(
+        &Address::generate(&env),
+        &usdc.address,
+        &recipient,
+        &100,
+    );
+    assert_eq!(result, Err(Ok(TreasuryError::InvalidApprover)));
+}
+
+#[test]
+fn test_budget_allocation_and_epoch_limit() {
+    let env = create_test_env();
+    let (client, usdc, _) = setup_funded_treasury(&env);
+    let ops = symbol_short!("ops");
+    let spender = Address::generate(&env);
+    let vendor = Address::generate(&env);
+
+    client.set_budget(&ops, &usdc.address, &1_000, &86400);
+    client.allocate_budget(&ops, &usdc.address, &3_000);
+    assert_eq!(client.get_platform_fees(&usdc.address), 2_000);
+    client.set_budget_spender(&ops, &spender, &true);
+
+    client.spend_budget(&spender, &ops, &usdc.address, &vendor, &600);
+    let result = client.try_spend_budget(&spender, &ops, &usdc.address, &vendor, &401);
+    assert_eq!(result, Err(Ok(TreasuryError::BudgetLimitExceeded)));
+
+    // Limit resets with the next epoch
+    env.ledger().with_mut(|li| li.timestamp += 86400);
+    client.spend_budget(&spender, &ops, &usdc.address, &vendor, &1_000);
+    assert_eq!(usdc.balance(&vendor), 1_600);
+    let budget = client.get_budget(&ops, &usdc.address).unwrap();
+    assert_eq!(budget.balance, 1_400);
+    assert_eq!(budget.spent_in_epoch, 1_000);
+}
+
+#[test]
+fn test_budget_spender_and_balance_checks() {
+    let env = create_test_env();
+    let (client, usdc, _) = setup_funded_treasury(&env);
+    let ops = symbol_short!("ops");
+    let spender = Address::generate(&env);
+    let vendor = Address::generate(&env);
+
+    let result = client.try_allocate_budget(&ops, &usdc.address, &100);
+    assert_eq!(result, Err(Ok(TreasuryError::BudgetNotFound)));
+
+    client.set_budget(&ops, &usdc.address, &1_000, &86400);
+    client.allocate_budget(&ops, &usdc.address, &200);
+    let result = client.try_spend_budget(&spender, &ops, &usdc.address, &vendor, &100);
+    assert_eq!(result, Err(Ok(TreasuryError::NotBudgetSpender)));
+
+    client.set_budget_spender(&ops, &spender, &true);
+    let result = client.try_spend_budget(&spender, &ops, &usdc.address, &vendor, &201);
+    assert_eq!(result, Err(Ok(TreasuryError::InsufficientBalance)));
+
+    client.set_budget_spender(&ops, &spender, &false);
+    assert!(!client.is_budget_spender(&ops, &spender));
+    let result = client.try_spend_budget(&spender, &ops, &usdc.address, &vendor, &100);
+    assert_eq!(result, Err(Ok(TreasuryError::NotBudgetSpender)));
+}
+
+#[test]
+fn test_deposit_fees_requires_registered_source() {
+    let env = create_test_env();
+    let (client, usdc, _) = setup_funded_treasury(&env);
+    let stranger = Address::generate(&env);
+    token::StellarAssetClient::new(&env, &usdc.address).mint(&stranger, &1_000);
+
+    let result =
+        client.try_deposit_fees(&stranger, &usdc.address, &FeeKind::Trading, &None, &1_000);
+    assert_eq!(result, Err(Ok(TreasuryError::UnregisteredSource)));
+    assert_eq!(client.get_total_fees(&usdc.address), 10_000);
+
+    client.set_fee_source(&stranger, &vec![&env, FeeKind::Trading]);
+    assert!(client.is_fee_source(&stranger));
+    client.deposit_fees(&stranger, &usdc.address, &FeeKind::Trading, &None, &400);
+
+    client.set_fee_source(&stranger, &vec![&env]);
+    assert!(!client.is_fee_source(&stranger));
+    let result = client.try_deposit_fees(&stranger, &usdc.address, &FeeKind::Trading, &None, &600);
+    assert_eq!(result, Err(Ok(TreasuryError::UnregisteredSource)));
+    assert_eq!(client.get_total_fees(&usdc.address), 10_400);
+}
+
+#[test]
+fn test_deposit_fees_rejects_unlisted_kind() {
+    let env = create_test_env();
+    let (client, usdc, _) = setup_funded_treasury(&env);
+    let market = Address::generate(&env);
+    token::StellarAssetClient::new(&env, &usdc.address).mint(&market, &1_000);
+    client.set_fee_source(&market, &vec![&env, FeeKind::Trading]);
+
+    for kind in [FeeKind::Creation, FeeKind::Winnings, FeeKind::Slashing] {
+        let result = client.try_deposit_fees(&market, &usdc.address, &kind, &None, &100);
+        assert_eq!(result, Err(Ok(TreasuryError::KindNotAllowed)));
+    }
+    client.deposit_fees(&market, &usdc.address, &FeeKind::Trading, &None, &100);
+    assert_eq!(
+        client.get_fees_by_kind(&FeeKind::Trading, &usdc.address),
+        10_100
+    );
+    assert_eq!(
+        client.get_fees_by_kind(&FeeKind::Creation, &usdc.address),
+        0
+    );
+
+    // The factory may only book creation fees
+    let client = TreasuryClient::new(&env, &register_treasury(&env));
+    let factory = Address::generate(&env);
+    client.initialize(&Address::generate(&env), &usdc.address, &factory);
+    assert_eq!(
+        client.get_fee_source_kinds(&factory),
+        vec![&env, FeeKind::Creation]
+    );
+    token::StellarAssetClient::new(&env, &usdc.address).mint(&factory, &100);
+    let result = client.try_deposit_fees(&factory, &usdc.address, &FeeKind::Trading, &None, &100);
+    assert_eq!(result, Err(Ok(TreasuryError::KindNotAllowed)));
+}
+
+#[test]
+fn test_fees_totalled_per_kind() {
+    let env = create_test_env();
+    let (client, usdc, _) = setup_funded_treasury(&env);
+    let source = fee_source(&env, &client);
+    token::StellarAssetClient::new(&env, &usdc.address).mint(&source, &1_000);
+
+    client.deposit_fees(&source, &usdc.address, &FeeKind::Winnings, &None, &700);
+    client.deposit_fees(&source, &usdc.address, &FeeKind::Slashing, &None, &300);
+
+    assert_eq!(
+        client.get_fees_by_kind(&FeeKind::Trading, &usdc.address),
+        10_000
+    );
+    assert_eq!(
+        client.get_fees_by_kind(&FeeKind::Winnings, &usdc.address),
+        700
+    );
+    assert_eq!(
+        client.get_fees_by_kind(&FeeKind::Slashing, &usdc.address),
+        300
+    );
+    assert_eq!(
+        client.get_fees_by_kind(&FeeKind::Creation, &usdc.address),
+        0
+    );
+    assert_eq!(client.get_total_fees(&usdc.address), 11_000);
+}
+
+#[test]
+fn test_fees_tracked_per_token() {
+    let env = create_test_env();
+    let (client, usdc, admin) = setup_funded_treasury(&env);
+    let eurc = env
+        .register_stellar_asset_contract_v2(Address::generate(&env))
+        .address();
+    let source = fee_source(&env, &client);
+    token::StellarAssetClient::new(&env, &eurc).mint(&source, &1_000);
+
+    let result = client.try_deposit_fees(&source, &eurc, &FeeKind::Trading, &None, &1_000);
+    assert_eq!(result, Err(Ok(TreasuryError::UnsupportedToken)));
+
+    client.add_token(&eurc);
+    let result = client.try_add_token(&eurc);
+    assert_eq!(result, Err(Ok(TreasuryError::TokenAlreadySupported)));
+    client.deposit_fees(&source, &eurc, &FeeKind::Trading, &None, &1_000);
+
+    assert_eq!(client.get_platform_fees(&eurc), 500);
+    assert_eq!(client.get_creator_fees(&eurc), 200);
+    assert_eq!(client.get_total_fees(&eurc), 1_000);
+    assert_eq!(client.get_platform_fees(&usdc.address), 5_000);
+    assert_eq!(client.get_total_fees(&usdc.address), 10_000);
+    assert_eq!(
+        client.get_treasury_balance(),
+        vec![&env, (usdc.address.clone(), 10_000), (eurc.clone(), 1_000)]
+    );
+
+    // Pools in one token cannot pay out another
+    let recipient = Address::generate(&env);
+    let result = client.try_distribute_creator_rewards(
+        &admin,
+        &eurc,
+        &vec![&env, (recipient.clone(), 201i128)],
+    );
+    assert_eq!(result, Err(Ok(TreasuryError::InsufficientBalance)));
+    let result = client.try_withdraw_platform_fees(&admin, &eurc, &recipient, &501);
+    assert_eq!(result, Err(Ok(TreasuryError::InsufficientBalance)));
+}
+
+#[test]
+fn test_stream_vests_after_cliff() {
+    let env = create_test_env();
+    let (client, usdc, _) = setup_funded_treasury(&env);
+    let partner = Address::generate(&env);
+
+    // 4_000 over 1000..5000 with nothing before 2000
+    let id = client.create_stream(
+        &partner,
+        &usdc.address,
+        &FeePool::Platform,
+        &4_000,
+        &1000,
+        &2000,
+        &5000,
+    );
+    assert_eq!(client.get_platform_fees(&usdc.address), 1_000);
+
+    env.ledger().with_mut(|li| li.timestamp = 1500);
+    let result = client.try_withdraw_from_stream(&partner, &id);
+    assert_eq!(result, Err(Ok(TreasuryError::NothingToClaim)));
+
+    env.ledger().with_mut(|li| li.timestamp = 2000);
+    assert_eq!(client.withdraw_from_stream(&partner, &id), 1_000);
+    env.ledger().with_mut(|li| li.timestamp = 3000);
+    assert_eq!(client.get_stream_withdrawable(&id), 1_000);
+    assert_eq!(client.withdraw_from_stream(&partner, &id), 1_000);
+    env.ledger().with_mut(|li| li.timestamp = 9000);
+    assert_eq!(client.withdraw_from_stream(&partner, &id), 2_000);
+    assert_eq!(usdc.balance(&partner), 4_000);
+
+    let stranger = Address::generate(&env);
+    let result = client.try_withdraw_from_stream(&stranger, &id);
+    assert_eq!(result, Err(Ok(TreasuryError::Unauthorized)));
+}
+
+#[test]
+fn test_revoke_stream_returns_unvested() {
+    let env = create_test_env();
+    let (client, usdc, _) = setup_funded_treasury(&env);
+    let creator = Address::generate(&env);
+
+    let id = client.create_stream(
+        &creator,
+        &usdc.address,
+        &FeePool::Creator,
+        &2_000,
+        &0,
+        &0,
+        &1000,
+    );
+    assert_eq!(client.get_creator_fees(&usdc.address), 0);
+
+    env.ledger().with_mut(|li| li.timestamp = 250);
+    assert_eq!(client.revoke_stream(&id), 1_500);
+    assert_eq!(client.get_creator_fees(&usdc.address), 1_500);
+    let result = client.try_revoke_stream(&id);
+    assert_eq!(result, Err(Ok(TreasuryError::StreamRevoked)));
+
+    // Vested part stays claimable, nothing more accrues
+    env.ledger().with_mut(|li| li.timestamp = 1000);
+    assert_eq!(client.withdraw_from_stream(&creator, &id), 500);
+    assert_eq!(usdc.balance(&creator), 500);
+}
+
+#[test]
+fn test_create_stream_validation() {
+    let env = create_test_env();
+    let (client, usdc, _) = setup_funded_treasury(&env);
+    let partner = Address::generate(&env);
+    let token = usdc.address;
+
+    let result = client.try_create_stream(
+        &partner,
+        &token,
+        &FeePool::Platform,
+        &100,
+        &500,
+        &400,
+        &1000,
+    );
+    assert_eq!(result, Err(Ok(TreasuryError::InvalidStream)));
+    let result = client.try_create_stream(
+        &partner,
+        &token,
+        &FeePool::Platform,
+        &100,
+        &1000,
+        &1000,
+        &1000,
+    );
+    assert_eq!(result, Err(Ok(TreasuryError::InvalidStream)));
+    let result = client.try_create_stream(
+        &partner,
+        &token,
+        &FeePool::Leaderboard,
+        &3_001,
+        &0,
+        &0,
+        &1000,
+    );
+    assert_eq!(result, Err(Ok(TreasuryError::InsufficientBalance)));
+    let result = client.try_withdraw_from_stream(&partner, &0);
+    assert_eq!(result, Err(Ok(TreasuryError::StreamNotFound)));
+}
+
+#[test]
+fn test_create_stream_rejects_backdated_start() {
+    let env = create_test_env();
+    let (client, usdc, _) = setup_funded_treasury(&env);
+    let partner = Address::generate(&env);
+    env.ledger().with_mut(|li| li.timestamp = 2000);
+
+    // Starting in the past would vest part of the stream on creation
+    let result = client.try_create_stream(
+        &partner,
+        &usdc.address,
+        &FeePool::Platform,
+        &4_000,
+        &0,
+        &0,
+        &2000,
+    );
+    assert_eq!(result, Err(Ok(TreasuryError::InvalidStream)));
+    assert_eq!(client.get_platform_fees(&usdc.address), 5_000);
+
+    let id = client.create_stream(
+        &partner,
+        &usdc.address,
+        &FeePool::Platform,
+        &4_000,
+        &2000,
+        &2000,
+        &6000,
+    );
+    assert_eq!(client.get_stream_withdrawable(&id), 0);
+}
+
+#[test]
+fn test_reconcile_and_credit_surplus() {
+    let env = create_test_env();
+    let (client, usdc, _) = setup_funded_treasury(&env);
+    let report = client.reconcile().get(0).unwrap();
+    assert_eq!(report.tracked, 10_000);
+    assert_eq!(report.actual, 10_000);
+    assert_eq!((report.surplus, report.deficit), (0, 0));
+
+    // Tokens sent directly are not tracked by any pool
+    let donor = Address::generate(&env);
+    token::StellarAssetClient::new(&env, &usdc.address).mint(&donor, &700);
+    usdc.transfer(&donor, &client.address, &700);
+    assert_eq!(client.reconcile().get(0).unwrap().surplus, 700);
+
+    let result = client.try_credit_surplus(&usdc.address, &FeePool::Platform, &701);
+    assert_eq!(result, Err(Ok(TreasuryError::InsufficientSurplus)));
+    client.credit_surplus(&usdc.address, &FeePool::Platform, &700);
+    assert_eq!(client.get_platform_fees(&usdc.address), 5_700);
+    assert_eq!(client.reconcile().get(0).unwrap().surplus, 0);
+}
+
+#[test]
+fn test_fee_splits_in_basis_points() {
+    let env = create_test_env();
+    let (client, usdc, signers) = setup_withdrawals(&env);
+    let token = usdc.address.clone();
+    let bucket = |name: &str| Symbol::new(&env, name);
+    let partner = bucket("partner");
+
+    // 12.5% to a partner, dust to the platform
+    client.set_fee_splits(
+        &vec![
+            &env,
+            FeeSplit {
+                bucket: bucket("platform"),
+                bps: 5_000,
+            },
+            FeeSplit {
+                bucket: bucket("leaderboard"),
+                bps: 2_500,
+            },
+            FeeSplit {
+                bucket: partner.clone(),
+                bps: 1_250,
+            },
+            FeeSplit {
+                bucket: bucket("creator"),
+                bps: 1_250,
+            },
+        ],
+        &bucket("platform"),
+    );
+    assert_eq!(
+        client.get_buckets(),
+        vec![&env, bucket("insurance"), partner.clone()]
+    );
+
+    let source = fee_source(&env, &client);
+    token::StellarAssetClient::new(&env, &token).mint(&source, &1_001);
+    client.deposit_fees(&source, &token, &FeeKind::Trading, &None, &1_001);
+
+    // 500 + 250 + 125 + 125 = 1_000, the remaining 1 is dust
+    assert_eq!(client.get_bucket_balance(&partner, &token), 125);
+    assert_eq!(
+        client.get_bucket_balance(&bucket("platform"), &token),
+        5_501
+    );
+    assert_eq!(client.get_leaderboard_fees(&token), 3_250);
+    assert_eq!(client.get_creator_fees(&token), 2_125);
+    assert_eq!(client.reconcile().get(0).unwrap().surplus, 0);
+
+    // Custom buckets pay out through the withdrawal queue
+    let recipient = Address::generate(&env);
+    let id = client.emergency_withdraw(&signers[0], &token, &partner, &recipient, &125);
+    client.approve_withdrawal(&signers[1], &id);
+    env.ledger().with_mut(|li| li.timestamp = 4600);
+    client.execute_withdrawal(&id);
+    assert_eq!(usdc.balance(&recipient), 125);
+    assert_eq!(client.get_bucket_balance(&partner, &token), 0);
+    let result =
+        client.try_emergency_withdraw(&signers[0], &token, &bucket("unknown"), &recipient, &1);
+    assert_eq!(result, Err(Ok(TreasuryError::BucketNotFound)));
+}
+
+/// Funded treasury sending 10% of fees to the insurance fund, with one
+/// registered market
+fn setup_insurance(
+    env: &Env,
+) -> (
+    TreasuryClient<'static>,
+    token::TokenClient<'static>,
+    Address,
+) {
+    let (client, usdc, _) = setup_funded_treasury(env);
+    let split = |name: &str, bps: u32| FeeSplit {
+        bucket: Symbol::new(env, name),
+        bps,
+    };
+    client.set_fee_splits(
+        &vec![
+            env,
+            split("platform", 5_000),
+            split("leaderboard", 3_000),
+            split("insurance", 1_000),
+            split("creator", 1_000),
+        ],
+        &Symbol::new(env, "creator"),
+    );
+    let source = fee_source(env, &client);
+    token::StellarAssetClient::new(env, &usdc.address).mint(&source, &5_000);
+    client.deposit_fees(&source, &usdc.address, &FeeKind::Trading, &None, &5_000);
+
+    let market = Address::generate(env);
+    client.set_insured_contract(&market, &true);
+    (client, usdc, market)
+}
+
+#[test]
+fn test_cover_shortfall_records_draw() {
+    let env = create_test_env();
+    let (client, usdc, market) = setup_insurance(&env);
+    assert_eq!(client.get_insurance_fund(&usdc.address), 500);
+
+    env.ledger().with_mut(|li| li.timestamp = 1234);
+    let reason = symbol_short!("rounding");
+    let id = client.cover_shortfall(&market, &usdc.address, &120, &reason);
+    assert_eq!(usdc.balance(&market), 120);
+    assert_eq!(client.get_insurance_fund(&usdc.address), 380);
+    assert_eq!(client.get_shortfall_draw_count(), 1);
+
+    let draw = client.get_shortfall_draw(&id).unwrap();
+    assert_eq!(draw.market, market);
+    assert_eq!(draw.amount, 120);
+    assert_eq!(draw.reason, reason);
+    assert_eq!(draw.timestamp, 1234);
+    assert_eq!(client.reconcile().get(0).unwrap().deficit, 0);
+
+    let result = client.try_cover_shortfall(&market, &usdc.address, &381, &reason);
+    assert_eq!(result, Err(Ok(TreasuryError::InsufficientBalance)));
+}
+
+#[test]
+fn test_cover_shortfall_requires_registration() {
+    let env = create_test_env();
+    let (client, usdc, market) = setup_insurance(&env);
+    let reason = symbol_short!("amm_short");
+
+    let stranger = Address::generate(&env);
+    let result = client.try_cover_shortfall(&stranger, &usdc.address, &10, &reason);
+    assert_eq!(result, Err(Ok(TreasuryError::NotInsured)));
+
+    client.set_insured_contract(&market, &false);
+    assert!(!client.is_insured_contract(&market));
+    let result = client.try_cover_shortfall(&market, &usdc.address, &10, &reason);
+    assert_eq!(result, Err(Ok(TreasuryError::NotInsured)));
+}
+
+#[test]
+fn test_default_splits_fund_insurance() {
+    let env = create_test_env();
+    env.mock_all_auths();
+    let client = TreasuryClient::new(&env, &register_treasury(&env));
+    let usdc = env
+        .register_stellar_asset_contract_v2(Address::generate(&env))
+        .address();
+    let admin = Address::generate(&env);
+    client.initialize(&admin, &usdc, &Address::generate(&env));
+
+    // 5% to insurance, the rest split 50/30/20
+    let bps = |client: &TreasuryClient| -> std::vec::Vec<(Symbol, u32)> {
+        client
+            .get_fee_splits()
+            .splits
+            .iter()
+            .map(|split| (split.bucket, split.bps))
+            .collect()
+    };
+    let bucket = |name: &str| Symbol::new(&env, name);
+    assert_eq!(
+        bps(&client),
+        [
+            (bucket("platform"), 4_750),
+            (bucket("leaderboard"), 2_850),
+            (bucket("creator"), 1_900),
+            (bucket("insurance"), 500),
+        ]
+    );
+    let source = fee_source(&env, &client);
+    token::StellarAssetClient::new(&env, &usdc).mint(&source, &20_000);
+    client.deposit_fees(&source, &usdc, &FeeKind::Trading, &None, &10_000);
+    assert_eq!(client.get_insurance_fund(&usdc), 500);
+    assert_eq!(client.get_platform_fees(&usdc), 4_750);
+    assert_eq!(client.get_leaderboard_fees(&usdc), 2_850);
+    assert_eq!(client.get_creator_fees(&usdc), 1_900);
+
+    // The percentage shorthand means exactly what it says
+    client.set_fee_distribution(&60, &20, &20);
+    assert_eq!(
+        bps(&client),
+        [
+            (bucket("platform"), 6_000),
+            (bucket("leaderboard"), 2_000),
+            (bucket("creator"), 2_000),
+        ]
+    );
+    client.deposit_fees(&source, &usdc, &FeeKind::Trading, &None, &10_000);
+    assert_eq!(client.get_insurance_fund(&usdc), 500);
+    assert_eq!(client.get_platform_fees(&usdc), 10_750);
+
+    // Only cover_shortfall pays out of the fund
+    let insurance = Symbol::new(&env, "insurance");
+    let recipient = Address::generate(&env);
+    let result = client.try_emergency_withdraw(&admin, &usdc, &insurance, &recipient, &100);
+    assert_eq!(result, Err(Ok(TreasuryError::InsuranceLocked)));
+}
+
+/// Property test: every entry point keeps pools in line with holdings
+mod invariant {
+    use proptest::prelude::*;
+    use soroban_sdk::{
+        symbol_short,
+        testutils::{Address as _, Ledger},
+        token, Address, Env, Symbol,
+    };
+
+    use super::{create_test_env, fee_source, register_treasury};
+    use boxmeout::treasury::{FeeKind, FeePool, FeeSplit, TreasuryClient, WithdrawalPolicy};
+
+    /// Treasury entry points exercised by the invariant property test
+    #[derive(Clone, Debug)]
+    enum Op {
+        Deposit {
+            token: usize,
+            attributed: bool,
+            amount: i128,
+        },
+        ClaimCreator {
+            token: usize,
+        },
+        DistributeCreator {
+            token: usize,
+            amount: i128,
+        },
+        WithdrawPlatform {
+            token: usize,
+            amount: i128,
+        },
+        Leaderboard {
+            token: usize,
+            amount: i128,
+            claim: bool,
+        },
+        AllocateBudget {
+            token: usize,
+            amount: i128,
+        },
+        SpendBudget {
+            token: usize,
+            amount: i128,
+        },
+        CreateStream {
+            token: usize,
+            pool: u8,
+            amount: i128,
+        },
+        WithdrawStream {
+            stream: u32,
+        },
+        RevokeStream {
+            stream: u32,
+        },
+        EmergencyWithdraw {
+            token: usize,
+            pool: u8,
+            amount: i128,
+        },
+        WithdrawBucket {
+            token: usize,
+            amount: i128,
+        },
+        CoverShortfall {
+            token: usize,
+            amount: i128,
+        },
+        Donate {
+            token: usize,
+            amount: i128,
+        },
+        CreditSurplus {
+            token: usize,
+            pool: u8,
+        },
+        AdvanceTime {
+            seconds: u64,
+        },
+    }
+
+    fn op_strategy() -> impl Strategy<Value = Op> {
+        let token = 0usize..2;
+        let amount = 1i128..4_000;
+        let pool = 0u8..3;
+        prop_oneof![
+            (token.clone(), any::<bool>(), amount.clone()).prop_map(
+                |(token, attributed, amount)| {
+                    Op::Deposit {
+                        token,
+                        attributed,
+                        amount,
+                    }
+                }
+            ),
+            token.clone().prop_map(|token| Op::ClaimCreator { token }),
+            (token.clone(), amount.clone())
+                .prop_map(|(token, amount)| Op::DistributeCreator { token, amount }),
+            (token.clone(), amount.clone())
+                .prop_map(|(token, amount)| Op::WithdrawPlatform { token, amount }),
+            (token.clone(), amount.clone(), any::<bool>()).prop_map(|(token, amount, claim)| {
+                Op::Leaderboard {
+                    token,
+                    amount,
+                    claim,
+                }
+            }),
+            (token.clone(), amount.clone())
+                .prop_map(|(token, amount)| Op::AllocateBudget { token, amount }),
+            (token.clone(), amount.clone())
+                .prop_map(|(token, amount)| Op::SpendBudget { token, amount }),
+            (token.clone(), pool.clone(), amount.clone()).prop_map(|(token, pool, amount)| {
+                Op::CreateStream {
+                    token,
+                    pool,
+                    amount,
+                }
+            }),
+            (0u32..4).prop_map(|stream| Op::WithdrawStream { stream }),
+            (0u32..4).prop_map(|stream| Op::RevokeStream { stream }),
+            (token.clone(), pool.clone(), amount.clone()).prop_map(|(token, pool, amount)| {
+                Op::EmergencyWithdraw {
+                    token,
+                    pool,
+                    amount,
+                }
+            }),
+            (token.clone(), amount.clone())
+                .prop_map(|(token, amount)| Op::WithdrawBucket { token, amount }),
+            (token.clone(), 1i128..400)
+                .prop_map(|(token, amount)| Op::CoverShortfall { token, amount }),
+            (token.clone(), amount).prop_map(|(token, amount)| Op::Donate { token, amount }),
+            (token, pool).prop_map(|(token, pool)| Op::CreditSurplus { token, pool }),
+            (1u64..2_000).prop_map(|seconds| Op::AdvanceTime { seconds }),
+        ]
+    }
+
+    fn fee_pool(pool: u8) -> FeePool {
+        match pool {
+            0 => FeePool::Platform,
+            1 => FeePool::Leaderboard,
+            _ => FeePool::Creator,
+        }
+    }
+
+    fn pool_bucket(env: &Env, pool: u8) -> Symbol {
+        Symbol::new(
+            env,
+            ["platform", "leaderboard", "creator"][pool.min(2) as usize],
+        )
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(64))]
+
+        /// Tracked balances never exceed holdings, and any excess is exactly
+        /// what was sent outside `deposit_fees` and not yet credited
+        #[test]
+        fn prop_entry_points_keep_accounting_invariant(
+            ops in proptest::collection::vec(op_strategy(), 1..40)
+        ) {
+            let env = create_test_env();
+            env.mock_all_auths();
+            env.cost_estimate().budget().reset_unlimited();
+
+            let admin = Address::generate(&env);
+            let client = TreasuryClient::new(&env, &register_treasury(&env));
+            let tokens = [
+                env.register_stellar_asset_contract_v2(Address::generate(&env)).address(),
+                env.register_stellar_asset_contract_v2(Address::generate(&env)).address(),
+            ];
+            client.initialize(&admin, &tokens[0], &Address::generate(&env));
+            client.add_token(&tokens[1]);
+
+            let source = fee_source(&env, &client);
+            let creator = Address::generate(&env);
+            let winner = Address::generate(&env);
+            let spender = Address::generate(&env);
+            let recipient = Address::generate(&env);
+            let ops_budget = symbol_short!("ops");
+            let partner = symbol_short!("partner");
+            // Odd basis points so deposits leave rounding dust
+            let split = |bucket: Symbol, bps: u32| FeeSplit { bucket, bps };
+            client.set_fee_splits(
+                &soroban_sdk::vec![
+                    &env,
+                    split(Symbol::new(&env, "platform"), 4_999),
+                    split(Symbol::new(&env, "leaderboard"), 3_001),
+                    split(partner.clone(), 1_000),
+                    split(symbol_short!("insurance"), 250),
+                    split(Symbol::new(&env, "creator"), 750),
+                ],
+                &partner,
+            );
+            for token in tokens.iter() {
+                token::StellarAssetClient::new(&env, token).mint(&source, &1_000_000);
+                client.set_budget(&ops_budget, token, &i128::MAX, &86400);
+                client.deposit_fees(&source, token, &FeeKind::Trading, &None, &20_000);
+            }
+            client.set_budget_spender(&ops_budget, &spender, &true);
+            let market = Address::generate(&env);
+            client.set_insured_contract(&market, &true);
+            client.set_withdrawal_policy(
+                &soroban_sdk::vec![&env, admin.clone()],
+                &WithdrawalPolicy {
+                    signers: soroban_sdk::vec![&env, admin.clone()],
+                    required: 1,
+                    delay: 0,
+                    period: 86400,
+                    period_cap: i128::MAX,
+                },
+            );
+
+            let mut donated = [0i128; 2];
+            for op in ops {
+                match op {
+                    Op::Deposit { token, attributed, amount } => {
+                        let creator = attributed.then(|| creator.clone());
+                        let _ = client.try_deposit_fees(&source, &tokens[token], &FeeKind::Trading, &creator, &amount);
+                    }
+                    Op::ClaimCreator { token } => {
+                        let _ = client.try_claim_creator_rewards(&creator, &tokens[token]);
+                    }
+                    Op::DistributeCreator { token, amount } => {
+                        let _ = client.try_distribute_creator_rewards(
+                            &admin,
+                            &tokens[token],
+                            &soroban_sdk::vec![&env, (recipient.clone(), amount)],
+                        );
+                    }
+                    Op::WithdrawPlatform { token, amount } => {
+                        if let Ok(Ok(id)) = client.try_withdraw_platform_fees(
+                            &admin,
+                            &tokens[token],
+                            &recipient,
+                            &amount,
+                        ) {
+                            client.execute_withdrawal(&id);
+                        }
+                    }
+                    Op::Leaderboard { token, amount, claim } => {
+                        let root = client.leaderboard_leaf(&winner, &amount);
+                        if let Ok(Ok(epoch)) =
+                            client.try_distribute_leaderboard_rewards(&tokens[token], &root, &amount)
+                        {
+                            if claim {
+                                client.claim_leaderboard_reward(&winner, &epoch, &amount, &soroban_sdk::vec![&env]);
+                            }
+                        }
+                    }
+                    Op::AllocateBudget { token, amount } => {
+                        let _ = client.try_allocate_budget(&ops_budget, &tokens[token], &amount);
+                    }
+                    Op::SpendBudget { token, amount } => {
+                        let _ = client.try_spend_budget(
+                            &spender,
+                            &ops_budget,
+                            &tokens[token],
+                            &recipient,
+                            &amount,
+                        );
+                    }
+                    Op::CreateStream { token, pool, amount } => {
+                        let start = env.ledger().timestamp();
+                        let _ = client.try_create_stream(
+                            &recipient,
+                            &tokens[token],
+                            &fee_pool(pool),
+                            &amount,
+                            &start,
+                            &(start + 500),
+                            &(start + 3_000),
+                        );
+                    }
+                    Op::WithdrawStream { stream } => {
+                        let _ = client.try_withdraw_from_stream(&recipient, &stream);
+                    }
+                    Op::RevokeStream { stream } => {
+                        let _ = client.try_revoke_stream(&stream);
+                    }
+                    Op::EmergencyWithdraw { token, pool, amount } => {
+                        if let Ok(Ok(id)) = client.try_emergency_withdraw(
+                            &admin,
+                            &tokens[token],
+                            &pool_bucket(&env, pool),
+                            &recipient,
+                            &amount,
+                        ) {
+                            client.execute_withdrawal(&id);
+                        }
+                    }
+                    Op::WithdrawBucket { token, amount } => {
+                        if let Ok(Ok(id)) = client.try_emergency_withdraw(
+                            &admin,
+                            &tokens[token],
+                            &partner,
+                            &recipient,
+                            &amount,
+                        ) {
+                            client.execute_withdrawal(&id);
+                        }
+                    }
+                    Op::CoverShortfall { token, amount } => {
+                        let _ = client.try_cover_shortfall(
+                            &market,
+                            &tokens[token],
+                            &amount,
+                            &symbol_short!("rounding"),
+                        );
+                    }
+                    Op::Donate { token, amount } => {
+                        token::TokenClient::new(&env, &tokens[token])
+                            .transfer(&source, &client.address, &amount);
+                        donated[token] += amount;
+                    }
+                    Op::CreditSurplus { token, pool } => {
+                        if donated[token] > 0 {
+                            client.credit_surplus(&tokens[token], &fee_pool(pool), &donated[token]);
+                            donated[token] = 0;
+                        }
+                    }
+                    Op::AdvanceTime { seconds } => {
+                        env.ledger().with_mut(|li| li.timestamp += seconds);
+                    }
+                }
+
+                for (i, report) in client.reconcile().iter().enumerate() {
+                    prop_assert_eq!(report.deficit, 0);
+                    prop_assert_eq!(report.surplus, donated[i]);
+                }
+            }
+        }
+    }
+}
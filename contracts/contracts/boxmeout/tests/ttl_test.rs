@@ -0,0 +1,323 @@
+use soroban_sdk::{
+    testutils::{storage::Persistent, Address as _, Ledger},
+    token, Address, BytesN, Env, Symbol,
+};
+
+use boxmeout::{
+    amm::{AMMClient, AmmError, DataKey as AmmKey, AMM},
+    factory::{DataKey as FactoryKey, MarketFactory, MarketFactoryClient},
+    helpers::{DAY_IN_LEDGERS, PERSISTENT_BUMP_AMOUNT},
+    market::{
+        DataKey as MarketKey, MarketError, PredictionMarket, PredictionMarketClient, MAX_BUMP_BATCH,
+    },
+    oracle::{ConsensusMode, DataKey as OracleKey, OracleManager, OracleManagerClient},
+    treasury::{Treasury, TreasuryClient},
+};
+
+/// Advance the ledger sequence (and time, at ~5s per ledger) by `days`
+fn advance_days(env: &Env, days: u32) {
+    env.ledger().with_mut(|li| {
+        li.sequence_number += days * DAY_IN_LEDGERS;
+        li.timestamp += days as u64 * DAY_IN_LEDGERS as u64 * 5;
+    });
+}
+
+fn create_token<'a>(env: &Env) -> token::StellarAssetClient<'a> {
+    let address = env
+        .register_stellar_asset_contract_v2(Address::generate(env))
+        .address();
+    token::StellarAssetClient::new(env, &address)
+}
+
+fn setup_amm(env: &Env) -> (AMMClient<'_>, token::StellarAssetClient<'_>) {
+    let usdc = create_token(env);
+    let amm_id = env.register(AMM, ());
+    let amm = AMMClient::new(env, &amm_id);
+    amm.initialize(
+        &Address::generate(env),
+        &Address::generate(env),
+        &usdc.address,
+        &100_000_000_000u128,
+    );
+    (amm, usdc)
+}
+
+fn pool_key_ttl(env: &Env, amm: &Address, market_id: &BytesN<32>) -> u32 {
+    env.as_contract(amm, || {
+        env.storage()
+            .persistent()
+            .get_ttl(&AmmKey::PoolYesReserve(market_id.clone()))
+    })
+}
+
+#[test]
+fn test_amm_pool_survives_with_bump() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (amm, usdc) = setup_amm(&env);
+
+    let creator = Address::generate(&env);
+    let market_id = BytesN::from_array(&env, &[1u8; 32]);
+    usdc.mint(&creator, &10_000_000);
+    amm.create_pool(&creator, &market_id, &10_000_000);
+
+    assert_eq!(
+        pool_key_ttl(&env, &amm.address, &market_id),
+        PERSISTENT_BUMP_AMOUNT
+    );
+
+    // 150 days: well past a single extension, kept alive by periodic bumps
+    for _ in 0..6 {
+        advance_days(&env, 25);
+        amm.bump(&market_id);
+    }
+
+    let (yes, no, total, _, _) = amm.get_pool_state(&market_id);
+    assert_eq!((yes, no, total), (5_000_000, 5_000_000, 10_000_000));
+    assert_eq!(
+        pool_key_ttl(&env, &amm.address, &market_id),
+        PERSISTENT_BUMP_AMOUNT
+    );
+}
+
+#[test]
+fn test_untouched_pool_ttl_runs_down() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (amm, usdc) = setup_amm(&env);
+
+    let creator = Address::generate(&env);
+    let idle_market = BytesN::from_array(&env, &[1u8; 32]);
+    let bumped_market = BytesN::from_array(&env, &[2u8; 32]);
+    usdc.mint(&creator, &20_000_000);
+    amm.create_pool(&creator, &idle_market, &10_000_000);
+    amm.create_pool(&creator, &bumped_market, &10_000_000);
+
+    for _ in 0..2 {
+        advance_days(&env, 25);
+        amm.bump(&bumped_market);
+    }
+
+    // Only the bumped pool was extended; the idle one is 10 days from archival
+    assert_eq!(
+        pool_key_ttl(&env, &amm.address, &idle_market),
+        PERSISTENT_BUMP_AMOUNT - 50 * DAY_IN_LEDGERS
+    );
+    assert_eq!(
+        pool_key_ttl(&env, &amm.address, &bumped_market),
+        PERSISTENT_BUMP_AMOUNT
+    );
+}
+
+#[test]
+fn test_liquidity_change_extends_lp_balance() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (amm, usdc) = setup_amm(&env);
+
+    let provider = Address::generate(&env);
+    let market_id = BytesN::from_array(&env, &[1u8; 32]);
+    usdc.mint(&provider, &10_000_000);
+    amm.create_pool(&provider, &market_id, &10_000_000);
+
+    advance_days(&env, 20);
+    amm.remove_liquidity(&provider, &market_id, &1_000_000);
+
+    let lp_ttl = env.as_contract(&amm.address, || {
+        env.storage()
+            .persistent()
+            .get_ttl(&AmmKey::PoolLpTokens(market_id.clone(), provider.clone()))
+    });
+    assert_eq!(lp_ttl, PERSISTENT_BUMP_AMOUNT);
+    // Touching the pool refreshed its entries as well
+    assert_eq!(
+        pool_key_ttl(&env, &amm.address, &market_id),
+        PERSISTENT_BUMP_AMOUNT
+    );
+}
+
+#[test]
+fn test_amm_bump_unknown_pool() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (amm, _) = setup_amm(&env);
+    let result = amm.try_bump(&BytesN::from_array(&env, &[9u8; 32]));
+    assert_eq!(result, Err(Ok(AmmError::PoolNotFound)));
+}
+
+#[test]
+fn test_oracle_votes_survive_with_bump() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let oracle_id = env.register(OracleManager, ());
+    let client = OracleManagerClient::new(&env, &oracle_id);
+    client.initialize(&Address::generate(&env), &1u32);
+
+    let oracle = Address::generate(&env);
+    client.register_oracle(&oracle, &Symbol::new(&env, "Oracle1"));
+
+    let market_id = BytesN::from_array(&env, &[1u8; 32]);
+    let resolution_time = env.ledger().timestamp() + 10;
+    client.register_market(&market_id, &resolution_time, &ConsensusMode::Count);
+    env.ledger().with_mut(|li| li.timestamp = resolution_time);
+    client.submit_attestation(
+        &oracle,
+        &market_id,
+        &1,
+        &BytesN::from_array(&env, &[2u8; 32]),
+    );
+
+    for _ in 0..6 {
+        advance_days(&env, 25);
+        client.bump(&market_id);
+    }
+
+    let vote_ttl = env.as_contract(&oracle_id, || {
+        env.storage()
+            .persistent()
+            .get_ttl(&OracleKey::Vote(market_id.clone(), oracle.clone()))
+    });
+    assert_eq!(vote_ttl, PERSISTENT_BUMP_AMOUNT);
+    assert!(client.get_attestation(&market_id, &oracle).is_some());
+    assert_eq!(client.get_attestation_counts(&market_id), (1, 0));
+    assert_eq!(client.check_consensus(&market_id), (true, 1));
+}
+
+#[test]
+fn test_market_predictions_survive_with_bump() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let usdc = create_token(&env);
+    let market_contract = env.register(PredictionMarket, ());
+    let market = PredictionMarketClient::new(&env, &market_contract);
+    let market_id = BytesN::from_array(&env, &[1u8; 32]);
+    market.initialize(
+        &market_id,
+        &Address::generate(&env),
+        &Address::generate(&env),
+        &usdc.address,
+        &Address::generate(&env),
+        &(env.ledger().timestamp() + 1000),
+        &(env.ledger().timestamp() + 2000),
+    );
+
+    let user = Address::generate(&env);
+    market.test_add_participant(&user);
+    market.test_set_prediction(&user, &1, &500);
+
+    for _ in 0..6 {
+        advance_days(&env, 25);
+        assert_eq!(market.bump(&market_id, &0, &10), 1);
+    }
+
+    let prediction_ttl = env.as_contract(&market_contract, || {
+        env.storage()
+            .persistent()
+            .get_ttl(&MarketKey::Prediction(user.clone()))
+    });
+    assert_eq!(prediction_ttl, PERSISTENT_BUMP_AMOUNT);
+    assert_eq!(market.test_get_prediction(&user).unwrap().amount, 500);
+    assert_eq!(market.get_market_state_value(), Some(0));
+}
+
+#[test]
+fn test_market_bump_pages_through_participants() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let market_contract = env.register(PredictionMarket, ());
+    let market = PredictionMarketClient::new(&env, &market_contract);
+    let market_id = BytesN::from_array(&env, &[1u8; 32]);
+    market.initialize(
+        &market_id,
+        &Address::generate(&env),
+        &Address::generate(&env),
+        &Address::generate(&env),
+        &Address::generate(&env),
+        &1000,
+        &2000,
+    );
+    for _ in 0..MAX_BUMP_BATCH + 5 {
+        let user = Address::generate(&env);
+        market.test_add_participant(&user);
+        market.test_set_prediction(&user, &1, &500);
+    }
+
+    // Limits above the batch cap are clamped
+    assert_eq!(market.bump(&market_id, &0, &u32::MAX), MAX_BUMP_BATCH);
+    assert_eq!(
+        market.bump(&market_id, &MAX_BUMP_BATCH, &10),
+        MAX_BUMP_BATCH + 5
+    );
+    assert_eq!(
+        market.bump(&market_id, &(MAX_BUMP_BATCH + 5), &10),
+        MAX_BUMP_BATCH + 5
+    );
+}
+
+#[test]
+fn test_market_bump_rejects_other_id() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let market_contract = env.register(PredictionMarket, ());
+    let market = PredictionMarketClient::new(&env, &market_contract);
+    market.initialize(
+        &BytesN::from_array(&env, &[1u8; 32]),
+        &Address::generate(&env),
+        &Address::generate(&env),
+        &Address::generate(&env),
+        &Address::generate(&env),
+        &1000,
+        &2000,
+    );
+
+    let result = market.try_bump(&BytesN::from_array(&env, &[2u8; 32]), &0, &10);
+    assert_eq!(result, Err(Ok(MarketError::MarketIdMismatch)));
+}
+
+#[test]
+fn test_factory_registry_survives_with_bump() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let usdc = create_token(&env);
+    let treasury_id = env.register(Treasury, ());
+    let factory_id = env.register(MarketFactory, ());
+    TreasuryClient::new(&env, &treasury_id).initialize(
+        &Address::generate(&env),
+        &usdc.address,
+        &factory_id,
+    );
+    let factory = MarketFactoryClient::new(&env, &factory_id);
+    factory.initialize(&Address::generate(&env), &usdc.address, &treasury_id);
+
+    let creator = Address::generate(&env);
+    usdc.mint(&creator, &100_000_000);
+    let now = env.ledger().timestamp();
+    let market_id = factory.create_market(
+        &creator,
+        &0u64,
+        &Symbol::new(&env, "Title"),
+        &Symbol::new(&env, "Description"),
+        &Symbol::new(&env, "Sports"),
+        &(now + 86_400),
+        &(now + 172_800),
+    );
+
+    for _ in 0..6 {
+        advance_days(&env, 25);
+        factory.bump(&market_id);
+    }
+
+    let registry_ttl = env.as_contract(&factory_id, || {
+        env.storage()
+            .persistent()
+            .get_ttl(&FactoryKey::Market(market_id.clone()))
+    });
+    assert_eq!(registry_ttl, PERSISTENT_BUMP_AMOUNT);
+    assert!(factory.market_exists(&market_id));
+    assert_eq!(factory.get_market_count(), 1);
+}
@@ -0,0 +1,427 @@
+use soroban_sdk::{
+    testutils::{Address as _, Ledger},
+    Address, BytesN, Env, IntoVal, Val,
+};
+
+use boxmeout::{
+    amm::{AMMClient, AmmError, DataKey as AmmKey, AMM},
+    factory::{FactoryError, MarketFactory, MarketFactoryClient},
+    market::{MarketError, PredictionMarket, PredictionMarketClient},
+    oracle::{DataKey as OracleKey, OracleError, OracleManager, OracleManagerClient},
+    treasury::{DataKey as TreasuryKey, Treasury, TreasuryClient, TreasuryError},
+};
+
+/// v2 test contract: exposes `version() -> 2` (source in tests/fixtures/upgrade_v2)
+mod upgrade_v2 {
+    soroban_sdk::contractimport!(file = "tests/fixtures/upgrade_v2.wasm");
+}
+
+fn upload_v2(env: &Env) -> BytesN<32> {
+    env.deployer().upload_contract_wasm(upgrade_v2::WASM)
+}
+
+/// Contract storage survives the WASM swap
+fn has_admin<K>(env: &Env, contract: &Address, admin_key: K) -> bool
+where
+    K: IntoVal<Env, Val>,
+{
+    env.as_contract(contract, || env.storage().instance().has(&admin_key))
+}
+
+#[test]
+fn test_oracle_upgrade_to_v2() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let oracle_id = env.register(OracleManager, ());
+    let client = OracleManagerClient::new(&env, &oracle_id);
+    client.initialize(&Address::generate(&env), &2u32);
+    assert_eq!(client.get_schema_version(), 1);
+
+    client.upgrade(&upload_v2(&env));
+
+    let v2 = upgrade_v2::Client::new(&env, &oracle_id);
+    assert_eq!(v2.version(), 2);
+    assert!(has_admin(&env, &oracle_id, OracleKey::Admin));
+}
+
+#[test]
+fn test_treasury_upgrade_to_v2() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let treasury_id = env.register(Treasury, ());
+    let client = TreasuryClient::new(&env, &treasury_id);
+    client.initialize(
+        &Address::generate(&env),
+        &Address::generate(&env),
+        &Address::generate(&env),
+    );
+
+    client.upgrade(&upload_v2(&env));
+
+    assert_eq!(upgrade_v2::Client::new(&env, &treasury_id).version(), 2);
+    assert!(has_admin(&env, &treasury_id, TreasuryKey::Admin));
+}
+
+#[test]
+fn test_amm_upgrade_to_v2() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let amm_id = env.register(AMM, ());
+    let client = AMMClient::new(&env, &amm_id);
+    client.initialize(
+        &Address::generate(&env),
+        &Address::generate(&env),
+        &Address::generate(&env),
+        &100_000_000_000u128,
+    );
+
+    client.upgrade(&upload_v2(&env));
+
+    assert_eq!(upgrade_v2::Client::new(&env, &amm_id).version(), 2);
+    assert!(has_admin(&env, &amm_id, AmmKey::Admin));
+}
+
+#[test]
+fn test_oracle_rejects_reinitialize() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let oracle_id = env.register(OracleManager, ());
+    let client = OracleManagerClient::new(&env, &oracle_id);
+    let admin = Address::generate(&env);
+    client.initialize(&admin, &2u32);
+
+    // A second initialize would hand the upgrade authority to the caller
+    let result = client.try_initialize(&Address::generate(&env), &1u32);
+    assert_eq!(result, Err(Ok(OracleError::AlreadyInitialized)));
+    assert_eq!(client.get_admin_signers().get(0), Some(admin));
+}
+
+#[test]
+fn test_amm_rejects_reinitialize() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let amm_id = env.register(AMM, ());
+    let client = AMMClient::new(&env, &amm_id);
+    client.initialize(
+        &Address::generate(&env),
+        &Address::generate(&env),
+        &Address::generate(&env),
+        &100_000_000_000u128,
+    );
+
+    let result = client.try_initialize(
+        &Address::generate(&env),
+        &Address::generate(&env),
+        &Address::generate(&env),
+        &100_000_000_000u128,
+    );
+    assert_eq!(result, Err(Ok(AmmError::AlreadyInitialized)));
+}
+
+#[test]
+fn test_market_rejects_reinitialize() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let market_id = env.register(PredictionMarket, ());
+    let market = PredictionMarketClient::new(&env, &market_id);
+    let id = BytesN::from_array(&env, &[1u8; 32]);
+    market.initialize(
+        &id,
+        &Address::generate(&env),
+        &Address::generate(&env),
+        &Address::generate(&env),
+        &Address::generate(&env),
+        &2000,
+        &3000,
+    );
+
+    // Re-running with another factory would take over `upgrade`
+    let result = market.try_initialize(
+        &id,
+        &Address::generate(&env),
+        &Address::generate(&env),
+        &Address::generate(&env),
+        &Address::generate(&env),
+        &2000,
+        &3000,
+    );
+    assert_eq!(result, Err(Ok(MarketError::AlreadyInitialized)));
+}
+
+#[test]
+fn test_factory_upgrades_itself_and_markets() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let factory_id = env.register(MarketFactory, ());
+    let factory = MarketFactoryClient::new(&env, &factory_id);
+    factory.initialize(
+        &Address::generate(&env),
+        &Address::generate(&env),
+        &Address::generate(&env),
+    );
+
+    // Market whose upgrade authority is the factory
+    let market_id = env.register(PredictionMarket, ());
+    let market = PredictionMarketClient::new(&env, &market_id);
+    market.initialize(
+        &BytesN::from_array(&env, &[1u8; 32]),
+        &Address::generate(&env),
+        &factory_id,
+        &Address::generate(&env),
+        &Address::generate(&env),
+        &2000,
+        &3000,
+    );
+    assert_eq!(market.get_schema_version(), 1);
+
+    let v2_hash = upload_v2(&env);
+    factory.upgrade_market(&market_id, &v2_hash);
+    assert_eq!(upgrade_v2::Client::new(&env, &market_id).version(), 2);
+
+    factory.upgrade(&v2_hash);
+    assert_eq!(upgrade_v2::Client::new(&env, &factory_id).version(), 2);
+}
+
+#[test]
+fn test_timelocked_upgrade_flow() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let oracle_id = env.register(OracleManager, ());
+    let client = OracleManagerClient::new(&env, &oracle_id);
+    client.initialize(&Address::generate(&env), &2u32);
+
+    client.set_upgrade_delay(&3600);
+    env.ledger().set_timestamp(1000);
+
+    let v2_hash = upload_v2(&env);
+    let eta = client.propose_upgrade(&v2_hash);
+    assert_eq!(eta, 4600);
+
+    let pending = client.get_pending_upgrade().unwrap();
+    assert_eq!(pending.wasm_hash, v2_hash);
+    assert_eq!(pending.eta, 4600);
+
+    // Too early
+    assert!(client.try_upgrade(&v2_hash).is_err());
+
+    env.ledger().set_timestamp(eta);
+    client.upgrade(&v2_hash);
+
+    assert_eq!(upgrade_v2::Client::new(&env, &oracle_id).version(), 2);
+}
+
+#[test]
+fn test_market_upgrade_follows_factory_timelock() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let factory_id = env.register(MarketFactory, ());
+    let factory = MarketFactoryClient::new(&env, &factory_id);
+    factory.initialize(
+        &Address::generate(&env),
+        &Address::generate(&env),
+        &Address::generate(&env),
+    );
+    let market_id = env.register(PredictionMarket, ());
+    PredictionMarketClient::new(&env, &market_id).initialize(
+        &BytesN::from_array(&env, &[1u8; 32]),
+        &Address::generate(&env),
+        &factory_id,
+        &Address::generate(&env),
+        &Address::generate(&env),
+        &2000,
+        &3000,
+    );
+
+    factory.set_upgrade_delay(&3600);
+    env.ledger().set_timestamp(1000);
+    let v2_hash = upload_v2(&env);
+
+    let result = factory.try_upgrade_market(&market_id, &v2_hash);
+    assert_eq!(result, Err(Ok(FactoryError::NoPendingUpgrade)));
+
+    let eta = factory.propose_market_upgrade(&market_id, &v2_hash);
+    assert_eq!(eta, 4600);
+    let result = factory.try_upgrade_market(&market_id, &v2_hash);
+    assert_eq!(result, Err(Ok(FactoryError::TimelockNotElapsed)));
+
+    env.ledger().set_timestamp(eta);
+    factory.upgrade_market(&market_id, &v2_hash);
+    assert_eq!(upgrade_v2::Client::new(&env, &market_id).version(), 2);
+    assert!(factory.get_pending_market_upgrade(&market_id).is_none());
+}
+
+#[test]
+fn test_upgrade_delay_increase_retimes_queued_upgrades() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let factory_id = env.register(MarketFactory, ());
+    let factory = MarketFactoryClient::new(&env, &factory_id);
+    factory.initialize(
+        &Address::generate(&env),
+        &Address::generate(&env),
+        &Address::generate(&env),
+    );
+    let market_id = env.register(PredictionMarket, ());
+    PredictionMarketClient::new(&env, &market_id).initialize(
+        &BytesN::from_array(&env, &[1u8; 32]),
+        &Address::generate(&env),
+        &factory_id,
+        &Address::generate(&env),
+        &Address::generate(&env),
+        &2000,
+        &3000,
+    );
+
+    factory.set_upgrade_delay(&3600);
+    env.ledger().set_timestamp(1000);
+    let v2_hash = upload_v2(&env);
+    assert_eq!(factory.propose_upgrade(&v2_hash), 4600);
+    assert_eq!(factory.propose_market_upgrade(&market_id, &v2_hash), 4600);
+
+    // Raising the delay after queueing pushes both proposals back
+    env.ledger().set_timestamp(2000);
+    factory.set_upgrade_delay(&7200);
+    assert_eq!(factory.get_pending_upgrade().unwrap().eta, 8200);
+    assert_eq!(
+        factory.get_pending_market_upgrade(&market_id).unwrap().eta,
+        8200
+    );
+
+    env.ledger().set_timestamp(4600);
+    let result = factory.try_upgrade_market(&market_id, &v2_hash);
+    assert_eq!(result, Err(Ok(FactoryError::TimelockNotElapsed)));
+    let result = factory.try_upgrade(&v2_hash);
+    assert_eq!(result, Err(Ok(FactoryError::TimelockNotElapsed)));
+
+    env.ledger().set_timestamp(8200);
+    factory.upgrade_market(&market_id, &v2_hash);
+    assert_eq!(upgrade_v2::Client::new(&env, &market_id).version(), 2);
+    factory.upgrade(&v2_hash);
+    assert_eq!(upgrade_v2::Client::new(&env, &factory_id).version(), 2);
+}
+
+#[test]
+fn test_upgrade_delay_reduction_waits_out_current_delay() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let oracle_id = env.register(OracleManager, ());
+    let client = OracleManagerClient::new(&env, &oracle_id);
+    client.initialize(&Address::generate(&env), &2u32);
+    client.set_upgrade_delay(&3600);
+    env.ledger().set_timestamp(1000);
+
+    // Dropping the delay to zero does not allow an immediate upgrade
+    assert_eq!(client.set_upgrade_delay(&0), 4600);
+    let v2_hash = upload_v2(&env);
+    let result = client.try_upgrade(&v2_hash);
+    assert_eq!(result, Err(Ok(OracleError::NoPendingUpgrade)));
+    assert_eq!(client.propose_upgrade(&v2_hash), 4600);
+    let result = client.try_upgrade(&v2_hash);
+    assert_eq!(result, Err(Ok(OracleError::TimelockNotElapsed)));
+
+    // Increases apply at once
+    assert_eq!(client.set_upgrade_delay(&7200), 1000);
+    assert_eq!(client.propose_upgrade(&v2_hash), 8200);
+
+    // Once the old delay has passed the reduction is in force
+    client.set_upgrade_delay(&0);
+    env.ledger().set_timestamp(8200);
+    client.upgrade(&v2_hash);
+    assert_eq!(upgrade_v2::Client::new(&env, &oracle_id).version(), 2);
+}
+
+#[test]
+fn test_timelocked_upgrade_requires_proposal() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let treasury_id = env.register(Treasury, ());
+    let client = TreasuryClient::new(&env, &treasury_id);
+    client.initialize(
+        &Address::generate(&env),
+        &Address::generate(&env),
+        &Address::generate(&env),
+    );
+
+    client.set_upgrade_delay(&3600);
+    let result = client.try_upgrade(&upload_v2(&env));
+    assert_eq!(result, Err(Ok(TreasuryError::NoPendingUpgrade)));
+}
+
+#[test]
+fn test_timelocked_upgrade_rejects_other_hash() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let amm_id = env.register(AMM, ());
+    let client = AMMClient::new(&env, &amm_id);
+    client.initialize(
+        &Address::generate(&env),
+        &Address::generate(&env),
+        &Address::generate(&env),
+        &100_000_000_000u128,
+    );
+
+    client.set_upgrade_delay(&60);
+    client.propose_upgrade(&BytesN::from_array(&env, &[9u8; 32]));
+    env.ledger().set_timestamp(env.ledger().timestamp() + 60);
+
+    let result = client.try_upgrade(&upload_v2(&env));
+    assert_eq!(result, Err(Ok(AmmError::WasmHashMismatch)));
+}
+
+#[test]
+fn test_cancel_upgrade_clears_pending() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let oracle_id = env.register(OracleManager, ());
+    let client = OracleManagerClient::new(&env, &oracle_id);
+    client.initialize(&Address::generate(&env), &2u32);
+
+    client.set_upgrade_delay(&3600);
+    client.propose_upgrade(&upload_v2(&env));
+    client.cancel_upgrade();
+
+    assert!(client.get_pending_upgrade().is_none());
+}
+
+#[test]
+fn test_migrate_rejects_current_schema() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let oracle_id = env.register(OracleManager, ());
+    let client = OracleManagerClient::new(&env, &oracle_id);
+    client.initialize(&Address::generate(&env), &2u32);
+
+    let result = client.try_migrate();
+    assert_eq!(result, Err(Ok(OracleError::SchemaUpToDate)));
+}
+
+#[test]
+#[should_panic]
+fn test_upgrade_requires_admin_auth() {
+    let env = Env::default();
+
+    let oracle_id = env.register(OracleManager, ());
+    let client = OracleManagerClient::new(&env, &oracle_id);
+    env.mock_all_auths();
+    client.initialize(&Address::generate(&env), &2u32);
+    let v2_hash = upload_v2(&env);
+
+    // Drop mocked auths: the admin has not signed
+    env.set_auths(&[]);
+    client.upgrade(&v2_hash);
+}